@@ -0,0 +1,84 @@
+//! Compares the block-scanning kernels on a large log-shaped document.
+//!
+//! Run with `cargo bench --bench block_scan`, and add `--no-default-features` to see the
+//! scalar kernel alone. Every kernel the CPU supports classifies each block of the document
+//! and validates it as UTF-8, and the whole document is then parsed once with the kernel the
+//! parser picks at runtime. Throughput is the best of several rounds, in MB/s.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use rusty_json::extra::JsonParser;
+
+// The kernels are internal to the crate, so the module is compiled into the benchmark as well,
+// where most of it, and the imports of its unit tests, go unused.
+#[allow(dead_code, unused_imports)]
+#[path = "../src/extra/block_scan.rs"]
+mod block_scan;
+
+use block_scan::BlockKernel;
+
+const RECORDS: usize = 50_000;
+const ROUNDS: usize = 10;
+
+/// Builds a JSON array of log records, one per line, with long messages and some non-ASCII text.
+fn log_document() -> String {
+    let levels = ["INFO", "WARN", "ERROR", "DEBUG"];
+    let services = ["gateway", "billing", "search", "auth-proxy"];
+    let mut document = String::from("[\n");
+    for index in 0..RECORDS {
+        if index > 0 {
+            document.push_str(",\n");
+        }
+        let level = levels[index % levels.len()];
+        let service = services[index / 7 % services.len()];
+        document.push_str(&format!(
+            concat!(
+                r#"  {{"ts": "2024-03-{:02}T{:02}:{:02}:{:02}.{:03}Z", "level": "{}", "service": "{}", "#,
+                r#""request_id": "{:016x}", "latency_ms": {}.{}, "status": {}, "#,
+                r#""message": "handled GET /api/v2/items/{} for user café-{} in region eu-west-1 after {} retries", "#,
+                r#""tags": ["http", "{}", "shard-{}"]}}"#,
+            ),
+            index % 28 + 1, index % 24, index % 60, (index * 7) % 60, index % 1000, level, service,
+            (index as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15), index % 900, index % 10, 200 + index % 5 * 100,
+            index, index % 313, index % 4, service, index % 16,
+        ));
+    }
+    document.push_str("\n]\n");
+    document
+}
+
+/// Runs `work` several times and returns the throughput of the fastest round in MB/s.
+fn throughput(bytes: usize, mut work: impl FnMut()) -> f64 {
+    let mut best = Duration::MAX;
+    for _ in 0..ROUNDS {
+        let start = Instant::now();
+        work();
+        best = best.min(start.elapsed());
+    }
+    bytes as f64 / best.as_secs_f64() / 1_000_000.0
+}
+
+fn main() {
+    let document = log_document();
+    let bytes = document.as_bytes();
+    let blocks: Vec<&[u8; 64]> = bytes.chunks_exact(64).map(|block| block.try_into().expect("a full block")).collect();
+    println!("log document: {} records, {:.1} MB", RECORDS, bytes.len() as f64 / 1_000_000.0);
+
+    for kernel in BlockKernel::supported() {
+        let classify = throughput(bytes.len(), || {
+            for block in &blocks {
+                black_box(kernel.classify(black_box(block)));
+            }
+        });
+        let validate = throughput(bytes.len(), || {
+            black_box(kernel.to_str(black_box(bytes))).expect("the document is UTF-8");
+        });
+        println!("{:>8}: classify {:>8.0} MB/s, validate UTF-8 {:>8.0} MB/s", kernel.name(), classify, validate);
+    }
+
+    let parse = throughput(bytes.len(), || {
+        black_box(JsonParser::parse(black_box(&document))).expect("the document is JSON");
+    });
+    println!("   parse: {:>8.0} MB/s", parse);
+}
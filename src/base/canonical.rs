@@ -0,0 +1,91 @@
+//! The RFC 8785 JSON Canonicalization Scheme (JCS), shared by `JsonValue::canonical_bytes`
+//! and the canonical mode of `JsonFormatter`.
+
+use std::cmp::Ordering;
+use std::fmt::{Result as FmtResult, Write};
+
+use thiserror::Error;
+
+use crate::base::escape::{write_string, EscapeOptions};
+use crate::base::json_number::JsonNumber;
+use crate::base::json_value::JsonValue;
+use crate::base::number_format::write_ecmascript;
+use crate::base::traversal::Children;
+
+/// Error returned when a value has no canonical form.
+///
+/// RFC 8785 only covers numbers that are finite IEEE 754 doubles, so a document holding
+/// `NaN` or an infinity cannot be canonicalized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("NaN and infinite numbers cannot be written as canonical JSON")]
+pub struct CanonicalError;
+
+/// Orders keys by their UTF-16 code units, as RFC 8785 requires.
+pub(crate) fn compare_keys(a: &str, b: &str) -> Ordering {
+    a.encode_utf16().cmp(b.encode_utf16())
+}
+
+/// Writes `number` as the IEEE 754 double closest to it, in ECMAScript notation.
+///
+/// # Errors
+///
+/// Fails for `NaN` and infinities, unless `lenient` is set, in which case they are written as
+/// `null`, as ECMAScript's `JSON.stringify` does.
+pub(crate) fn write_canonical_number<W: Write + ?Sized>(out: &mut W, number: &JsonNumber, lenient: bool) -> FmtResult {
+    let value = number.as_f64();
+    match value.is_finite() {
+        true => write_ecmascript(out, value),
+        false if lenient => out.write_str("null"),
+        false => Err(std::fmt::Error),
+    }
+}
+
+/// Writes `value` in canonical form: no whitespace, object keys sorted by `compare_keys`,
+/// numbers as ECMAScript doubles and strings with only the escapes JSON requires.
+///
+/// # Errors
+///
+/// Fails if `out` fails or `value` holds a number that has no canonical form and `lenient`
+/// is not set.
+pub(crate) fn write_canonical<W: Write + ?Sized>(out: &mut W, value: &JsonValue, lenient: bool) -> FmtResult {
+    let mut stack: Vec<(Children, bool)> = Vec::new();
+    let mut next = Some(value);
+    loop {
+        if let Some(value) = next.take() {
+            match value {
+                JsonValue::String(string) => write_string(out, string, EscapeOptions::default())?,
+                JsonValue::Number(number) => write_canonical_number(out, number, lenient)?,
+                JsonValue::Boolean(b) => write!(out, "{}", b)?,
+                JsonValue::Null => out.write_str("null")?,
+                JsonValue::Array(array) => {
+                    out.write_char('[')?;
+                    stack.push((Children::Array(array.iter()), true));
+                }
+                JsonValue::Object(object) => {
+                    out.write_char('{')?;
+                    stack.push((Children::sorted(object, compare_keys), true));
+                }
+            }
+        }
+
+        let Some((children, first)) = stack.last_mut() else {
+            return Ok(());
+        };
+        match children.next() {
+            Some((key, child)) => {
+                if !std::mem::take(first) {
+                    out.write_char(',')?;
+                }
+                if let Some(key) = key {
+                    write_string(out, key, EscapeOptions::default())?;
+                    out.write_char(':')?;
+                }
+                next = Some(child);
+            }
+            None => {
+                out.write_char(children.close())?;
+                stack.pop();
+            }
+        }
+    }
+}
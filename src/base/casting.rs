@@ -4,7 +4,11 @@ use indexmap::IndexMap;
 
 use thiserror::Error;
 
+#[cfg(feature = "arbitrary_precision")]
+use crate::base::json_decimal::JsonDecimal;
+use crate::base::json_number::JsonNumber;
 use crate::base::json_value::JsonValue;
+use crate::base::json_value_ref::JsonValueRef;
 
 /// Errors that can occur during casting operations.
 #[derive(Debug, Error)]
@@ -31,13 +35,7 @@ impl TryFrom<JsonValue> for String {
     fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
         match value {
             JsonValue::String(string) => Ok(string),
-            JsonValue::Number(num) => {
-                if num.fract() == 0.0 {
-                    Ok((num as i64).to_string())
-                } else {
-                    Ok(num.to_string())
-                }
-            },
+            JsonValue::Number(num) => Ok(num.to_string()),
             JsonValue::Null => Ok("null".to_string()),
             JsonValue::Boolean(bool) => Ok(bool.to_string()),
             JsonValue::Object(object) => Ok(object.to_string()),
@@ -52,13 +50,7 @@ impl TryFrom<&JsonValue> for String {
     fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
         match value {
             JsonValue::String(string) => Ok(string.to_string()),
-            JsonValue::Number(num) => {
-                if num.fract() == 0.0 {
-                    Ok((*num as i64).to_string())
-                } else {
-                    Ok(num.to_string())
-                }
-            },
+            JsonValue::Number(num) => Ok(num.to_string()),
             JsonValue::Null => Ok("null".to_string()),
             JsonValue::Boolean(bool) => Ok(bool.to_string()),
             JsonValue::Object(object) => Ok(object.to_string()),
@@ -137,6 +129,58 @@ impl TryFrom<&JsonValue> for JsonValue {
     }
 }
 
+impl TryFrom<&JsonValueRef<'_>> for String {
+    type Error = CastError;
+
+    fn try_from(value: &JsonValueRef<'_>) -> Result<Self, Self::Error> {
+        match value {
+            JsonValueRef::String(string) => Ok(string.to_string()),
+            JsonValueRef::Number(num) => Ok(num.to_string()),
+            JsonValueRef::Null => Ok("null".to_string()),
+            JsonValueRef::Boolean(bool) => Ok(bool.to_string()),
+            other => Ok(other.to_string()),
+        }
+    }
+}
+
+impl TryFrom<&JsonValueRef<'_>> for bool {
+    type Error = CastError;
+
+    fn try_from(value: &JsonValueRef<'_>) -> Result<Self, Self::Error> {
+        match value {
+            JsonValueRef::Boolean(bool) => Ok(*bool),
+            JsonValueRef::Null => Ok(false),
+            _ => Err(CastError::InvalidType),
+        }
+    }
+}
+
+impl<'a, T> TryFrom<&JsonValueRef<'a>> for Vec<T>
+    where
+        T: for<'b> TryFrom<&'b JsonValueRef<'a>, Error = CastError>,
+{
+    type Error = CastError;
+
+    fn try_from(json_value: &JsonValueRef<'a>) -> Result<Self, Self::Error> {
+        match json_value {
+            JsonValueRef::Array(arr) => {
+                arr.iter()
+                    .map(|item| T::try_from(item))
+                    .collect::<Result<Vec<T>, _>>()
+            }
+            JsonValueRef::Null => Ok(Vec::new()),
+            _ => Err(CastError::InvalidType),
+        }
+    }
+}
+
+impl TryFrom<&JsonValueRef<'_>> for JsonValue {
+    type Error = CastError;
+
+    fn try_from(value: &JsonValueRef<'_>) -> Result<Self, Self::Error> {
+        Ok(value.to_json_value())
+    }
+}
 
 macro_rules! register_map {
     ($type:ident) => {
@@ -183,9 +227,79 @@ macro_rules! register_map {
                 }
             }
         }
+
+        impl<'a, V> TryFrom<&JsonValueRef<'a>> for $type<String, V>
+        where
+            V: for<'b> TryFrom<&'b JsonValueRef<'a>, Error = CastError>,
+        {
+            type Error = CastError;
+
+            fn try_from(value: &JsonValueRef<'a>) -> Result<Self, Self::Error> {
+                match value {
+                    JsonValueRef::Object(obj) => {
+                        let mut map = $type::new();
+                        for (k, v) in obj {
+                            map.insert(k.to_string(), V::try_from(v)?);
+                        }
+                        Ok(map)
+                    },
+                    JsonValueRef::Null => Ok($type::new()),
+                    _ => Err(CastError::InvalidType),
+                }
+            }
+        }
     };
 }
 
+/// Returns the exact integer value of a number for the integer casts.
+///
+/// Numbers with a fraction, `NaN` and infinities are `CastError::InvalidType`, and floats
+/// outside the `i128` range are `CastError::OutOfRange`.
+fn integer_of(number: &JsonNumber) -> Result<i128, CastError> {
+    if let Some(value) = number.as_i64() {
+        return Ok(value.into());
+    }
+    if let Some(value) = number.as_u64() {
+        return Ok(value.into());
+    }
+    #[cfg(feature = "arbitrary_precision")]
+    if let Some(decimal) = number.as_decimal() {
+        return decimal.to_i128();
+    }
+    let float = number.as_f64();
+    if float.fract() != 0.0 {
+        return Err(CastError::InvalidType);
+    }
+    let limit = 2f64.powi(127);
+    if !(-limit..limit).contains(&float) {
+        return Err(CastError::OutOfRange);
+    }
+    Ok(float as i128)
+}
+
+/// Returns the exact value of a non-negative number for the unsigned integer casts, with the
+/// errors of `integer_of`. Negative numbers are `CastError::OutOfRange`.
+fn unsigned_of(number: &JsonNumber) -> Result<u128, CastError> {
+    if let Some(value) = number.as_u64() {
+        return Ok(value.into());
+    }
+    if number.as_i64().is_some() {
+        return Err(CastError::OutOfRange);
+    }
+    #[cfg(feature = "arbitrary_precision")]
+    if let Some(decimal) = number.as_decimal() {
+        return decimal.to_u128();
+    }
+    let float = number.as_f64();
+    if float.fract() != 0.0 {
+        return Err(CastError::InvalidType);
+    }
+    if !(0.0..2f64.powi(128)).contains(&float) {
+        return Err(CastError::OutOfRange);
+    }
+    Ok(float as u128)
+}
+
 /// Registering TryFrom for integers
 macro_rules! register_tfi {
     ($type:ty) => {
@@ -195,15 +309,7 @@ macro_rules! register_tfi {
             fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
                 match value {
                     JsonValue::Number(num) => {
-                        if num.fract() != 0.0 {
-                            return Err(CastError::InvalidType);
-                        }
-                        let int_value = num as i64;
-                        if int_value < Self::MIN as i64 || int_value > Self::MAX as i64 {
-                            Err(CastError::OutOfRange)
-                        } else {
-                            Ok(int_value as Self)
-                        }
+                        Self::try_from(integer_of(&num)?).map_err(|_| CastError::OutOfRange)
                     },
                     JsonValue::Null => Ok(0 as Self),
                     _ => Err(CastError::InvalidType),
@@ -217,24 +323,40 @@ macro_rules! register_tfi {
             fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
                 match value {
                     JsonValue::Number(num) => {
-                        if num.fract() != 0.0 {
-                            return Err(CastError::InvalidType);
-                        }
-                        let int_value = *num as i64;
-                        if int_value < Self::MIN as i64 || int_value > Self::MAX as i64 {
-                            Err(CastError::OutOfRange)
-                        } else {
-                            Ok(int_value as Self)
-                        }
+                        Self::try_from(integer_of(num)?).map_err(|_| CastError::OutOfRange)
                     },
                     JsonValue::Null => Ok(0 as Self),
                     _ => Err(CastError::InvalidType),
                 }
             }
         }
+
+        impl TryFrom<&JsonValueRef<'_>> for $type {
+            type Error = CastError;
+
+            fn try_from(value: &JsonValueRef<'_>) -> Result<Self, Self::Error> {
+                match value {
+                    JsonValueRef::Number(num) => {
+                        Self::try_from(integer_of(num)?).map_err(|_| CastError::OutOfRange)
+                    },
+                    JsonValueRef::Null => Ok(0 as Self),
+                    _ => Err(CastError::InvalidType),
+                }
+            }
+        }
     };
 }
 
+/// Returns the value of a number for the float casts. With the `arbitrary_precision` feature,
+/// decimals beyond the range of `f64` are `CastError::OutOfRange`.
+fn float_of(number: &JsonNumber) -> Result<f64, CastError> {
+    #[cfg(feature = "arbitrary_precision")]
+    if let Some(decimal) = number.as_decimal() {
+        return decimal.to_f64();
+    }
+    Ok(number.as_f64())
+}
+
 /// Registering TryFrom for floats
 macro_rules! register_tff {
     ($type:ty) => {
@@ -243,7 +365,7 @@ macro_rules! register_tff {
 
             fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
                 match value {
-                    JsonValue::Number(num) => Ok(num as Self),
+                    JsonValue::Number(num) => Ok(float_of(&num)? as Self),
                     JsonValue::Null => Ok(0.0 as Self),
                     _ => Err(CastError::InvalidType),
                 }
@@ -255,12 +377,24 @@ macro_rules! register_tff {
 
             fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
                 match value {
-                    JsonValue::Number(num) => Ok(*num as Self),
+                    JsonValue::Number(num) => Ok(float_of(num)? as Self),
                     JsonValue::Null => Ok(0.0 as Self),
                     _ => Err(CastError::InvalidType),
                 }
             }
         }
+
+        impl TryFrom<&JsonValueRef<'_>> for $type {
+            type Error = CastError;
+
+            fn try_from(value: &JsonValueRef<'_>) -> Result<Self, Self::Error> {
+                match value {
+                    JsonValueRef::Number(num) => Ok(float_of(num)? as Self),
+                    JsonValueRef::Null => Ok(0.0 as Self),
+                    _ => Err(CastError::InvalidType),
+                }
+            }
+        }
     };
 }
 
@@ -273,15 +407,7 @@ macro_rules! register_tfu {
             fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
                 match value {
                     JsonValue::Number(num) => {
-                        if num.fract() != 0.0 {
-                            return Err(CastError::InvalidType);
-                        }
-                        let int_value = num as u64;
-                        if int_value > Self::MAX as u64 {
-                            Err(CastError::OutOfRange)
-                        } else {
-                            Ok(int_value as Self)
-                        }
+                        Self::try_from(unsigned_of(&num)?).map_err(|_| CastError::OutOfRange)
                     }
                     JsonValue::Null => Ok(0 as Self),
                     _ => Err(CastError::InvalidType),
@@ -295,21 +421,27 @@ macro_rules! register_tfu {
             fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
                 match value {
                     JsonValue::Number(num) => {
-                        if num.fract() != 0.0 {
-                            return Err(CastError::InvalidType);
-                        }
-                        let int_value = *num as u64;
-                        if int_value > Self::MAX as u64 {
-                            Err(CastError::OutOfRange)
-                        } else {
-                            Ok(int_value as Self)
-                        }
+                        Self::try_from(unsigned_of(num)?).map_err(|_| CastError::OutOfRange)
                     }
                     JsonValue::Null => Ok(0 as Self),
                     _ => Err(CastError::InvalidType),
                 }
             }
         }
+
+        impl TryFrom<&JsonValueRef<'_>> for $type {
+            type Error = CastError;
+
+            fn try_from(value: &JsonValueRef<'_>) -> Result<Self, Self::Error> {
+                match value {
+                    JsonValueRef::Number(num) => {
+                        Self::try_from(unsigned_of(num)?).map_err(|_| CastError::OutOfRange)
+                    }
+                    JsonValueRef::Null => Ok(0 as Self),
+                    _ => Err(CastError::InvalidType),
+                }
+            }
+        }
     };
 }
 
@@ -330,6 +462,41 @@ register_tfu!(usize);
 register_tff!(f32);
 register_tff!(f64);
 
+#[cfg(feature = "arbitrary_precision")]
+impl TryFrom<&JsonValue> for JsonDecimal {
+    type Error = CastError;
+
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        match value {
+            JsonValue::Number(num) => num.to_decimal().ok_or(CastError::InvalidType),
+            JsonValue::Null => Ok(JsonDecimal::from(0u128)),
+            _ => Err(CastError::InvalidType),
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary_precision")]
+impl TryFrom<&JsonValueRef<'_>> for JsonDecimal {
+    type Error = CastError;
+
+    fn try_from(value: &JsonValueRef<'_>) -> Result<Self, Self::Error> {
+        match value {
+            JsonValueRef::Number(num) => num.to_decimal().ok_or(CastError::InvalidType),
+            JsonValueRef::Null => Ok(JsonDecimal::from(0u128)),
+            _ => Err(CastError::InvalidType),
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary_precision")]
+impl TryFrom<JsonValue> for JsonDecimal {
+    type Error = CastError;
+
+    fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
+        JsonDecimal::try_from(&value)
+    }
+}
+
 register_map!(HashMap);
 register_map!(IndexMap);
 register_map!(BTreeMap);
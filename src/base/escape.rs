@@ -0,0 +1,116 @@
+//! The string escaping shared by every writer in the crate.
+
+use std::fmt::{Display, Formatter, Result as FmtResult, Write};
+
+/// Controls which characters are written as escape sequences in JSON strings.
+///
+/// Quotes, backslashes and control characters are always escaped. The default options
+/// escape nothing else, so the output is as short as possible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct EscapeOptions {
+    ascii_only: bool,
+    escape_slash: bool,
+    html_safe: bool,
+}
+
+impl EscapeOptions {
+    /// Creates options that only escape what JSON requires.
+    pub fn new() -> Self {
+        EscapeOptions::default()
+    }
+
+    /// Sets whether characters outside ASCII are written as `\uXXXX`, using a surrogate pair
+    /// for characters outside the Basic Multilingual Plane.
+    pub fn with_ascii_only(mut self, ascii_only: bool) -> Self {
+        self.ascii_only = ascii_only;
+        self
+    }
+
+    /// Sets whether `/` is written as `\/`.
+    pub fn with_escaped_slash(mut self, escape_slash: bool) -> Self {
+        self.escape_slash = escape_slash;
+        self
+    }
+
+    /// Sets whether `<`, `>` and `&` are written as `\u003c`, `\u003e` and `\u0026`, so the
+    /// output can be embedded in HTML.
+    pub fn with_html_safe(mut self, html_safe: bool) -> Self {
+        self.html_safe = html_safe;
+        self
+    }
+
+    /// Returns whether characters outside ASCII are escaped.
+    pub fn ascii_only(&self) -> bool {
+        self.ascii_only
+    }
+
+    /// Returns whether `/` is escaped.
+    pub fn escape_slash(&self) -> bool {
+        self.escape_slash
+    }
+
+    /// Returns whether `<`, `>` and `&` are escaped.
+    pub fn html_safe(&self) -> bool {
+        self.html_safe
+    }
+
+    /// Returns the escape sequence for `c`, or `None` if it is written as is.
+    fn escape(&self, c: char) -> Option<Escape> {
+        let escape = match c {
+            '"' => Escape::Short('"'),
+            '\\' => Escape::Short('\\'),
+            '\n' => Escape::Short('n'),
+            '\r' => Escape::Short('r'),
+            '\t' => Escape::Short('t'),
+            '\x08' => Escape::Short('b'),
+            '\x0C' => Escape::Short('f'),
+            '/' if self.escape_slash => Escape::Short('/'),
+            '<' | '>' | '&' if self.html_safe => Escape::Unicode(c),
+            c if c.is_ascii_control() && c != '\x7F' => Escape::Unicode(c),
+            c if !c.is_ascii() && self.ascii_only => Escape::Unicode(c),
+            _ => return None,
+        };
+        Some(escape)
+    }
+}
+
+/// An escape sequence: a backslash and a letter, or one or two `\uXXXX` units.
+enum Escape {
+    Short(char),
+    Unicode(char),
+}
+
+/// Writes `value` as a quoted JSON string.
+pub(crate) fn write_string<W: Write + ?Sized>(out: &mut W, value: &str, options: EscapeOptions) -> FmtResult {
+    out.write_char('"')?;
+    let mut start = 0;
+    for (index, c) in value.char_indices() {
+        let Some(escape) = options.escape(c) else {
+            continue;
+        };
+        out.write_str(&value[start..index])?;
+        start = index + c.len_utf8();
+        match escape {
+            Escape::Short(letter) => {
+                out.write_char('\\')?;
+                out.write_char(letter)?;
+            }
+            Escape::Unicode(c) => {
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    write!(out, "\\u{:04x}", unit)?;
+                }
+            }
+        }
+    }
+    out.write_str(&value[start..])?;
+    out.write_char('"')
+}
+
+/// Displays a string as a quoted and escaped JSON string.
+pub(crate) struct Quoted<'a>(pub(crate) &'a str, pub(crate) EscapeOptions);
+
+impl Display for Quoted<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write_string(f, self.0, self.1)
+    }
+}
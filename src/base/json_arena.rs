@@ -0,0 +1,267 @@
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::ops::Index;
+
+use bumpalo::Bump;
+
+use crate::base::casting::CastError;
+use crate::base::json_array::JsonArray;
+#[cfg(feature = "arbitrary_precision")]
+use crate::base::json_decimal::JsonDecimal;
+use crate::base::json_number::JsonNumber;
+use crate::base::json_object::JsonObject;
+use crate::base::json_value::JsonValue;
+use crate::base::json_value_ref::{rebuild, JsonValueRef, Split};
+
+/// A memory region that documents parsed with `JsonParser::parse_in` are allocated from.
+///
+/// Every string, array and object of a document is placed in the arena with a pointer bump
+/// instead of its own heap allocation, and all of them are freed at once when the arena is
+/// reset or dropped. Reusing one arena for many short-lived documents keeps its memory
+/// around, so that after the first few documents parsing allocates nothing at all.
+///
+/// # Example
+///
+/// ```
+/// use rusty_json::base::JsonArena;
+/// use rusty_json::extra::JsonParser;
+///
+/// let mut arena = JsonArena::new();
+/// for request in [r#"{"id": 1}"#, r#"{"id": 2}"#] {
+///     let value = JsonParser::parse_in(&arena, request).unwrap();
+///     assert!(value["id"].parse::<u32>().unwrap() > 0);
+///     arena.reset(); // Frees the whole document at once
+/// }
+/// ```
+pub struct JsonArena {
+    bump: Bump,
+}
+
+impl JsonArena {
+    /// Creates an empty arena, which allocates its first chunk on first use.
+    pub fn new() -> Self {
+        JsonArena { bump: Bump::new() }
+    }
+
+    /// Creates an arena with room for `capacity` bytes before it has to grow.
+    pub fn with_capacity(capacity: usize) -> Self {
+        JsonArena { bump: Bump::with_capacity(capacity) }
+    }
+
+    /// Returns the number of bytes the arena has reserved, including unused space.
+    pub fn allocated_bytes(&self) -> usize {
+        self.bump.allocated_bytes()
+    }
+
+    /// Frees every document allocated from the arena, keeping its largest chunk for reuse.
+    pub fn reset(&mut self) {
+        self.bump.reset();
+    }
+
+    /// Copies `string` into the arena.
+    pub(crate) fn alloc_str(&self, string: &str) -> &str {
+        self.bump.alloc_str(string)
+    }
+
+    /// Moves the elements of an array into the arena.
+    pub(crate) fn alloc_array<'a, I>(&'a self, items: I) -> &'a [ArenaValue<'a>]
+        where
+            I: ExactSizeIterator<Item = ArenaValue<'a>>,
+    {
+        self.bump.alloc_slice_fill_iter(items)
+    }
+
+    /// Moves the pairs of an object into the arena.
+    pub(crate) fn alloc_object<'a, I>(&'a self, pairs: I) -> &'a [(&'a str, ArenaValue<'a>)]
+        where
+            I: ExactSizeIterator<Item = (&'a str, ArenaValue<'a>)>,
+    {
+        self.bump.alloc_slice_fill_iter(pairs)
+    }
+
+    /// Stores a number in a form that owns no heap memory, copying the text of an exact
+    /// decimal into the arena. The arena never runs destructors, so nothing it holds may need one.
+    pub(crate) fn alloc_number(&self, number: JsonNumber) -> ArenaNumber<'_> {
+        #[cfg(feature = "arbitrary_precision")]
+        if let Some(decimal) = number.as_decimal() {
+            return ArenaNumber { repr: ArenaRepr::Decimal(self.alloc_str(decimal.as_str())) };
+        }
+        let repr = match (number.as_u64(), number.as_i64()) {
+            (Some(value), _) => ArenaRepr::PosInt(value),
+            (None, Some(value)) => ArenaRepr::NegInt(value),
+            (None, None) => ArenaRepr::Float(number.as_f64()),
+        };
+        ArenaNumber { repr }
+    }
+}
+
+impl Default for JsonArena {
+    fn default() -> Self {
+        JsonArena::new()
+    }
+}
+
+impl std::fmt::Debug for JsonArena {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("JsonArena").field("allocated_bytes", &self.allocated_bytes()).finish()
+    }
+}
+
+/// A JSON number allocated in a `JsonArena`.
+///
+/// Integers and floats are stored inline, and exact decimals (with the `arbitrary_precision`
+/// feature) as their text in the arena, so like every other part of an `ArenaValue` it owns
+/// no heap memory. `to_json_number` turns it back into the `JsonNumber` the parser produced.
+#[derive(Debug, Clone, Copy)]
+pub struct ArenaNumber<'a> {
+    repr: ArenaRepr<'a>,
+}
+
+/// The representation of an `ArenaNumber`, mirroring that of `JsonNumber`.
+#[derive(Debug, Clone, Copy)]
+enum ArenaRepr<'a> {
+    PosInt(u64),
+    NegInt(i64),
+    Float(f64),
+    #[cfg_attr(not(feature = "arbitrary_precision"), allow(dead_code))]
+    Decimal(&'a str),
+}
+
+impl ArenaNumber<'_> {
+    /// Copies the number into a `JsonNumber` that no longer depends on the arena.
+    pub fn to_json_number(&self) -> JsonNumber {
+        match self.repr {
+            ArenaRepr::PosInt(value) => JsonNumber::from(value),
+            ArenaRepr::NegInt(value) => JsonNumber::from(value),
+            ArenaRepr::Float(value) => JsonNumber::from_f64(value),
+            #[cfg(feature = "arbitrary_precision")]
+            ArenaRepr::Decimal(text) => JsonNumber::from(JsonDecimal::parse(text).expect("the arena holds valid decimals")),
+            #[cfg(not(feature = "arbitrary_precision"))]
+            ArenaRepr::Decimal(_) => unreachable!("decimals require the arbitrary_precision feature"),
+        }
+    }
+}
+
+impl Display for ArenaNumber<'_> {
+    /// Writes the number in the same form as `JsonNumber`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        self.to_json_number().fmt(f)
+    }
+}
+
+/// A JSON value allocated in a `JsonArena`.
+///
+/// Strings, arrays and objects are slices of the arena, so dropping a value frees nothing;
+/// the memory is released with the arena. Objects keep their pairs in document order, with
+/// repeated keys already resolved by the parser's duplicate-key policy, and are searched
+/// linearly. It offers the same indexing and casting as `JsonValue`, and `to_json_value`
+/// copies it into a `JsonValue` that outlives the arena.
+#[derive(Debug, Clone, Copy)]
+pub enum ArenaValue<'a> {
+    String(&'a str),
+    Number(ArenaNumber<'a>),
+    Null,
+    Boolean(bool),
+    Object(&'a [(&'a str, ArenaValue<'a>)]),
+    Array(&'a [ArenaValue<'a>]),
+}
+
+impl<'a> ArenaValue<'a> {
+    /// Returns the value of `key` if this value is an object that contains it.
+    pub fn get(&self, key: &str) -> Option<&ArenaValue<'a>> {
+        match self {
+            ArenaValue::Object(pairs) => pairs.iter().find(|(name, _)| *name == key).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// Parses the JSON value into a specified type `T`, returning a result.
+    ///
+    /// Any type that `JsonValue::parse` produces can be extracted.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `CastError` if parsing fails or the type conversion is not possible.
+    pub fn parse<T>(&self) -> Result<T, CastError>
+        where
+            T: for<'b> TryFrom<&'b JsonValueRef<'a>, Error = CastError>,
+    {
+        T::try_from(&self.to_value_ref())
+    }
+
+    /// Converts the value into a `JsonValueRef` whose strings and keys borrow from the arena.
+    pub fn to_value_ref(&self) -> JsonValueRef<'a> {
+        rebuild(
+            self,
+            |value| match value {
+                ArenaValue::Array(items) => Split::Array(items.iter().collect()),
+                ArenaValue::Object(pairs) => Split::Object(pairs.iter().map(|(k, v)| (Cow::Borrowed(*k), v)).collect()),
+                ArenaValue::String(string) => Split::Leaf(JsonValueRef::String(Cow::Borrowed(string))),
+                ArenaValue::Number(number) => Split::Leaf(JsonValueRef::Number(number.to_json_number())),
+                ArenaValue::Boolean(b) => Split::Leaf(JsonValueRef::Boolean(*b)),
+                ArenaValue::Null => Split::Leaf(JsonValueRef::Null),
+            },
+            JsonValueRef::Array,
+            |pairs| JsonValueRef::Object(pairs.into_iter().collect()),
+        )
+    }
+
+    /// Copies the value into a `JsonValue` that no longer depends on the arena.
+    pub fn to_json_value(&self) -> JsonValue {
+        rebuild(
+            self,
+            |value| match value {
+                ArenaValue::Array(items) => Split::Array(items.iter().collect()),
+                ArenaValue::Object(pairs) => Split::Object(pairs.iter().map(|(k, v)| (k.to_string(), v)).collect()),
+                ArenaValue::String(string) => Split::Leaf(JsonValue::String(string.to_string())),
+                ArenaValue::Number(number) => Split::Leaf(JsonValue::Number(number.to_json_number())),
+                ArenaValue::Boolean(b) => Split::Leaf(JsonValue::Boolean(*b)),
+                ArenaValue::Null => Split::Leaf(JsonValue::Null),
+            },
+            |items| JsonValue::Array(items.into_iter().collect::<JsonArray>()),
+            |pairs| JsonValue::Object(pairs.into_iter().collect::<JsonObject>()),
+        )
+    }
+}
+
+static NULL: ArenaValue<'static> = ArenaValue::Null;
+
+impl<'a> Index<usize> for ArenaValue<'a> {
+    type Output = ArenaValue<'a>;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        if let ArenaValue::Array(items) = self {
+            if index >= items.len() {
+                panic!("Out of bounds index: {} >= {}", index, items.len());
+            }
+            &items[index]
+        } else {
+            &NULL
+        }
+    }
+}
+
+impl<'a> Index<&str> for ArenaValue<'a> {
+    type Output = ArenaValue<'a>;
+
+    fn index(&self, key: &str) -> &Self::Output {
+        if let ArenaValue::Object(_) = self {
+            self.get(key).expect("Key not found in JsonObject")
+        } else {
+            panic!("Attempted to index a non-object ArenaValue")
+        }
+    }
+}
+
+impl Display for ArenaValue<'_> {
+    /// Writes the value in the same compact form as `JsonValue`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        self.to_value_ref().fmt(f)
+    }
+}
+
+impl From<&ArenaValue<'_>> for JsonValue {
+    fn from(value: &ArenaValue<'_>) -> Self {
+        value.to_json_value()
+    }
+}
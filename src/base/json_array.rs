@@ -5,6 +5,7 @@ use std::slice::Iter;
 use indexmap::IndexSet;
 
 use crate::base::json_value::JsonValue;
+use crate::base::traversal::{clone_tree, dismantle, tree_eq, write_tree};
 
 
 /// Represents a JSON array containing `JsonValue` elements.
@@ -20,17 +21,18 @@ impl Debug for JsonArray {
 impl Clone for JsonArray {
     fn clone(&self) -> Self {
         JsonArray {
-            vec: self.vec.clone(),
+            vec: self.vec.iter().map(clone_tree).collect(),
         }
     }
-
-    fn clone_from(&mut self, source: &Self) {
-        self.vec.clone_from(&source.vec);
-    }
 }
 impl PartialEq for JsonArray {
     fn eq(&self, other: &Self) -> bool {
-        self.vec.eq(&other.vec)
+        self.vec.len() == other.vec.len() && self.vec.iter().zip(&other.vec).all(|(a, b)| tree_eq(a, b))
+    }
+}
+impl Drop for JsonArray {
+    fn drop(&mut self) {
+        dismantle(self.vec.drain(..));
     }
 }
 
@@ -53,6 +55,13 @@ impl JsonArray {
         }
     }
 
+    /// Creates a new, empty `JsonArray` with room for `capacity` elements.
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        JsonArray {
+            vec: Vec::with_capacity(capacity)
+        }
+    }
+
     /// Appends a `JsonValue` element to the end of the array.
     pub fn push<V>(&mut self, v: V)
         where
@@ -130,7 +139,7 @@ impl JsonArray {
     /// # Parameters
     ///
     /// - `predicate`: A closure that takes a reference to a `JsonValue` and returns `true` to include the element
-    ///                in the filtered array, or `false` to exclude it.
+    ///   in the filtered array, or `false` to exclude it.
     ///
     /// # Returns
     ///
@@ -159,7 +168,7 @@ impl JsonArray {
         where
             F: Fn(&JsonValue) -> JsonValue,
     {
-        let mapped_vec: Vec<JsonValue> = self.vec.iter().map(|v| mapper(v)).collect();
+        let mapped_vec: Vec<JsonValue> = self.vec.iter().map(mapper).collect();
 
         JsonArray {
             vec: mapped_vec,
@@ -167,12 +176,18 @@ impl JsonArray {
     }
 }
 
+impl Default for JsonArray {
+    fn default() -> Self {
+        JsonArray::new()
+    }
+}
+
 impl Display for JsonArray {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "[")?;
         let mut iter = self.vec.iter().peekable();
         while let Some(json) = iter.next() {
-            write!(f, "{}", json)?;
+            write_tree(f, json)?;
             if iter.peek().is_some() {
                 write!(f, ", ")?;
             }
@@ -184,7 +199,7 @@ impl Display for JsonArray {
 
 impl From<JsonValue> for JsonArray {
     fn from(value: JsonValue) -> Self {
-        return match value {
+        match value {
             JsonValue::Array(arr) => arr,
             _ => JsonArray::new()
         }
@@ -261,8 +276,8 @@ impl IntoIterator for JsonArray {
     type Item = JsonValue;
     type IntoIter = std::vec::IntoIter<JsonValue>;
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.vec.into_iter()
+    fn into_iter(mut self) -> Self::IntoIter {
+        std::mem::take(&mut self.vec).into_iter()
     }
 }
 
@@ -0,0 +1,438 @@
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::hash::{Hash, Hasher};
+use std::ops::Neg;
+use std::str::FromStr;
+
+use crate::base::casting::CastError;
+
+/// An exact decimal number, available with the `arbitrary_precision` feature.
+///
+/// A decimal keeps the text of the number it was parsed from and writes it back unchanged,
+/// so amounts such as `12345678901234567890.123456789` round-trip exactly. Comparison is
+/// by value, so `1.50` equals `1.5`, and arithmetic never rounds. Arithmetic and conversions
+/// to floats and integers are explicit and report values that do not fit through `CastError`.
+#[derive(Debug, Clone)]
+pub struct JsonDecimal {
+    text: Box<str>,
+    parts: Parts,
+}
+
+/// The value of a decimal: `digits × 10^exponent`, with the digits most significant first.
+///
+/// The digits never start or end with a zero, and zero has no digits and is never negative,
+/// so equal values have equal parts.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Parts {
+    negative: bool,
+    digits: Vec<u8>,
+    exponent: i64,
+}
+
+impl Parts {
+    fn new(negative: bool, mut digits: Vec<u8>, mut exponent: i64) -> Self {
+        let trailing = digits.iter().rev().take_while(|&&digit| digit == 0).count();
+        digits.truncate(digits.len() - trailing);
+        exponent += trailing as i64;
+        let leading = digits.iter().take_while(|&&digit| digit == 0).count();
+        digits.drain(..leading);
+        if digits.is_empty() {
+            return Parts { negative: false, digits, exponent: 0 };
+        }
+        Parts { negative, digits, exponent }
+    }
+
+    /// Parses a number with an optional sign, fraction and exponent. A leading `+`, a
+    /// missing integer part and a trailing `.` are accepted.
+    fn parse(text: &str) -> Option<Self> {
+        let bytes = text.as_bytes();
+        let (negative, bytes) = match bytes.first() {
+            Some(b'-') => (true, &bytes[1..]),
+            Some(b'+') => (false, &bytes[1..]),
+            _ => (false, bytes),
+        };
+        let integer = bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+        let mut digits: Vec<u8> = bytes[..integer].iter().map(|b| b - b'0').collect();
+        let mut rest = &bytes[integer..];
+        let mut exponent = 0i64;
+        if let Some((b'.', fraction)) = rest.split_first() {
+            let count = fraction.iter().take_while(|b| b.is_ascii_digit()).count();
+            digits.extend(fraction[..count].iter().map(|b| b - b'0'));
+            exponent = -(count as i64);
+            rest = &fraction[count..];
+        }
+        if digits.is_empty() {
+            return None;
+        }
+        if let Some((b'e' | b'E', power)) = rest.split_first() {
+            let power: i32 = std::str::from_utf8(power).ok()?.parse().ok()?;
+            exponent += i64::from(power);
+            rest = &[];
+        }
+        if !rest.is_empty() {
+            return None;
+        }
+        Some(Parts::new(negative, digits, exponent))
+    }
+
+    /// Returns the exponent of the most significant digit plus one.
+    fn magnitude(&self) -> i64 {
+        self.digits.len() as i64 + self.exponent
+    }
+
+    /// Compares absolute values.
+    fn cmp_abs(&self, other: &Parts) -> Ordering {
+        match (self.digits.is_empty(), other.digits.is_empty()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            _ => self.magnitude().cmp(&other.magnitude()).then_with(|| self.digits.cmp(&other.digits)),
+        }
+    }
+
+    /// Returns the digits of `self` padded with zeros down to `exponent`.
+    fn aligned(&self, exponent: i64) -> Vec<u8> {
+        let mut digits = self.digits.clone();
+        digits.resize(digits.len() + (self.exponent - exponent) as usize, 0);
+        digits
+    }
+
+    /// Returns the sum, or `None` if aligning the operands would take more than
+    /// `JsonDecimal::MAX_DIGITS` digits.
+    fn add(&self, other: &Parts) -> Option<Parts> {
+        if other.digits.is_empty() {
+            return Some(self.clone());
+        }
+        if self.digits.is_empty() {
+            return Some(other.clone());
+        }
+        let exponent = self.exponent.min(other.exponent);
+        let width = self.magnitude().max(other.magnitude()).checked_sub(exponent)?;
+        if width > JsonDecimal::MAX_DIGITS as i64 {
+            return None;
+        }
+        let (a, b) = (self.aligned(exponent), other.aligned(exponent));
+        if self.negative == other.negative {
+            return Some(Parts::new(self.negative, add_digits(&a, &b), exponent));
+        }
+        Some(match self.cmp_abs(other) {
+            Ordering::Less => Parts::new(other.negative, sub_digits(&b, &a), exponent),
+            _ => Parts::new(self.negative, sub_digits(&a, &b), exponent),
+        })
+    }
+
+    /// Returns the product, or `None` if it would have more than `JsonDecimal::MAX_DIGITS`
+    /// digits.
+    fn mul(&self, other: &Parts) -> Option<Parts> {
+        if self.digits.len() + other.digits.len() > JsonDecimal::MAX_DIGITS {
+            return None;
+        }
+        let exponent = self.exponent.checked_add(other.exponent)?;
+        let mut product = vec![0u32; self.digits.len() + other.digits.len()];
+        for (i, &a) in self.digits.iter().enumerate().rev() {
+            for (j, &b) in other.digits.iter().enumerate().rev() {
+                product[i + j + 1] += u32::from(a) * u32::from(b);
+            }
+        }
+        for index in (1..product.len()).rev() {
+            product[index - 1] += product[index] / 10;
+            product[index] %= 10;
+        }
+        let digits = product.into_iter().map(|digit| digit as u8).collect();
+        Some(Parts::new(self.negative != other.negative, digits, exponent))
+    }
+
+    /// Renders the value as a JSON number, in plain notation unless that would need more
+    /// than 21 zeros of padding.
+    fn render(&self) -> String {
+        if self.digits.is_empty() {
+            return "0".to_string();
+        }
+        let digits: String = self.digits.iter().map(|&digit| char::from(b'0' + digit)).collect();
+        let sign = if self.negative { "-" } else { "" };
+        let magnitude = self.magnitude();
+        if self.exponent >= 0 && magnitude <= 21 {
+            format!("{}{}{}", sign, digits, "0".repeat(self.exponent as usize))
+        } else if self.exponent < 0 && magnitude > 0 {
+            let (integer, fraction) = digits.split_at(magnitude as usize);
+            format!("{}{}.{}", sign, integer, fraction)
+        } else if self.exponent < 0 && magnitude > -6 {
+            format!("{}0.{}{}", sign, "0".repeat(-magnitude as usize), digits)
+        } else {
+            let (first, rest) = digits.split_at(1);
+            let point = if rest.is_empty() { "" } else { "." };
+            format!("{}{}{}{}e{}", sign, first, point, rest, magnitude - 1)
+        }
+    }
+
+    /// Returns the absolute value as an integer.
+    fn to_u128(&self) -> Result<u128, CastError> {
+        if self.exponent < 0 {
+            return Err(CastError::InvalidType);
+        }
+        if self.magnitude() > 39 {
+            return Err(CastError::OutOfRange);
+        }
+        let mut value: u128 = 0;
+        for &digit in &self.digits {
+            value = value.checked_mul(10).and_then(|value| value.checked_add(u128::from(digit))).ok_or(CastError::OutOfRange)?;
+        }
+        value.checked_mul(10u128.pow(self.exponent as u32)).ok_or(CastError::OutOfRange)
+    }
+}
+
+impl JsonDecimal {
+    /// The largest number of digits an arithmetic result may take, counting the zeros that
+    /// align operands of different exponents. It keeps a sum such as `1e2000000000 + 1` from
+    /// allocating billions of digits.
+    pub const MAX_DIGITS: usize = 10_000;
+
+    /// Parses the text of a JSON number.
+    ///
+    /// The text is kept as is when it is valid JSON, and written in a canonical form when it
+    /// uses a lenient spelling such as `+1` or `.5`.
+    ///
+    /// # Returns
+    ///
+    /// The decimal, or `None` if `text` is not a number or its exponent is out of the
+    /// `i32` range.
+    pub fn parse(text: &str) -> Option<Self> {
+        let parts = Parts::parse(text)?;
+        let text = if is_json_number(text) { text.into() } else { parts.render().into() };
+        Some(JsonDecimal { text, parts })
+    }
+
+    fn from_parts(parts: Parts) -> Self {
+        JsonDecimal { text: parts.render().into(), parts }
+    }
+
+    /// Returns the text of the number.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns `true` if the value is zero.
+    pub fn is_zero(&self) -> bool {
+        self.parts.digits.is_empty()
+    }
+
+    /// Returns `true` if the value is less than zero.
+    pub fn is_negative(&self) -> bool {
+        self.parts.negative
+    }
+
+    /// Returns `true` if the value has no fractional part.
+    pub fn is_integer(&self) -> bool {
+        self.parts.exponent >= 0
+    }
+
+    /// Adds two decimals exactly.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CastError::OutOfRange` if the exact sum would take more than `MAX_DIGITS`
+    /// digits, such as `1e100000 + 1`.
+    pub fn checked_add(&self, other: &JsonDecimal) -> Result<JsonDecimal, CastError> {
+        self.parts.add(&other.parts).map(JsonDecimal::from_parts).ok_or(CastError::OutOfRange)
+    }
+
+    /// Subtracts `other` exactly, with the errors of `checked_add`.
+    pub fn checked_sub(&self, other: &JsonDecimal) -> Result<JsonDecimal, CastError> {
+        self.checked_add(&-other.clone())
+    }
+
+    /// Multiplies two decimals exactly.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CastError::OutOfRange` if the exact product would have more than `MAX_DIGITS`
+    /// digits.
+    pub fn checked_mul(&self, other: &JsonDecimal) -> Result<JsonDecimal, CastError> {
+        self.parts.mul(&other.parts).map(JsonDecimal::from_parts).ok_or(CastError::OutOfRange)
+    }
+
+    /// Returns the closest `f64`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CastError::OutOfRange` if the value is beyond the range of `f64`.
+    pub fn to_f64(&self) -> Result<f64, CastError> {
+        match self.text.parse::<f64>() {
+            Ok(value) if value.is_finite() => Ok(value),
+            _ => Err(CastError::OutOfRange),
+        }
+    }
+
+    /// Returns the value as an `i128`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CastError::InvalidType` if the value has a fractional part and
+    /// `CastError::OutOfRange` if it does not fit.
+    pub fn to_i128(&self) -> Result<i128, CastError> {
+        let magnitude = self.parts.to_u128()?;
+        if self.parts.negative {
+            0i128.checked_sub_unsigned(magnitude).ok_or(CastError::OutOfRange)
+        } else {
+            i128::try_from(magnitude).map_err(|_| CastError::OutOfRange)
+        }
+    }
+
+    /// Returns the value as a `u128`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CastError::InvalidType` if the value has a fractional part and
+    /// `CastError::OutOfRange` if it is negative or does not fit.
+    pub fn to_u128(&self) -> Result<u128, CastError> {
+        let magnitude = self.parts.to_u128()?;
+        if self.parts.negative {
+            return Err(CastError::OutOfRange);
+        }
+        Ok(magnitude)
+    }
+
+    /// Returns the value as an `i64`, with the errors of `to_i128`.
+    pub fn to_i64(&self) -> Result<i64, CastError> {
+        i64::try_from(self.to_i128()?).map_err(|_| CastError::OutOfRange)
+    }
+
+    /// Returns the value as a `u64`, with the errors of `to_u128`.
+    pub fn to_u64(&self) -> Result<u64, CastError> {
+        u64::try_from(self.to_u128()?).map_err(|_| CastError::OutOfRange)
+    }
+}
+
+/// Returns `true` if `text` follows the number grammar of RFC 8259.
+fn is_json_number(text: &str) -> bool {
+    let bytes = text.strip_prefix('-').unwrap_or(text).as_bytes();
+    let digits = |bytes: &[u8]| bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+    let integer = digits(bytes);
+    if integer == 0 || (integer > 1 && bytes[0] == b'0') {
+        return false;
+    }
+    let mut rest = &bytes[integer..];
+    if let Some((b'.', fraction)) = rest.split_first() {
+        let count = digits(fraction);
+        if count == 0 {
+            return false;
+        }
+        rest = &fraction[count..];
+    }
+    if let Some((b'e' | b'E', power)) = rest.split_first() {
+        let power = power.strip_prefix(b"+").or_else(|| power.strip_prefix(b"-")).unwrap_or(power);
+        return !power.is_empty() && digits(power) == power.len();
+    }
+    rest.is_empty()
+}
+
+/// Digit-wise sum of two aligned magnitudes.
+fn add_digits(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let mut sum = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0;
+    let (mut a, mut b) = (a.iter().rev(), b.iter().rev());
+    loop {
+        let (x, y) = (a.next(), b.next());
+        if x.is_none() && y.is_none() {
+            break;
+        }
+        let digit = x.unwrap_or(&0) + y.unwrap_or(&0) + carry;
+        sum.push(digit % 10);
+        carry = digit / 10;
+    }
+    sum.push(carry);
+    sum.reverse();
+    sum
+}
+
+/// Digit-wise difference of two aligned magnitudes, where `a` is at least `b`.
+fn sub_digits(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow = 0;
+    let mut b = b.iter().rev();
+    for &x in a.iter().rev() {
+        let y = b.next().unwrap_or(&0) + borrow;
+        borrow = u8::from(x < y);
+        difference.push(x + borrow * 10 - y);
+    }
+    difference.reverse();
+    difference
+}
+
+impl PartialEq for JsonDecimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.parts == other.parts
+    }
+}
+
+impl Eq for JsonDecimal {}
+
+impl Hash for JsonDecimal {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.parts.hash(state);
+    }
+}
+
+impl PartialOrd for JsonDecimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for JsonDecimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.parts.negative, other.parts.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => self.parts.cmp_abs(&other.parts),
+            (true, true) => other.parts.cmp_abs(&self.parts),
+        }
+    }
+}
+
+impl Display for JsonDecimal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str(&self.text)
+    }
+}
+
+impl FromStr for JsonDecimal {
+    type Err = CastError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        JsonDecimal::parse(s).ok_or(CastError::InvalidType)
+    }
+}
+
+impl From<i128> for JsonDecimal {
+    fn from(value: i128) -> Self {
+        JsonDecimal::parse(&value.to_string()).expect("an integer is a valid number")
+    }
+}
+
+impl From<u128> for JsonDecimal {
+    fn from(value: u128) -> Self {
+        JsonDecimal::parse(&value.to_string()).expect("an integer is a valid number")
+    }
+}
+
+impl TryFrom<f64> for JsonDecimal {
+    type Error = CastError;
+
+    /// Converts a finite float through its shortest round-trip representation.
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        if !value.is_finite() {
+            return Err(CastError::InvalidType);
+        }
+        JsonDecimal::parse(&value.to_string()).ok_or(CastError::InvalidType)
+    }
+}
+
+impl Neg for JsonDecimal {
+    type Output = JsonDecimal;
+
+    fn neg(self) -> JsonDecimal {
+        let Parts { negative, digits, exponent } = self.parts;
+        JsonDecimal::from_parts(Parts::new(!negative, digits, exponent))
+    }
+}
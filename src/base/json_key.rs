@@ -0,0 +1,104 @@
+use std::borrow::{Borrow, Cow};
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// The key of a pair in a `JsonObject`.
+///
+/// Keys parsed without interning own their text outright, so each costs the single allocation
+/// a `String` needs. Documents parsed with key interning (see
+/// `JsonParserBuilder::with_key_interning`) instead share one reference-counted copy between
+/// all occurrences of the same key. The shared copy is an `Arc<String>` rather than an
+/// `Arc<str>` so that `JsonObject` can keep handing out `&String` keys; its extra allocation
+/// is paid once per distinct key, not once per occurrence.
+#[derive(Clone)]
+pub(crate) enum JsonKey {
+    /// A key owned by a single pair.
+    Owned(String),
+    /// A key shared through a `KeyInterner`.
+    Shared(Arc<String>),
+}
+
+impl JsonKey {
+    /// Returns the key as a `String`.
+    pub(crate) fn as_string(&self) -> &String {
+        match self {
+            JsonKey::Owned(key) => key,
+            JsonKey::Shared(key) => key,
+        }
+    }
+
+    /// Returns the key as an owned `String`, copying it only if it is shared.
+    pub(crate) fn into_string(self) -> String {
+        match self {
+            JsonKey::Owned(key) => key,
+            JsonKey::Shared(key) => Arc::try_unwrap(key).unwrap_or_else(|shared| shared.as_ref().clone()),
+        }
+    }
+}
+
+impl Default for JsonKey {
+    fn default() -> Self {
+        JsonKey::Owned(String::new())
+    }
+}
+
+impl PartialEq for JsonKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_string() == other.as_string()
+    }
+}
+
+impl Eq for JsonKey {}
+
+impl Hash for JsonKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Must agree with `Borrow<str>`, so hash the text the same way `str` does.
+        self.as_string().as_str().hash(state)
+    }
+}
+
+impl Deref for JsonKey {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_string()
+    }
+}
+
+impl Borrow<str> for JsonKey {
+    fn borrow(&self) -> &str {
+        self.as_string()
+    }
+}
+
+impl From<String> for JsonKey {
+    fn from(key: String) -> Self {
+        JsonKey::Owned(key)
+    }
+}
+
+impl From<Cow<'_, str>> for JsonKey {
+    fn from(key: Cow<'_, str>) -> Self {
+        JsonKey::Owned(key.into_owned())
+    }
+}
+
+/// The table of keys seen in one document, handing out the same `JsonKey` for equal keys.
+#[derive(Default)]
+pub(crate) struct KeyInterner {
+    keys: HashSet<JsonKey>,
+}
+
+impl KeyInterner {
+    /// Returns the shared key equal to `key`, adding it to the table on first sight.
+    pub(crate) fn intern(&mut self, key: Cow<'_, str>) -> JsonKey {
+        if let Some(shared) = self.keys.get(key.as_ref()) {
+            return shared.clone();
+        }
+        let shared = JsonKey::Shared(Arc::new(key.into_owned()));
+        self.keys.insert(shared.clone());
+        shared
+    }
+}
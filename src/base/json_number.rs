@@ -0,0 +1,203 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+#[cfg(feature = "arbitrary_precision")]
+use crate::base::json_decimal::JsonDecimal;
+use crate::base::number_format::{write_number, NumberFormat};
+
+/// A JSON number that keeps integers exact.
+///
+/// Integers that fit in an `i64` or a `u64` are stored as such, so IDs above 2^53 survive
+/// parsing and writing unchanged. Every other number, including an `i128` or `u128` beyond
+/// those ranges, is stored as an `f64`.
+///
+/// With the `arbitrary_precision` feature, parsed numbers that are not stored as integers,
+/// and `i128` or `u128` values beyond those ranges, are stored as an exact `JsonDecimal`
+/// instead, and only floats created with `from_f64` are stored as an `f64`.
+#[derive(Debug, Clone)]
+pub struct JsonNumber {
+    repr: Repr,
+}
+
+/// The representation of a `JsonNumber`. Non-negative integers are always `PosInt`, so
+/// `NegInt` only holds negative values.
+#[derive(Debug, Clone)]
+enum Repr {
+    PosInt(u64),
+    NegInt(i64),
+    Float(f64),
+    #[cfg(feature = "arbitrary_precision")]
+    Decimal(JsonDecimal),
+}
+
+impl JsonNumber {
+    /// Creates a number from a float. The float is kept as is, even if it has no fraction.
+    pub fn from_f64(value: f64) -> Self {
+        JsonNumber { repr: Repr::Float(value) }
+    }
+
+    /// Returns `true` if the number is an integer that fits in an `i64`.
+    pub fn is_i64(&self) -> bool {
+        self.as_i64().is_some()
+    }
+
+    /// Returns `true` if the number is an integer that fits in a `u64`.
+    pub fn is_u64(&self) -> bool {
+        self.as_u64().is_some()
+    }
+
+    /// Returns `true` if the number is stored as a float.
+    pub fn is_f64(&self) -> bool {
+        matches!(self.repr, Repr::Float(_))
+    }
+
+    /// Returns `true` if the number is stored as a decimal.
+    #[cfg(feature = "arbitrary_precision")]
+    pub fn is_decimal(&self) -> bool {
+        matches!(self.repr, Repr::Decimal(_))
+    }
+
+    /// Returns the number as an `i64` if it is stored as an integer in range.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self.repr {
+            Repr::PosInt(value) => i64::try_from(value).ok(),
+            Repr::NegInt(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the number as a `u64` if it is stored as a non-negative integer in range.
+    pub fn as_u64(&self) -> Option<u64> {
+        match self.repr {
+            Repr::PosInt(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the number as an `f64`, rounding numbers that have no exact `f64` form.
+    pub fn as_f64(&self) -> f64 {
+        match &self.repr {
+            Repr::PosInt(value) => *value as f64,
+            Repr::NegInt(value) => *value as f64,
+            Repr::Float(value) => *value,
+            #[cfg(feature = "arbitrary_precision")]
+            Repr::Decimal(value) => value.as_str().parse().unwrap_or(f64::NAN),
+        }
+    }
+
+    /// Returns the number if it is stored as a decimal.
+    #[cfg(feature = "arbitrary_precision")]
+    pub fn as_decimal(&self) -> Option<&JsonDecimal> {
+        match &self.repr {
+            Repr::Decimal(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the exact value of the number as a decimal.
+    ///
+    /// # Returns
+    ///
+    /// The decimal, or `None` for `NaN` and infinities.
+    #[cfg(feature = "arbitrary_precision")]
+    pub fn to_decimal(&self) -> Option<JsonDecimal> {
+        match &self.repr {
+            Repr::PosInt(value) => Some(JsonDecimal::from(u128::from(*value))),
+            Repr::NegInt(value) => Some(JsonDecimal::from(i128::from(*value))),
+            Repr::Float(value) => JsonDecimal::try_from(*value).ok(),
+            Repr::Decimal(value) => Some(value.clone()),
+        }
+    }
+}
+
+impl PartialEq for JsonNumber {
+    /// Integers and decimals are compared exactly. Floats are compared to other numbers as
+    /// `f64`, within `f64::EPSILON`, so `1` equals `1.0`, and `NaN` equals nothing.
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.repr, &other.repr) {
+            (Repr::PosInt(a), Repr::PosInt(b)) => a == b,
+            (Repr::NegInt(a), Repr::NegInt(b)) => a == b,
+            (Repr::PosInt(_), Repr::NegInt(_)) | (Repr::NegInt(_), Repr::PosInt(_)) => false,
+            #[cfg(feature = "arbitrary_precision")]
+            (Repr::Decimal(_), _) | (_, Repr::Decimal(_)) => match (self.to_decimal(), other.to_decimal()) {
+                (Some(a), Some(b)) => a == b,
+                _ => false,
+            },
+            _ => (self.as_f64() - other.as_f64()).abs() < f64::EPSILON,
+        }
+    }
+}
+
+impl Display for JsonNumber {
+    /// Writes the number in the default `NumberFormat`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write_number(f, self, NumberFormat::default())
+    }
+}
+
+/// The representation of an integer beyond the `i64` and `u64` ranges.
+macro_rules! wide {
+    ($value:expr) => {{
+        #[cfg(feature = "arbitrary_precision")]
+        let repr = Repr::Decimal(JsonDecimal::from($value));
+        #[cfg(not(feature = "arbitrary_precision"))]
+        let repr = Repr::Float($value as f64);
+        repr
+    }};
+}
+
+macro_rules! from_signed {
+    ($($type:ty),*) => {
+        $(
+            impl From<$type> for JsonNumber {
+                fn from(value: $type) -> Self {
+                    let repr = match u64::try_from(value) {
+                        Ok(value) => Repr::PosInt(value),
+                        Err(_) => match i64::try_from(value) {
+                            Ok(value) => Repr::NegInt(value),
+                            Err(_) => wide!(value as i128),
+                        },
+                    };
+                    JsonNumber { repr }
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! from_unsigned {
+    ($($type:ty),*) => {
+        $(
+            impl From<$type> for JsonNumber {
+                fn from(value: $type) -> Self {
+                    let repr = match u64::try_from(value) {
+                        Ok(value) => Repr::PosInt(value),
+                        Err(_) => wide!(value as u128),
+                    };
+                    JsonNumber { repr }
+                }
+            }
+        )*
+    };
+}
+
+from_signed!(i8, i16, i32, i64, i128, isize);
+from_unsigned!(u8, u16, u32, u64, u128, usize);
+
+impl From<f32> for JsonNumber {
+    fn from(value: f32) -> Self {
+        JsonNumber::from_f64(value as f64)
+    }
+}
+
+impl From<f64> for JsonNumber {
+    fn from(value: f64) -> Self {
+        JsonNumber::from_f64(value)
+    }
+}
+
+#[cfg(feature = "arbitrary_precision")]
+impl From<JsonDecimal> for JsonNumber {
+    fn from(value: JsonDecimal) -> Self {
+        JsonNumber { repr: Repr::Decimal(value) }
+    }
+}
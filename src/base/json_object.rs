@@ -1,14 +1,105 @@
 use std::cmp::PartialEq;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
 use std::ops::Index;
 use indexmap::IndexMap;
 use indexmap::map::{IntoIter, Iter, Keys, Values};
+use thiserror::Error;
+use crate::base::escape::{EscapeOptions, Quoted};
+use crate::base::json_array::JsonArray;
+use crate::base::json_key::JsonKey;
 use crate::base::json_value::JsonValue;
+use crate::base::traversal::{clone_tree, dismantle, tree_eq, write_tree};
 
 /// Represents a JSON object containing key-value pairs of strings and `JsonValue`s.
 pub struct JsonObject {
-    index_map: IndexMap<String, JsonValue>
+    index_map: IndexMap<JsonKey, JsonValue>
+}
+
+/// What to do when the same key appears more than once while building an object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DuplicateKeyPolicy {
+    /// Keeps the last value, at the position of the first occurrence of the key.
+    #[default]
+    KeepLast,
+    /// Keeps the first value and ignores the later ones.
+    KeepFirst,
+    /// Fails on the first repeated key.
+    Reject,
+    /// Gathers all values of a repeated key, in order, into an array at the position of
+    /// the first occurrence of the key.
+    Collect,
+}
+
+/// Error returned when a repeated key is found under `DuplicateKeyPolicy::Reject`.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Duplicate key {key:?} at pairs {first} and {second}")]
+pub struct DuplicateKeyError {
+    key: String,
+    first: usize,
+    second: usize,
+}
+
+impl DuplicateKeyError {
+    /// Returns the repeated key.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Returns the index of the pair where the key first appeared.
+    pub fn first(&self) -> usize {
+        self.first
+    }
+
+    /// Returns the index of the pair that repeated the key.
+    pub fn second(&self) -> usize {
+        self.second
+    }
+}
+
+/// Fills a `JsonObject` pair by pair, applying a `DuplicateKeyPolicy` to repeated keys.
+pub(crate) struct ObjectBuilder {
+    object: JsonObject,
+    policy: DuplicateKeyPolicy,
+    collected: HashSet<JsonKey>,
+}
+
+impl ObjectBuilder {
+    pub(crate) fn new(policy: DuplicateKeyPolicy) -> Self {
+        ObjectBuilder {
+            object: JsonObject::new(),
+            policy,
+            collected: HashSet::new(),
+        }
+    }
+
+    /// Adds a pair. Under `DuplicateKeyPolicy::Reject` a repeated key is refused and the
+    /// index of its first occurrence among the distinct keys is returned.
+    pub(crate) fn insert(&mut self, key: JsonKey, value: JsonValue) -> Result<(), usize> {
+        let Some(index) = self.object.index_map.get_index_of(&key) else {
+            self.object.index_map.insert(key, value);
+            return Ok(());
+        };
+        let current = &mut self.object.index_map[index];
+        match self.policy {
+            DuplicateKeyPolicy::KeepLast => *current = value,
+            DuplicateKeyPolicy::KeepFirst => {}
+            DuplicateKeyPolicy::Reject => return Err(index),
+            DuplicateKeyPolicy::Collect => match current {
+                JsonValue::Array(values) if self.collected.contains(&key) => values.push(value),
+                _ => {
+                    let first = std::mem::replace(current, JsonValue::Null);
+                    *current = JsonValue::Array(JsonArray::from(vec![first, value]));
+                    self.collected.insert(key);
+                }
+            },
+        }
+        Ok(())
+    }
+
+    pub(crate) fn finish(self) -> JsonObject {
+        self.object
+    }
 }
 
 impl Debug for JsonObject {
@@ -18,25 +109,24 @@ impl Debug for JsonObject {
 }
 impl Clone for JsonObject {
     fn clone(&self) -> Self {
-        let mut cloned_map = IndexMap::new();
+        let mut cloned_map = IndexMap::with_capacity(self.index_map.len());
         for (key, value) in &self.index_map {
-            cloned_map.insert(key.clone(), value.clone());
+            cloned_map.insert(key.clone(), clone_tree(value));
         }
         JsonObject {
             index_map: cloned_map,
         }
     }
-
-    fn clone_from(&mut self, source: &Self) {
-        self.index_map.clear();
-        for (key, value) in &source.index_map {
-            self.index_map.insert(key.clone(), value.clone());
-        }
-    }
 }
 impl PartialEq for JsonObject {
     fn eq(&self, other: &Self) -> bool {
-        self.index_map.eq(&other.index_map)
+        self.index_map.len() == other.index_map.len()
+            && self.index_map.iter().all(|(k, v)| other.index_map.get(k).is_some_and(|o| tree_eq(v, o)))
+    }
+}
+impl Drop for JsonObject {
+    fn drop(&mut self) {
+        dismantle(self.index_map.drain(..).map(|(_, value)| value));
     }
 }
 
@@ -58,6 +148,31 @@ impl JsonObject {
         }
     }
 
+    /// Creates a `JsonObject` from key-value pairs, applying `policy` to repeated keys.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DuplicateKeyError` naming the key and the indexes of both pairs if a key
+    /// is repeated under `DuplicateKeyPolicy::Reject`.
+    pub fn from_pairs<I, K, V>(pairs: I, policy: DuplicateKeyPolicy) -> Result<Self, DuplicateKeyError>
+        where
+            I: IntoIterator<Item = (K, V)>,
+            K: Into<String>,
+            V: Into<JsonValue>,
+    {
+        let mut builder = ObjectBuilder::new(policy);
+        let mut pair_indexes = Vec::new();
+        for (second, (k, v)) in pairs.into_iter().enumerate() {
+            let key = JsonKey::from(k.into());
+            match builder.insert(key.clone(), v.into()) {
+                Ok(()) if pair_indexes.len() < builder.object.len() => pair_indexes.push(second),
+                Ok(()) => {}
+                Err(index) => return Err(DuplicateKeyError { key: key.into_string(), first: pair_indexes[index], second }),
+            }
+        }
+        Ok(builder.finish())
+    }
+
     /// Sets a key-value pair in the `JsonObject`.
     ///
     /// If the key already exists, its corresponding value is overwritten.
@@ -66,7 +181,12 @@ impl JsonObject {
             K: Into<String>,
             V: Into<JsonValue>,
     {
-        self.index_map.insert(k.into(), v.into());
+        self.index_map.insert(JsonKey::from(k.into()), v.into());
+    }
+
+    /// Sets a pair whose key is already a `JsonKey`, keeping it shared.
+    pub(crate) fn insert(&mut self, key: JsonKey, value: JsonValue) {
+        self.index_map.insert(key, value);
     }
 
     /// Retrieves a reference to the value corresponding to the given key.
@@ -76,7 +196,12 @@ impl JsonObject {
         where
             K: Into<String>,
     {
-        self.index_map.get(&k.into())
+        self.index_map.get(k.into().as_str())
+    }
+
+    /// Retrieves a reference to the value of `key` without allocating.
+    pub(crate) fn lookup(&self, key: &str) -> Option<&JsonValue> {
+        self.index_map.get(key)
     }
 
     /// Retrieves a mutable reference to the value corresponding to the given key.
@@ -86,7 +211,7 @@ impl JsonObject {
         where
             K: Into<String>,
     {
-        self.index_map.get_mut(&k.into())
+        self.index_map.get_mut(k.into().as_str())
     }
 
     /// Removes and returns the value corresponding to the given key from the `JsonObject`.
@@ -96,7 +221,7 @@ impl JsonObject {
         where
             K: Into<String>,
     {
-        self.index_map.swap_remove(&k.into());
+        self.index_map.swap_remove(k.into().as_str());
     }
 
     /// Checks if the `JsonObject` contains the specified key.
@@ -104,7 +229,7 @@ impl JsonObject {
         where
             K: Into<String>,
     {
-        self.index_map.contains_key(&k.into())
+        self.index_map.contains_key(k.into().as_str())
     }
 
     /// Checks if the `JsonObject` contains the specified value.
@@ -117,18 +242,23 @@ impl JsonObject {
     }
 
     /// Returns an iterator over the key-value pairs in the `JsonObject`.
-    pub fn iter(&self) -> Iter<String, JsonValue> {
+    pub fn iter(&self) -> ObjectIter<'_> {
+        ObjectIter(self.index_map.iter())
+    }
+
+    /// Returns an iterator over the pairs with their shared keys.
+    pub(crate) fn pairs(&self) -> Iter<'_, JsonKey, JsonValue> {
         self.index_map.iter()
     }
 
     /// Returns an iterator over the keys in the `JsonObject`.
-    pub fn keys(&self) -> Keys<String, JsonValue> {
-        self.index_map.keys()
+    pub fn keys(&self) -> ObjectKeys<'_> {
+        ObjectKeys(self.index_map.keys())
     }
 
     /// Returns an iterator over the values in the `JsonObject`.
-    pub fn values(&self) -> Values<String, JsonValue> {
-        self.index_map.values()
+    pub fn values(&self) -> ObjectValues<'_> {
+        ObjectValues(self.index_map.values())
     }
 
     /// Returns the number of key-value pairs in the `JsonObject`.
@@ -147,9 +277,9 @@ impl JsonObject {
     }
 
     /// Merges another `JsonObject` into this one, replacing existing keys with new values.
-    pub fn merge(&mut self, other: JsonObject) {
-        for (key, value) in other.into_iter() {
-            self.set(key, value);
+    pub fn merge(&mut self, mut other: JsonObject) {
+        for (key, value) in std::mem::take(&mut other.index_map) {
+            self.index_map.insert(key, value);
         }
     }
 
@@ -158,8 +288,8 @@ impl JsonObject {
         where
             P: Fn(&String, &JsonValue) -> bool,
     {
-        let filtered_map: IndexMap<String, JsonValue> =
-            self.index_map.iter().filter(|(k, v)| predicate(k, v)).map(|(k, v)| (k.clone(), v.clone())).collect();
+        let filtered_map: IndexMap<JsonKey, JsonValue> =
+            self.index_map.iter().filter(|(k, v)| predicate(k.as_string(), v)).map(|(k, v)| (k.clone(), v.clone())).collect();
 
         JsonObject {
             index_map: filtered_map,
@@ -183,8 +313,8 @@ impl JsonObject {
         where
             F: Fn(&String, &JsonValue) -> JsonValue,
     {
-        let mapped_map: IndexMap<String, JsonValue> =
-            self.index_map.iter().map(|(k, v)| (k.clone(), mapper(k, v))).collect();
+        let mapped_map: IndexMap<JsonKey, JsonValue> =
+            self.index_map.iter().map(|(k, v)| (k.clone(), mapper(k.as_string(), v))).collect();
 
         JsonObject {
             index_map: mapped_map,
@@ -193,6 +323,12 @@ impl JsonObject {
 
 }
 
+impl Default for JsonObject {
+    fn default() -> Self {
+        JsonObject::new()
+    }
+}
+
 impl<K, V> FromIterator<(K, V)> for JsonObject
     where
         K: Into<String>,
@@ -200,7 +336,7 @@ impl<K, V> FromIterator<(K, V)> for JsonObject
 {
     fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
         let map = iter.into_iter()
-            .map(|(k, v)| (k.into(), v.into()))
+            .map(|(k, v)| (JsonKey::from(k.into()), v.into()))
             .collect();
         JsonObject {
             index_map: map
@@ -210,19 +346,113 @@ impl<K, V> FromIterator<(K, V)> for JsonObject
 
 impl IntoIterator for JsonObject {
     type Item = (String, JsonValue);
-    type IntoIter = IntoIter<String, JsonValue>;
+    type IntoIter = ObjectIntoIter;
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.index_map.into_iter()
+    fn into_iter(mut self) -> Self::IntoIter {
+        ObjectIntoIter(std::mem::take(&mut self.index_map).into_iter())
     }
 }
 
+/// An iterator over the key-value pairs of a `JsonObject`, created by `JsonObject::iter`.
+pub struct ObjectIter<'a>(Iter<'a, JsonKey, JsonValue>);
+
+impl<'a> Iterator for ObjectIter<'a> {
+    type Item = (&'a String, &'a JsonValue);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(key, value)| (key.as_string(), value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for ObjectIter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(key, value)| (key.as_string(), value))
+    }
+}
+
+impl ExactSizeIterator for ObjectIter<'_> {}
+
+/// An iterator over the keys of a `JsonObject`, created by `JsonObject::keys`.
+pub struct ObjectKeys<'a>(Keys<'a, JsonKey, JsonValue>);
+
+impl<'a> Iterator for ObjectKeys<'a> {
+    type Item = &'a String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(JsonKey::as_string)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for ObjectKeys<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(JsonKey::as_string)
+    }
+}
+
+impl ExactSizeIterator for ObjectKeys<'_> {}
+
+/// An iterator over the values of a `JsonObject`, created by `JsonObject::values`.
+pub struct ObjectValues<'a>(Values<'a, JsonKey, JsonValue>);
+
+impl<'a> Iterator for ObjectValues<'a> {
+    type Item = &'a JsonValue;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for ObjectValues<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+impl ExactSizeIterator for ObjectValues<'_> {}
+
+/// An iterator that moves the key-value pairs out of a `JsonObject`, created by
+/// `JsonObject::into_iter`.
+pub struct ObjectIntoIter(IntoIter<JsonKey, JsonValue>);
+
+impl Iterator for ObjectIntoIter {
+    type Item = (String, JsonValue);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(key, value)| (key.into_string(), value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for ObjectIntoIter {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(key, value)| (key.into_string(), value))
+    }
+}
+
+impl ExactSizeIterator for ObjectIntoIter {}
+
 impl Display for JsonObject {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         write!(f, "{{")?;
         let mut iter = self.index_map.iter().peekable();
         while let Some((k, v)) = iter.next() {
-            write!(f, "\"{}\": {}", k, v)?;
+            write!(f, "{}: ", Quoted(k, EscapeOptions::default()))?;
+            write_tree(f, v)?;
             if iter.peek().is_some() {
                 write!(f, ", ")?;
             }
@@ -234,7 +464,7 @@ impl Display for JsonObject {
 
 impl From<JsonValue> for JsonObject {
     fn from(value: JsonValue) -> Self {
-        return match value {
+        match value {
             JsonValue::Object(object) => object,
             _ => JsonObject::new()
         }
@@ -248,7 +478,7 @@ where K: Into<String>,
     fn from(map: HashMap<K, V>) -> Self {
         let mut obj = JsonObject::new();
         for (k, v) in map {
-            obj.set(k.into(), v.into());
+            obj.set(k, v);
         }
         obj
     }
@@ -261,7 +491,7 @@ impl<K, V> From<IndexMap<K, V>> for JsonObject
 {
     fn from(map: IndexMap<K, V>) -> Self {
         let map = map.into_iter()
-            .map(|(k, v)| (k.into(), v.into()))
+            .map(|(k, v)| (JsonKey::from(k.into()), v.into()))
             .collect();
         JsonObject {
             index_map: map
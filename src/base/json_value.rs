@@ -4,15 +4,20 @@ use std::ops::{Index, IndexMut};
 
 use indexmap::{IndexMap, IndexSet};
 
+use crate::base::canonical::{write_canonical, CanonicalError};
 use crate::base::casting::CastError;
 use crate::base::json_array::JsonArray;
+#[cfg(feature = "arbitrary_precision")]
+use crate::base::json_decimal::JsonDecimal;
+use crate::base::json_number::JsonNumber;
 use crate::base::json_object::JsonObject;
+use crate::base::traversal::{clone_tree, tree_eq, write_tree};
 
 /// Represents a JSON value, which can be a string, number, boolean, null, object, or array.
 #[derive(Debug)]
 pub enum JsonValue {
     String(String),
-    Number(f64),
+    Number(JsonNumber),
     Null,
     Boolean(bool),
     Object(JsonObject),
@@ -31,6 +36,22 @@ impl JsonValue {
     {
         T::try_from(self)
     }
+
+    /// Serializes the value in the canonical form of RFC 8785, the JSON Canonicalization
+    /// Scheme, for hashing and signing.
+    ///
+    /// The output has no whitespace, object keys sorted by their UTF-16 code units, every
+    /// number written as the closest IEEE 754 double in ECMAScript notation, and strings
+    /// escaped only where JSON requires it, so equal documents always produce the same bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `CanonicalError` if the value holds `NaN` or an infinity.
+    pub fn canonical_bytes(&self) -> Result<Vec<u8>, CanonicalError> {
+        let mut output = String::new();
+        write_canonical(&mut output, self, false).map_err(|_| CanonicalError)?;
+        Ok(output.into_bytes())
+    }
 }
 
 impl Index<usize> for JsonValue {
@@ -76,7 +97,7 @@ impl IndexMut<&str> for JsonValue {
     fn index_mut(&mut self, key: &str) -> &mut Self::Output {
         if let JsonValue::Object(object) = self {
             // Use `get_mut` to retrieve a mutable reference to the value.
-            object.get_mut(key).expect(&format!("Key {} not found in JsonObject", key))
+            object.get_mut(key).unwrap_or_else(|| panic!("Key {} not found in JsonObject", key))
         } else {
             panic!("Attempted to index a non-object JsonValue")
         }
@@ -102,60 +123,26 @@ impl IntoIterator for JsonValue {
 
 impl Clone for JsonValue {
     fn clone(&self) -> Self {
-        match self {
-            JsonValue::String(s) => JsonValue::String(s.clone()),
-            JsonValue::Number(n) => JsonValue::Number(*n),
-            JsonValue::Null => JsonValue::Null,
-            JsonValue::Boolean(b) => JsonValue::Boolean(*b),
-            JsonValue::Object(obj) => JsonValue::Object(obj.clone()),
-            JsonValue::Array(arr) => JsonValue::Array(arr.clone()),
-        }
+        clone_tree(self)
     }
 
     fn clone_from(&mut self, source: &Self) {
         match (self, source) {
             (JsonValue::String(s1), JsonValue::String(s2)) => s1.clone_from(s2),
-            (JsonValue::Number(n1), JsonValue::Number(n2)) => *n1 = *n2,
-            (JsonValue::Boolean(b1), JsonValue::Boolean(b2)) => *b1 = *b2,
-            (JsonValue::Object(o1), JsonValue::Object(o2)) => o1.clone_from(o2),
-            (JsonValue::Array(a1), JsonValue::Array(a2)) => a1.clone_from(a2),
-            (JsonValue::Null, _) => {}
-            _ => {}
+            (target, source) => *target = clone_tree(source),
         }
     }
 }
 
 impl PartialEq for JsonValue {
     fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
-            (JsonValue::String(s1), JsonValue::String(s2)) => s1 == s2,
-            (JsonValue::Number(n1), JsonValue::Number(n2)) => (n1 - n2).abs() < f64::EPSILON,
-            (JsonValue::Null, JsonValue::Null) => true,
-            (JsonValue::Boolean(b1), JsonValue::Boolean(b2)) => b1 == b2,
-            (JsonValue::Object(o1), JsonValue::Object(o2)) => o1 == o2,
-            (JsonValue::Array(a1), JsonValue::Array(a2)) => a1 == a2,
-            _ => false,
-        }
+        tree_eq(self, other)
     }
 }
 
 impl Display for JsonValue {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            JsonValue::String(string) => write!(f, "\"{}\"", string)?,
-            JsonValue::Number(num) => {
-                if num.fract() == 0.0 {
-                    write!(f, "{}", *num as i64)?
-                } else {
-                    write!(f, "{}", num)?
-                }
-            },
-            JsonValue::Null => write!(f, "null")?,
-            JsonValue::Boolean(b) => write!(f, "{}", b)?,
-            JsonValue::Object(obj) => write!(f, "{}", obj)?,
-            JsonValue::Array(arr) => write!(f, "{}", arr)?,
-        }
-        Ok(())
+        write_tree(f, self)
     }
 }
 
@@ -179,169 +166,182 @@ impl From<&str> for JsonValue {
 
 impl From<f32> for JsonValue {
     fn from(value: f32) -> Self {
-        JsonValue::Number(value as f64)
+        JsonValue::Number(JsonNumber::from(value))
     }
 }
 
 impl From<&f32> for JsonValue {
     fn from(value: &f32) -> Self {
-        JsonValue::Number(*value as f64)
+        JsonValue::Number(JsonNumber::from(*value))
     }
 }
 
 impl From<f64> for JsonValue {
     fn from(value: f64) -> Self {
-        JsonValue::Number(value)
+        JsonValue::Number(JsonNumber::from(value))
     }
 }
 
 impl From<&f64> for JsonValue {
     fn from(value: &f64) -> Self {
-        JsonValue::Number(*value)
+        JsonValue::Number(JsonNumber::from(*value))
     }
 }
 
 impl From<i8> for JsonValue {
     fn from(value: i8) -> Self {
-        JsonValue::Number(value as f64)
+        JsonValue::Number(JsonNumber::from(value))
     }
 }
 
 impl From<&i8> for JsonValue {
     fn from(value: &i8) -> Self {
-        JsonValue::Number(*value as f64)
+        JsonValue::Number(JsonNumber::from(*value))
     }
 }
 
 impl From<i16> for JsonValue {
     fn from(value: i16) -> Self {
-        JsonValue::Number(value as f64)
+        JsonValue::Number(JsonNumber::from(value))
     }
 }
 
 impl From<&i16> for JsonValue {
     fn from(value: &i16) -> Self {
-        JsonValue::Number(*value as f64)
+        JsonValue::Number(JsonNumber::from(*value))
     }
 }
 
 impl From<i32> for JsonValue {
     fn from(value: i32) -> Self {
-        JsonValue::Number(value as f64)
+        JsonValue::Number(JsonNumber::from(value))
     }
 }
 
 impl From<&i32> for JsonValue {
     fn from(value: &i32) -> Self {
-        JsonValue::Number(*value as f64)
+        JsonValue::Number(JsonNumber::from(*value))
     }
 }
 
 impl From<i64> for JsonValue {
     fn from(value: i64) -> Self {
-        JsonValue::Number(value as f64)
+        JsonValue::Number(JsonNumber::from(value))
     }
 }
 
 impl From<&i64> for JsonValue {
     fn from(value: &i64) -> Self {
-        JsonValue::Number(*value as f64)
+        JsonValue::Number(JsonNumber::from(*value))
     }
 }
 
 impl From<i128> for JsonValue {
     fn from(value: i128) -> Self {
-        JsonValue::Number(value as f64)
+        JsonValue::Number(JsonNumber::from(value))
     }
 }
 
 impl From<&i128> for JsonValue {
     fn from(value: &i128) -> Self {
-        JsonValue::Number(*value as f64)
+        JsonValue::Number(JsonNumber::from(*value))
     }
 }
 
 impl From<isize> for JsonValue {
     fn from(value: isize) -> Self {
-        JsonValue::Number(value as f64)
+        JsonValue::Number(JsonNumber::from(value))
     }
 }
 
 impl From<&isize> for JsonValue {
     fn from(value: &isize) -> Self {
-        JsonValue::Number(*value as f64)
+        JsonValue::Number(JsonNumber::from(*value))
     }
 }
 
 impl From<u8> for JsonValue {
     fn from(value: u8) -> Self {
-        JsonValue::Number(value as f64)
+        JsonValue::Number(JsonNumber::from(value))
     }
 }
 
 impl From<&u8> for JsonValue {
     fn from(value: &u8) -> Self {
-        JsonValue::Number(*value as f64)
+        JsonValue::Number(JsonNumber::from(*value))
     }
 }
 
 impl From<u16> for JsonValue {
     fn from(value: u16) -> Self {
-        JsonValue::Number(value as f64)
+        JsonValue::Number(JsonNumber::from(value))
     }
 }
 
 impl From<&u16> for JsonValue {
     fn from(value: &u16) -> Self {
-        JsonValue::Number(*value as f64)
+        JsonValue::Number(JsonNumber::from(*value))
     }
 }
 
 impl From<u32> for JsonValue {
     fn from(value: u32) -> Self {
-        JsonValue::Number(value as f64)
+        JsonValue::Number(JsonNumber::from(value))
     }
 }
 
 impl From<&u32> for JsonValue {
     fn from(value: &u32) -> Self {
-        JsonValue::Number(*value as f64)
+        JsonValue::Number(JsonNumber::from(*value))
     }
 }
 
 impl From<u64> for JsonValue {
     fn from(value: u64) -> Self {
-        JsonValue::Number(value as f64)
+        JsonValue::Number(JsonNumber::from(value))
     }
 }
 
 impl From<&u64> for JsonValue {
     fn from(value: &u64) -> Self {
-        JsonValue::Number(*value as f64)
+        JsonValue::Number(JsonNumber::from(*value))
     }
 }
 
 impl From<u128> for JsonValue {
     fn from(value: u128) -> Self {
-        JsonValue::Number(value as f64)
+        JsonValue::Number(JsonNumber::from(value))
     }
 }
 
 impl From<&u128> for JsonValue {
     fn from(value: &u128) -> Self {
-        JsonValue::Number(*value as f64)
+        JsonValue::Number(JsonNumber::from(*value))
     }
 }
 
 impl From<usize> for JsonValue {
     fn from(value: usize) -> Self {
-        JsonValue::Number(value as f64)
+        JsonValue::Number(JsonNumber::from(value))
     }
 }
 
 impl From<&usize> for JsonValue {
     fn from(value: &usize) -> Self {
-        JsonValue::Number(*value as f64)
+        JsonValue::Number(JsonNumber::from(*value))
+    }
+}
+
+impl From<JsonNumber> for JsonValue {
+    fn from(value: JsonNumber) -> Self {
+        JsonValue::Number(value)
+    }
+}
+
+#[cfg(feature = "arbitrary_precision")]
+impl From<JsonDecimal> for JsonValue {
+    fn from(value: JsonDecimal) -> Self {
+        JsonValue::Number(JsonNumber::from(value))
     }
 }
 
@@ -353,7 +353,7 @@ impl From<bool> for JsonValue {
 
 impl From<&bool> for JsonValue {
     fn from(value: &bool) -> Self {
-        JsonValue::Boolean(value.clone())
+        JsonValue::Boolean(*value)
     }
 }
 
@@ -0,0 +1,378 @@
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::ops::Index;
+
+use indexmap::IndexMap;
+
+use crate::base::casting::CastError;
+use crate::base::escape::{EscapeOptions, Quoted};
+use crate::base::json_array::JsonArray;
+use crate::base::json_number::JsonNumber;
+use crate::base::json_object::{DuplicateKeyPolicy, JsonObject};
+use crate::base::json_value::JsonValue;
+
+/// A JSON value whose strings and keys may borrow from the parsed text.
+///
+/// Produced by `JsonParser::parse_borrowed`: strings and keys without escape sequences point
+/// into the input instead of being copied, and only those that had to be unescaped are
+/// owned. It offers the same indexing and casting as `JsonValue`, and `into_owned` turns it
+/// into a `JsonValue` that no longer borrows the input.
+#[derive(Debug)]
+pub enum JsonValueRef<'a> {
+    String(Cow<'a, str>),
+    Number(JsonNumber),
+    Null,
+    Boolean(bool),
+    Object(IndexMap<Cow<'a, str>, JsonValueRef<'a>>),
+    Array(Vec<JsonValueRef<'a>>),
+}
+
+impl<'a> JsonValueRef<'a> {
+    /// Parses the JSON value into a specified type `T`, returning a result.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `CastError` if parsing fails or the type conversion is not possible.
+    pub fn parse<T>(&self) -> Result<T, CastError>
+        where
+            T: for<'b> TryFrom<&'b JsonValueRef<'a>, Error = CastError>,
+    {
+        T::try_from(self)
+    }
+
+    /// Converts the value into a `JsonValue`, moving owned strings and copying borrowed ones.
+    pub fn into_owned(self) -> JsonValue {
+        rebuild(
+            self,
+            |mut value| match &mut value {
+                JsonValueRef::Array(items) => Split::Array(std::mem::take(items)),
+                JsonValueRef::Object(pairs) => Split::Object(std::mem::take(pairs).into_iter().map(|(k, v)| (k.into_owned(), v)).collect()),
+                JsonValueRef::String(string) => Split::Leaf(JsonValue::String(std::mem::take(string).into_owned())),
+                JsonValueRef::Number(number) => Split::Leaf(JsonValue::Number(number.clone())),
+                JsonValueRef::Boolean(b) => Split::Leaf(JsonValue::Boolean(*b)),
+                JsonValueRef::Null => Split::Leaf(JsonValue::Null),
+            },
+            |items| JsonValue::Array(items.into_iter().collect::<JsonArray>()),
+            |pairs| JsonValue::Object(pairs.into_iter().collect::<JsonObject>()),
+        )
+    }
+
+    /// Moves the string out of a `String` value without copying it.
+    ///
+    /// `JsonValueRef` implements `Drop`, so its payloads cannot be moved out by a `match`;
+    /// this and `into_array` and `into_object` take them instead.
+    ///
+    /// # Returns
+    ///
+    /// The borrowed or owned string, or `None` if the value is not a string.
+    pub fn into_cow(mut self) -> Option<Cow<'a, str>> {
+        match &mut self {
+            JsonValueRef::String(string) => Some(std::mem::take(string)),
+            _ => None,
+        }
+    }
+
+    /// Moves the elements out of an `Array` value without copying them.
+    ///
+    /// # Returns
+    ///
+    /// The elements, or `None` if the value is not an array.
+    pub fn into_array(mut self) -> Option<Vec<JsonValueRef<'a>>> {
+        match &mut self {
+            JsonValueRef::Array(items) => Some(std::mem::take(items)),
+            _ => None,
+        }
+    }
+
+    /// Moves the entries out of an `Object` value without copying them.
+    ///
+    /// # Returns
+    ///
+    /// The entries in document order, or `None` if the value is not an object.
+    pub fn into_object(mut self) -> Option<IndexMap<Cow<'a, str>, JsonValueRef<'a>>> {
+        match &mut self {
+            JsonValueRef::Object(pairs) => Some(std::mem::take(pairs)),
+            _ => None,
+        }
+    }
+
+    /// Copies the value into a `JsonValue`, leaving this one untouched.
+    pub fn to_json_value(&self) -> JsonValue {
+        rebuild(
+            self,
+            |value| match value {
+                JsonValueRef::Array(items) => Split::Array(items.iter().collect()),
+                JsonValueRef::Object(pairs) => Split::Object(pairs.iter().map(|(k, v)| (k.to_string(), v)).collect()),
+                JsonValueRef::String(string) => Split::Leaf(JsonValue::String(string.to_string())),
+                JsonValueRef::Number(number) => Split::Leaf(JsonValue::Number(number.clone())),
+                JsonValueRef::Boolean(b) => Split::Leaf(JsonValue::Boolean(*b)),
+                JsonValueRef::Null => Split::Leaf(JsonValue::Null),
+            },
+            |items| JsonValue::Array(items.into_iter().collect::<JsonArray>()),
+            |pairs| JsonValue::Object(pairs.into_iter().collect::<JsonObject>()),
+        )
+    }
+}
+
+/// What `rebuild` does with a node: keep it as a finished value, or descend into children.
+pub(super) enum Split<S, K, T> {
+    Leaf(T),
+    Array(Vec<S>),
+    Object(Vec<(K, S)>),
+}
+
+/// A container being rebuilt, with the key it belongs to in its parent.
+struct Frame<S, K, T> {
+    key: Option<K>,
+    object: bool,
+    pending: std::vec::IntoIter<(Option<K>, S)>,
+    done: Vec<(Option<K>, T)>,
+}
+
+/// Converts a tree bottom-up without recursion, so trees of any depth can be converted.
+///
+/// `split` turns a node into a finished value or into its children, and `array` and `object`
+/// assemble the converted children of a container.
+pub(super) fn rebuild<S, K, T>(
+    root: S,
+    mut split: impl FnMut(S) -> Split<S, K, T>,
+    array: impl Fn(Vec<T>) -> T,
+    object: impl Fn(Vec<(K, T)>) -> T,
+) -> T {
+    let mut stack: Vec<Frame<S, K, T>> = Vec::new();
+    let mut next = (None, root);
+    loop {
+        let (key, source) = next;
+        let mut finished = match split(source) {
+            Split::Leaf(value) => Some((key, value)),
+            Split::Array(items) => {
+                let pending: Vec<_> = items.into_iter().map(|item| (None, item)).collect();
+                stack.push(Frame { key, object: false, pending: pending.into_iter(), done: Vec::new() });
+                None
+            }
+            Split::Object(pairs) => {
+                let pending: Vec<_> = pairs.into_iter().map(|(k, v)| (Some(k), v)).collect();
+                stack.push(Frame { key, object: true, pending: pending.into_iter(), done: Vec::new() });
+                None
+            }
+        };
+        loop {
+            let Some(top) = stack.last_mut() else {
+                return finished.expect("the root is finished last").1;
+            };
+            if let Some(child) = finished.take() {
+                top.done.push(child);
+            }
+            if let Some(child) = top.pending.next() {
+                next = child;
+                break;
+            }
+            let frame = stack.pop().expect("the stack is not empty");
+            let value = if frame.object {
+                object(frame.done.into_iter().filter_map(|(k, v)| Some((k?, v))).collect())
+            } else {
+                array(frame.done.into_iter().map(|(_, v)| v).collect())
+            };
+            finished = Some((frame.key, value));
+        }
+    }
+}
+
+impl Clone for JsonValueRef<'_> {
+    fn clone(&self) -> Self {
+        rebuild(
+            self,
+            |value| match value {
+                JsonValueRef::Array(items) => Split::Array(items.iter().collect()),
+                JsonValueRef::Object(pairs) => Split::Object(pairs.iter().map(|(k, v)| (k.clone(), v)).collect()),
+                JsonValueRef::String(string) => Split::Leaf(JsonValueRef::String(string.clone())),
+                JsonValueRef::Number(number) => Split::Leaf(JsonValueRef::Number(number.clone())),
+                JsonValueRef::Boolean(b) => Split::Leaf(JsonValueRef::Boolean(*b)),
+                JsonValueRef::Null => Split::Leaf(JsonValueRef::Null),
+            },
+            JsonValueRef::Array,
+            |pairs| JsonValueRef::Object(pairs.into_iter().collect()),
+        )
+    }
+}
+
+impl Drop for JsonValueRef<'_> {
+    /// Moves the children of nested containers onto a heap allocated stack, so that no drop
+    /// call recurses into a deeper level.
+    fn drop(&mut self) {
+        fn take_children<'a>(value: &mut JsonValueRef<'a>, pending: &mut Vec<JsonValueRef<'a>>) {
+            match value {
+                JsonValueRef::Array(items) => pending.append(items),
+                JsonValueRef::Object(pairs) => pending.extend(pairs.drain(..).map(|(_, v)| v)),
+                _ => {}
+            }
+        }
+
+        let mut pending = Vec::new();
+        take_children(self, &mut pending);
+        while let Some(mut value) = pending.pop() {
+            take_children(&mut value, &mut pending);
+        }
+    }
+}
+
+impl PartialEq for JsonValueRef<'_> {
+    /// Compares two trees, treating objects as equal regardless of key order.
+    fn eq(&self, other: &Self) -> bool {
+        let mut pending = vec![(self, other)];
+        while let Some(pair) = pending.pop() {
+            match pair {
+                (JsonValueRef::String(s1), JsonValueRef::String(s2)) if s1 == s2 => {}
+                (JsonValueRef::Number(n1), JsonValueRef::Number(n2)) if n1 == n2 => {}
+                (JsonValueRef::Boolean(b1), JsonValueRef::Boolean(b2)) if b1 == b2 => {}
+                (JsonValueRef::Null, JsonValueRef::Null) => {}
+                (JsonValueRef::Array(a1), JsonValueRef::Array(a2)) if a1.len() == a2.len() => {
+                    pending.extend(a1.iter().zip(a2.iter()));
+                }
+                (JsonValueRef::Object(o1), JsonValueRef::Object(o2)) if o1.len() == o2.len() => {
+                    for (key, value) in o1 {
+                        match o2.get(key) {
+                            Some(other) => pending.push((value, other)),
+                            None => return false,
+                        }
+                    }
+                }
+                _ => return false,
+            }
+        }
+        true
+    }
+}
+
+impl Display for JsonValueRef<'_> {
+    /// Writes the value in the same compact form as `JsonValue`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        enum Children<'v, 'a> {
+            Array(std::slice::Iter<'v, JsonValueRef<'a>>),
+            Object(indexmap::map::Iter<'v, Cow<'a, str>, JsonValueRef<'a>>),
+        }
+
+        let mut stack: Vec<(Children, bool)> = Vec::new();
+        let mut next = Some(self);
+        loop {
+            if let Some(value) = next.take() {
+                match value {
+                    JsonValueRef::String(string) => write!(f, "{}", Quoted(string, EscapeOptions::default()))?,
+                    JsonValueRef::Number(num) => write!(f, "{}", num)?,
+                    JsonValueRef::Null => write!(f, "null")?,
+                    JsonValueRef::Boolean(b) => write!(f, "{}", b)?,
+                    JsonValueRef::Array(items) => {
+                        write!(f, "[")?;
+                        stack.push((Children::Array(items.iter()), true));
+                    }
+                    JsonValueRef::Object(pairs) => {
+                        write!(f, "{{")?;
+                        stack.push((Children::Object(pairs.iter()), true));
+                    }
+                }
+            }
+
+            let Some((children, first)) = stack.last_mut() else {
+                return Ok(());
+            };
+            let child = match children {
+                Children::Array(iter) => iter.next().map(|value| (None, value)),
+                Children::Object(iter) => iter.next().map(|(key, value)| (Some(key), value)),
+            };
+            match child {
+                Some((key, child)) => {
+                    if !std::mem::take(first) {
+                        write!(f, ", ")?;
+                    }
+                    if let Some(key) = key {
+                        write!(f, "{}: ", Quoted(key, EscapeOptions::default()))?;
+                    }
+                    next = Some(child);
+                }
+                None => {
+                    match children {
+                        Children::Array(_) => write!(f, "]")?,
+                        Children::Object(_) => write!(f, "}}")?,
+                    }
+                    stack.pop();
+                }
+            }
+        }
+    }
+}
+
+/// The value indexing a non-array with a number returns.
+static NULL: JsonValueRef<'static> = JsonValueRef::Null;
+
+impl<'a> Index<usize> for JsonValueRef<'a> {
+    type Output = JsonValueRef<'a>;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        if let JsonValueRef::Array(items) = self {
+            if index >= items.len() {
+                panic!("Out of bounds index: {} >= {}", index, items.len());
+            }
+            &items[index]
+        } else {
+            &NULL
+        }
+    }
+}
+
+impl<'a> Index<&str> for JsonValueRef<'a> {
+    type Output = JsonValueRef<'a>;
+
+    fn index(&self, key: &str) -> &Self::Output {
+        if let JsonValueRef::Object(pairs) = self {
+            pairs.get(key).expect("Key not found in JsonObject")
+        } else {
+            panic!("Attempted to index a non-object JsonValueRef")
+        }
+    }
+}
+
+impl<'a> From<JsonValueRef<'a>> for JsonValue {
+    fn from(value: JsonValueRef<'a>) -> Self {
+        value.into_owned()
+    }
+}
+
+/// Fills the map of a `JsonValueRef::Object` pair by pair, applying a `DuplicateKeyPolicy` to
+/// repeated keys the same way `JsonObject::from_pairs` does.
+pub(crate) struct ObjectRefBuilder<'a> {
+    pairs: IndexMap<Cow<'a, str>, JsonValueRef<'a>>,
+    policy: DuplicateKeyPolicy,
+    collected: HashSet<Cow<'a, str>>,
+}
+
+impl<'a> ObjectRefBuilder<'a> {
+    pub(crate) fn new(policy: DuplicateKeyPolicy) -> Self {
+        ObjectRefBuilder { pairs: IndexMap::new(), policy, collected: HashSet::new() }
+    }
+
+    /// Adds a pair. Repeated keys under `DuplicateKeyPolicy::Reject` are rejected by the
+    /// reader before they get here, and are otherwise treated as `KeepLast`.
+    pub(crate) fn insert(&mut self, key: Cow<'a, str>, value: JsonValueRef<'a>) {
+        let Some(current) = self.pairs.get_mut(&key) else {
+            self.pairs.insert(key, value);
+            return;
+        };
+        match self.policy {
+            DuplicateKeyPolicy::KeepLast | DuplicateKeyPolicy::Reject => *current = value,
+            DuplicateKeyPolicy::KeepFirst => {}
+            DuplicateKeyPolicy::Collect => match current {
+                JsonValueRef::Array(values) if self.collected.contains(&key) => values.push(value),
+                _ => {
+                    let first = std::mem::replace(current, JsonValueRef::Null);
+                    *current = JsonValueRef::Array(vec![first, value]);
+                    self.collected.insert(key);
+                }
+            },
+        }
+    }
+
+    pub(crate) fn finish(self) -> JsonValueRef<'a> {
+        JsonValueRef::Object(self.pairs)
+    }
+}
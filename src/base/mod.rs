@@ -1,8 +1,39 @@
 mod json_object;
 mod json_array;
+mod json_key;
 mod json_value;
+mod json_value_ref;
+#[cfg(feature = "arena")]
+mod json_arena;
+mod json_number;
+#[cfg(feature = "arbitrary_precision")]
+mod json_decimal;
 pub mod casting;
+mod traversal;
+mod escape;
+mod number_format;
+mod canonical;
 
 pub use json_value::JsonValue;
+pub use json_value_ref::JsonValueRef;
+pub(crate) use json_value_ref::ObjectRefBuilder;
+#[cfg(feature = "arena")]
+pub use json_arena::{ArenaNumber, ArenaValue, JsonArena};
+pub use json_number::JsonNumber;
+#[cfg(feature = "arbitrary_precision")]
+pub use json_decimal::JsonDecimal;
 pub use json_object::JsonObject;
-pub use json_array::JsonArray;
\ No newline at end of file
+pub use json_object::DuplicateKeyPolicy;
+pub use json_object::DuplicateKeyError;
+pub use json_object::{ObjectIntoIter, ObjectIter, ObjectKeys, ObjectValues};
+pub(crate) use json_object::ObjectBuilder;
+pub use json_array::JsonArray;
+pub(crate) use json_key::JsonKey;
+pub(crate) use json_key::KeyInterner;
+pub use escape::EscapeOptions;
+pub(crate) use escape::Quoted;
+pub use number_format::NumberFormat;
+pub(crate) use number_format::Formatted;
+pub use canonical::CanonicalError;
+pub(crate) use canonical::write_canonical;
+pub(crate) use traversal::Children;
\ No newline at end of file
@@ -0,0 +1,177 @@
+//! The number writer shared by every writer in the crate.
+
+use std::fmt::{Display, Formatter, Result as FmtResult, Write};
+
+use crate::base::json_number::JsonNumber;
+
+/// Controls how floats are written.
+///
+/// Floats are written with the fewest significant digits that parse back to the same `f64`,
+/// bit for bit. A float `d.ddd × 10^e` is written in plain notation, such as `1500` or
+/// `0.0015`, when `lower_exponent <= e < upper_exponent`, and in exponent notation, such
+/// as `1.5e300`, otherwise. The default thresholds, `-6` and `21`, match
+/// JavaScript's `Number.prototype.toString`.
+///
+/// Integers are always written in full. `NaN` and infinities, which JSON cannot represent,
+/// are written as `NaN`, `inf` and `-inf`, the spellings `JsonParser::parse` accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NumberFormat {
+    lower_exponent: i32,
+    upper_exponent: i32,
+}
+
+impl NumberFormat {
+    /// The default smallest exponent written in plain notation.
+    pub const DEFAULT_LOWER_EXPONENT: i32 = -6;
+
+    /// The default smallest exponent written in exponent notation.
+    pub const DEFAULT_UPPER_EXPONENT: i32 = 21;
+
+    /// Creates a format with the default thresholds.
+    pub fn new() -> Self {
+        NumberFormat {
+            lower_exponent: NumberFormat::DEFAULT_LOWER_EXPONENT,
+            upper_exponent: NumberFormat::DEFAULT_UPPER_EXPONENT,
+        }
+    }
+
+    /// Sets the exponents between which floats are written in plain notation.
+    ///
+    /// # Arguments
+    ///
+    /// * `lower` - The smallest exponent written in plain notation, `i32::MIN` to never use
+    ///   exponent notation for small numbers.
+    /// * `upper` - The smallest exponent written in exponent notation, `i32::MAX` to never use
+    ///   exponent notation for large numbers.
+    pub fn with_exponent_thresholds(mut self, lower: i32, upper: i32) -> Self {
+        self.lower_exponent = lower;
+        self.upper_exponent = upper;
+        self
+    }
+
+    /// Returns the smallest exponent written in plain notation.
+    pub fn lower_exponent(&self) -> i32 {
+        self.lower_exponent
+    }
+
+    /// Returns the smallest exponent written in exponent notation.
+    pub fn upper_exponent(&self) -> i32 {
+        self.upper_exponent
+    }
+
+    /// Writes a float with the shortest digits that round-trip.
+    fn write_float<W: Write + ?Sized>(&self, out: &mut W, value: f64) -> FmtResult {
+        if value.is_nan() {
+            return out.write_str("NaN");
+        }
+        if value.is_sign_negative() {
+            out.write_char('-')?;
+        }
+        if value.is_infinite() {
+            return out.write_str("inf");
+        }
+        let (digits, exponent) = shortest_digits(value.abs());
+        self.write_digits(out, &digits, exponent, false)
+    }
+
+    /// Writes the significant `digits` of a number `d.ddd × 10^exponent` in plain or exponent
+    /// notation, marking positive exponents with `+` if `plus_sign` is set.
+    fn write_digits<W: Write + ?Sized>(&self, out: &mut W, digits: &str, exponent: i32, plus_sign: bool) -> FmtResult {
+        if exponent < self.lower_exponent || exponent >= self.upper_exponent {
+            let (first, rest) = digits.split_at(1);
+            let point = if rest.is_empty() { "" } else { "." };
+            let sign = if plus_sign && exponent > 0 { "+" } else { "" };
+            return write!(out, "{}{}{}e{}{}", first, point, rest, sign, exponent);
+        }
+        if exponent < 0 {
+            return write!(out, "0.{}{}", "0".repeat((-exponent - 1) as usize), digits);
+        }
+        let integer = exponent as usize + 1;
+        if digits.len() <= integer {
+            write!(out, "{}{}", digits, "0".repeat(integer - digits.len()))
+        } else {
+            write!(out, "{}.{}", &digits[..integer], &digits[integer..])
+        }
+    }
+}
+
+/// Returns the shortest significant digits that round-trip to `value`, with the decimal
+/// exponent of the first digit.
+fn shortest_digits(value: f64) -> (String, i32) {
+    // `LowerExp` without a precision produces the shortest round-trip digits.
+    let shortest = format!("{:e}", value);
+    let exponent = shortest.split_once('e').map_or(0, |(_, exponent)| exponent.parse().unwrap_or(0));
+    (mantissa_digits(&shortest), exponent)
+}
+
+/// Picks the even candidate when a positive `value` lies exactly halfway between two shortest
+/// digit strings. ECMAScript requires the even one, while `LowerExp` always rounds up.
+fn round_half_even(value: f64, digits: String, exponent: i32) -> String {
+    let last = digits.as_bytes()[digits.len() - 1];
+    if digits.len() < 2 || last % 2 == 0 {
+        return digits;
+    }
+    let lower = format!("{}{}", &digits[..digits.len() - 1], (last - 1) as char);
+
+    // A tie needs the digit after `lower` to be a 5, which the rounded expansion shows
+    // cheaply, and every digit after that to be 0, which only the exact expansion shows.
+    let halfway = format!("{}5", lower);
+    let rounded = format!("{:.*e}", lower.len(), value);
+    if mantissa_digits(&rounded) != halfway {
+        return digits;
+    }
+    // No `f64` has more than 767 significant digits.
+    let exact = mantissa_digits(&format!("{:.767e}", value));
+    let tie = exact.starts_with(&halfway) && exact[halfway.len()..].bytes().all(|digit| digit == b'0');
+    let round_trips = format!("{}.{}e{}", &lower[..1], &lower[1..], exponent).parse() == Ok(value);
+    if tie && round_trips { lower } else { digits }
+}
+
+/// Returns the digits of the mantissa of a number written with `LowerExp`.
+fn mantissa_digits(formatted: &str) -> String {
+    formatted.split('e').next().unwrap_or_default().replace('.', "")
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        NumberFormat::new()
+    }
+}
+
+/// Writes `number` in the given format.
+pub(crate) fn write_number<W: Write + ?Sized>(out: &mut W, number: &JsonNumber, format: NumberFormat) -> FmtResult {
+    if let Some(value) = number.as_u64() {
+        return write!(out, "{}", value);
+    }
+    if let Some(value) = number.as_i64() {
+        return write!(out, "{}", value);
+    }
+    #[cfg(feature = "arbitrary_precision")]
+    if let Some(decimal) = number.as_decimal() {
+        return out.write_str(decimal.as_str());
+    }
+    format.write_float(out, number.as_f64())
+}
+
+/// Writes a finite float exactly as ECMAScript's `Number.prototype.toString` does, which is
+/// the number form RFC 8785 prescribes: `-0` becomes `0` and exponents carry a sign.
+pub(crate) fn write_ecmascript<W: Write + ?Sized>(out: &mut W, value: f64) -> FmtResult {
+    if value == 0.0 {
+        return out.write_char('0');
+    }
+    if value < 0.0 {
+        out.write_char('-')?;
+    }
+    let (digits, exponent) = shortest_digits(value.abs());
+    let digits = round_half_even(value.abs(), digits, exponent);
+    NumberFormat::new().write_digits(out, &digits, exponent, true)
+}
+
+/// Displays a number in a given format.
+pub(crate) struct Formatted<'a>(pub(crate) &'a JsonNumber, pub(crate) NumberFormat);
+
+impl Display for Formatted<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write_number(f, self.0, self.1)
+    }
+}
@@ -0,0 +1,220 @@
+//! Iterative implementations of the operations that walk a whole `JsonValue` tree.
+//!
+//! Nested arrays and objects are tracked on an explicit stack instead of the call stack,
+//! so cloning, comparing, displaying and dropping work for trees of any depth.
+
+use std::cmp::Ordering;
+use std::fmt::{Formatter, Result as FmtResult};
+use std::slice::Iter as ArrayIter;
+use std::vec::IntoIter as SortedIter;
+
+use indexmap::map::Iter as ObjectIter;
+
+use crate::base::escape::{EscapeOptions, Quoted};
+use crate::base::json_array::JsonArray;
+use crate::base::json_key::JsonKey;
+use crate::base::json_object::JsonObject;
+use crate::base::json_value::JsonValue;
+
+/// The remaining children of an array or object being walked.
+pub(crate) enum Children<'a> {
+    Array(ArrayIter<'a, JsonValue>),
+    Object(ObjectIter<'a, JsonKey, JsonValue>),
+    /// The pairs of an object in a chosen key order rather than insertion order.
+    Sorted(SortedIter<(&'a JsonKey, &'a JsonValue)>),
+}
+
+impl<'a> Children<'a> {
+    pub(crate) fn of(value: &'a JsonValue) -> Option<Children<'a>> {
+        match value {
+            JsonValue::Array(array) => Some(Children::Array(array.iter())),
+            JsonValue::Object(object) => Some(Children::Object(object.pairs())),
+            _ => None,
+        }
+    }
+
+    /// Walks the pairs of `object` with its keys ordered by `compare`.
+    pub(crate) fn sorted(object: &'a JsonObject, compare: fn(&str, &str) -> Ordering) -> Children<'a> {
+        let mut pairs: Vec<_> = object.pairs().collect();
+        pairs.sort_by(|(a, _), (b, _)| compare(a, b));
+        Children::Sorted(pairs.into_iter())
+    }
+
+    pub(crate) fn next(&mut self) -> Option<(Option<&'a JsonKey>, &'a JsonValue)> {
+        match self {
+            Children::Array(iter) => iter.next().map(|value| (None, value)),
+            Children::Object(iter) => iter.next().map(|(key, value)| (Some(key), value)),
+            Children::Sorted(iter) => iter.next().map(|(key, value)| (Some(key), value)),
+        }
+    }
+
+    /// Returns the number of children not walked yet.
+    pub(crate) fn remaining(&self) -> usize {
+        match self {
+            Children::Array(iter) => iter.len(),
+            Children::Object(iter) => iter.len(),
+            Children::Sorted(iter) => iter.len(),
+        }
+    }
+
+    /// Returns the bracket that closes the container.
+    pub(crate) fn close(&self) -> char {
+        match self {
+            Children::Array(_) => ']',
+            Children::Object(_) | Children::Sorted(_) => '}',
+        }
+    }
+}
+
+/// A container being rebuilt by `clone_tree`, with the key it belongs to in its parent.
+struct CloneFrame<'a> {
+    key: Option<&'a JsonKey>,
+    children: Children<'a>,
+    copy: JsonValue,
+}
+
+impl<'a> CloneFrame<'a> {
+    fn new(key: Option<&'a JsonKey>, source: &'a JsonValue) -> Option<Self> {
+        let copy = match source {
+            JsonValue::Array(array) => JsonValue::Array(JsonArray::with_capacity(array.len())),
+            JsonValue::Object(_) => JsonValue::Object(JsonObject::new()),
+            _ => return None,
+        };
+        Children::of(source).map(|children| CloneFrame { key, children, copy })
+    }
+
+    fn add(&mut self, key: Option<&JsonKey>, value: JsonValue) {
+        match &mut self.copy {
+            JsonValue::Array(array) => array.push(value),
+            JsonValue::Object(object) => object.insert(key.cloned().unwrap_or_default(), value),
+            _ => {}
+        }
+    }
+}
+
+/// Clones a value that is not an array or object.
+fn clone_scalar(value: &JsonValue) -> JsonValue {
+    match value {
+        JsonValue::String(s) => JsonValue::String(s.clone()),
+        JsonValue::Number(n) => JsonValue::Number(n.clone()),
+        JsonValue::Boolean(b) => JsonValue::Boolean(*b),
+        _ => JsonValue::Null,
+    }
+}
+
+/// Deep-copies `value`.
+pub(crate) fn clone_tree(value: &JsonValue) -> JsonValue {
+    let mut stack: Vec<CloneFrame> = Vec::new();
+    let mut next = (None, value);
+    loop {
+        let (key, source) = next;
+        let mut finished = match CloneFrame::new(key, source) {
+            Some(frame) => {
+                stack.push(frame);
+                None
+            }
+            None => Some((key, clone_scalar(source))),
+        };
+        loop {
+            let Some(top) = stack.last_mut() else {
+                return finished.map_or(JsonValue::Null, |(_, value)| value);
+            };
+            if let Some((key, value)) = finished.take() {
+                top.add(key, value);
+            }
+            match top.children.next() {
+                Some(child) => {
+                    next = child;
+                    break;
+                }
+                None => {
+                    let frame = stack.pop().expect("the stack is not empty");
+                    finished = Some((frame.key, frame.copy));
+                }
+            }
+        }
+    }
+}
+
+/// Compares two trees, treating objects as equal regardless of key order.
+pub(crate) fn tree_eq(left: &JsonValue, right: &JsonValue) -> bool {
+    let mut pending = vec![(left, right)];
+    while let Some(pair) = pending.pop() {
+        match pair {
+            (JsonValue::String(s1), JsonValue::String(s2)) if s1 == s2 => {}
+            (JsonValue::Number(n1), JsonValue::Number(n2)) if n1 == n2 => {}
+            (JsonValue::Boolean(b1), JsonValue::Boolean(b2)) if b1 == b2 => {}
+            (JsonValue::Null, JsonValue::Null) => {}
+            (JsonValue::Array(a1), JsonValue::Array(a2)) if a1.len() == a2.len() => {
+                pending.extend(a1.iter().zip(a2.iter()));
+            }
+            (JsonValue::Object(o1), JsonValue::Object(o2)) if o1.len() == o2.len() => {
+                for (key, value) in o1.iter() {
+                    match o2.lookup(key) {
+                        Some(other) => pending.push((value, other)),
+                        None => return false,
+                    }
+                }
+            }
+            _ => return false,
+        }
+    }
+    true
+}
+
+/// Writes `value` in the compact form used by `Display`.
+pub(crate) fn write_tree(f: &mut Formatter<'_>, value: &JsonValue) -> FmtResult {
+    let mut stack: Vec<(Children, bool)> = Vec::new();
+    let mut next = Some(value);
+    loop {
+        if let Some(value) = next.take() {
+            match value {
+                JsonValue::String(string) => write!(f, "{}", Quoted(string, EscapeOptions::default()))?,
+                JsonValue::Number(num) => write!(f, "{}", num)?,
+                JsonValue::Null => write!(f, "null")?,
+                JsonValue::Boolean(b) => write!(f, "{}", b)?,
+                JsonValue::Array(array) => {
+                    write!(f, "[")?;
+                    stack.push((Children::Array(array.iter()), true));
+                }
+                JsonValue::Object(object) => {
+                    write!(f, "{{")?;
+                    stack.push((Children::Object(object.pairs()), true));
+                }
+            }
+        }
+
+        let Some((children, first)) = stack.last_mut() else {
+            return Ok(());
+        };
+        match children.next() {
+            Some((key, child)) => {
+                if !std::mem::take(first) {
+                    write!(f, ", ")?;
+                }
+                if let Some(key) = key {
+                    write!(f, "{}: ", Quoted(key, EscapeOptions::default()))?;
+                }
+                next = Some(child);
+            }
+            None => {
+                write!(f, "{}", children.close())?;
+                stack.pop();
+            }
+        }
+    }
+}
+
+/// Drops `values` while moving the children of nested arrays and objects onto a heap
+/// allocated stack, so that no drop call recurses into a deeper level.
+pub(crate) fn dismantle<I: Iterator<Item = JsonValue>>(values: I) {
+    let is_container = |value: &JsonValue| matches!(value, JsonValue::Array(_) | JsonValue::Object(_));
+    let mut pending: Vec<JsonValue> = values.filter(is_container).collect();
+    while let Some(value) = pending.pop() {
+        match value {
+            JsonValue::Array(array) => pending.extend(array.into_iter().filter(is_container)),
+            JsonValue::Object(object) => pending.extend(object.into_iter().map(|(_, value)| value).filter(is_container)),
+            _ => {}
+        }
+    }
+}
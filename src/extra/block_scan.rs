@@ -0,0 +1,632 @@
+//! Classifies input 64 bytes at a time to find the bytes the reader cares about.
+//!
+//! Each block is turned into bit masks, one bit per byte, marking quotes, backslashes, control
+//! characters, whitespace, newlines, structural characters and non-ASCII bytes, so that long
+//! runs of string content, whitespace, number tokens and ASCII text are skipped a block at a
+//! time. UTF-8 is validated a block at a time as well.
+//!
+//! With the `simd` feature on x86_64, blocks are classified with AVX2 when the CPU supports it,
+//! detected at runtime, and with SSE2 otherwise; with AVX2, UTF-8 is validated with the
+//! vectorized lookup algorithm of Keiser and Lemire. Other targets, and builds without the
+//! feature, use the portable scalar kernel, which is also the reference the SIMD kernels are
+//! tested against. Short runs are scanned byte by byte before any block is classified, and the
+//! tail of longer inputs after their last full block is scanned byte by byte too.
+
+/// The size of the blocks that are classified at once.
+const BLOCK: usize = 64;
+
+/// The number of bytes scanned one at a time before a run is classified in blocks. Most
+/// strings, numbers and gaps between tokens are shorter than this.
+const SHORT_RUN: usize = 16;
+
+/// Bit masks over a block, where bit `i` describes byte `i`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct BlockMasks {
+    pub(crate) quote: u64,
+    pub(crate) backslash: u64,
+    /// Bytes below `0x20`.
+    pub(crate) control: u64,
+    /// Spaces, tabs, line feeds and carriage returns.
+    pub(crate) whitespace: u64,
+    pub(crate) newline: u64,
+    /// The structural characters `{`, `}`, `[`, `]`, `:` and `,`.
+    pub(crate) structural: u64,
+    /// Bytes with the high bit set.
+    pub(crate) non_ascii: u64,
+    /// UTF-8 continuation bytes, `0b10xx_xxxx`.
+    pub(crate) continuation: u64,
+}
+
+/// A block classifier and UTF-8 validator for one instruction set.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct BlockKernel {
+    name: &'static str,
+    classify: fn(&[u8; BLOCK]) -> BlockMasks,
+    /// Returns `Err(offset)` if `bytes` is not UTF-8, where no sequence starting more than four
+    /// bytes before `offset` is invalid.
+    validate: fn(&[u8]) -> Result<(), usize>,
+}
+
+impl BlockKernel {
+    /// The portable kernel, available everywhere.
+    pub(crate) const SCALAR: BlockKernel = BlockKernel { name: "scalar", classify: classify_scalar, validate: validate_scalar };
+
+    /// Returns every kernel the CPU supports, the scalar one first and the fastest last.
+    pub(crate) fn supported() -> Vec<BlockKernel> {
+        #[allow(unused_mut)]
+        let mut kernels = vec![BlockKernel::SCALAR];
+        #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+        {
+            kernels.push(x86::SSE2);
+            if is_x86_feature_detected!("avx2") {
+                kernels.push(x86::AVX2);
+            }
+        }
+        kernels
+    }
+
+    /// Returns the fastest kernel the CPU supports, detected once per process.
+    fn best() -> BlockKernel {
+        use std::sync::OnceLock;
+
+        static BEST: OnceLock<BlockKernel> = OnceLock::new();
+        *BEST.get_or_init(|| BlockKernel::supported().pop().unwrap_or(BlockKernel::SCALAR))
+    }
+
+    /// Returns the name of the instruction set the kernel uses, for tests and benchmarks.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub(crate) fn name(self) -> &'static str {
+        self.name
+    }
+
+    /// Classifies one block.
+    pub(crate) fn classify(self, block: &[u8; BLOCK]) -> BlockMasks {
+        (self.classify)(block)
+    }
+
+    /// Validates `bytes` as UTF-8.
+    ///
+    /// # Returns
+    ///
+    /// The text, or the offset of the first byte that is not valid UTF-8.
+    pub(crate) fn to_str(self, bytes: &[u8]) -> Result<&str, usize> {
+        match (self.validate)(bytes) {
+            // SAFETY: the kernel has just validated all of `bytes`.
+            Ok(()) => Ok(unsafe { std::str::from_utf8_unchecked(bytes) }),
+            Err(near) => {
+                // Everything before the sequence that spans `near` is valid, so the exact offset
+                // is found by validating again from the first character boundary before it.
+                let mut start = near.saturating_sub(4);
+                while start < near && bytes[start] & 0xC0 == 0x80 {
+                    start += 1;
+                }
+                let rest = &bytes[start..];
+                Err(start + std::str::from_utf8(rest).err().map_or(rest.len(), |error| error.valid_up_to()))
+            }
+        }
+    }
+}
+
+/// The portable classifier.
+fn classify_scalar(block: &[u8; BLOCK]) -> BlockMasks {
+    let mut masks = BlockMasks::default();
+    for (index, &byte) in block.iter().enumerate() {
+        let bit = 1u64 << index;
+        let set = |mask: &mut u64, condition: bool| *mask |= if condition { bit } else { 0 };
+        set(&mut masks.quote, byte == b'"');
+        set(&mut masks.backslash, byte == b'\\');
+        set(&mut masks.control, byte < 0x20);
+        set(&mut masks.whitespace, matches!(byte, b' ' | b'\t' | b'\n' | b'\r'));
+        set(&mut masks.newline, byte == b'\n');
+        set(&mut masks.structural, matches!(byte, b'{' | b'}' | b'[' | b']' | b':' | b','));
+        set(&mut masks.non_ascii, byte >= 0x80);
+        set(&mut masks.continuation, byte & 0xC0 == 0x80);
+    }
+    masks
+}
+
+/// The portable UTF-8 validator, skipping aligned ASCII blocks and checking everything else
+/// one character at a time.
+///
+/// # Returns
+///
+/// `Err` with the offset of the first invalid sequence.
+fn validate_scalar(bytes: &[u8]) -> Result<(), usize> {
+    let mut index = 0;
+    while index < bytes.len() {
+        if index % BLOCK == 0 && bytes.get(index..index + BLOCK).is_some_and(<[u8]>::is_ascii) {
+            index += BLOCK;
+            continue;
+        }
+        index += sequence_len(&bytes[index..]).ok_or(index)?;
+    }
+    Ok(())
+}
+
+/// Returns the length of the UTF-8 sequence at the start of `bytes`, or `None` if it is
+/// invalid or cut short.
+fn sequence_len(bytes: &[u8]) -> Option<usize> {
+    let (width, second) = match bytes[0] {
+        0x00..=0x7F => return Some(1),
+        0xC2..=0xDF => (2, 0x80..=0xBF),
+        0xE0 => (3, 0xA0..=0xBF),
+        0xE1..=0xEC | 0xEE..=0xEF => (3, 0x80..=0xBF),
+        0xED => (3, 0x80..=0x9F),
+        0xF0 => (4, 0x90..=0xBF),
+        0xF1..=0xF3 => (4, 0x80..=0xBF),
+        0xF4 => (4, 0x80..=0x8F),
+        _ => return None,
+    };
+    let continued = |index: usize| bytes.get(index).is_some_and(|&byte| byte & 0xC0 == 0x80);
+    (bytes.get(1).is_some_and(|byte| second.contains(byte)) && (2..width).all(continued)).then_some(width)
+}
+
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod x86 {
+    use std::arch::x86_64::*;
+
+    use super::{BlockKernel, BlockMasks, BLOCK};
+
+    /// The SSE2 kernel. SSE2 has no byte shuffle for the UTF-8 lookup tables, so it validates
+    /// UTF-8 with the scalar validator.
+    pub(super) const SSE2: BlockKernel =
+        BlockKernel { name: "sse2", classify: classify_sse2, validate: super::validate_scalar };
+
+    /// The AVX2 kernel. Only handed out once AVX2 has been detected at runtime.
+    pub(super) const AVX2: BlockKernel = BlockKernel { name: "avx2", classify: classify_avx2, validate: validate_avx2 };
+
+    /// Moves the top bit of every byte of a lane into the low bits of a block mask.
+    macro_rules! bits {
+        ($movemask:ident, $vector:expr, $width:expr) => {
+            $movemask($vector) as u32 as u64 & (u64::MAX >> (64 - $width))
+        };
+    }
+
+    /// Classifies `block` in lanes of `$width` bytes, given the load, compare, and, max and
+    /// movemask intrinsics of one instruction set.
+    macro_rules! classify_lanes {
+        ($block:expr, $width:expr, $load:ident, $set1:ident, $cmpeq:ident, $and:ident, $or:ident, $max:ident, $movemask:ident, $vector:ty) => {{
+            let mut masks = BlockMasks::default();
+            for lane in 0..BLOCK / $width {
+                let v = $load($block.as_ptr().add(lane * $width) as *const $vector);
+                let shift = lane * $width;
+                let newline = $cmpeq(v, $set1(b'\n' as i8));
+                let whitespace = $or(
+                    $or($cmpeq(v, $set1(b' ' as i8)), $cmpeq(v, $set1(b'\t' as i8))),
+                    $or(newline, $cmpeq(v, $set1(b'\r' as i8))),
+                );
+                // `[` and `]` differ from `{` and `}` only in the 0x20 bit.
+                let folded = $or(v, $set1(0x20));
+                let brackets = $or($cmpeq(folded, $set1(b'{' as i8)), $cmpeq(folded, $set1(b'}' as i8)));
+                let structural = $or(brackets, $or($cmpeq(v, $set1(b':' as i8)), $cmpeq(v, $set1(b',' as i8))));
+                let control = $cmpeq($max(v, $set1(0x1F)), $set1(0x1F));
+                let continuation = $cmpeq($and(v, $set1(0xC0u8 as i8)), $set1(0x80u8 as i8));
+
+                masks.quote |= bits!($movemask, $cmpeq(v, $set1(b'"' as i8)), $width) << shift;
+                masks.backslash |= bits!($movemask, $cmpeq(v, $set1(b'\\' as i8)), $width) << shift;
+                masks.control |= bits!($movemask, control, $width) << shift;
+                masks.whitespace |= bits!($movemask, whitespace, $width) << shift;
+                masks.newline |= bits!($movemask, newline, $width) << shift;
+                masks.structural |= bits!($movemask, structural, $width) << shift;
+                masks.non_ascii |= bits!($movemask, v, $width) << shift;
+                masks.continuation |= bits!($movemask, continuation, $width) << shift;
+            }
+            masks
+        }};
+    }
+
+    /// Classifies a block with SSE2, which every x86_64 CPU supports.
+    fn classify_sse2(block: &[u8; BLOCK]) -> BlockMasks {
+        // SAFETY: SSE2 is part of the x86_64 baseline, and every load reads 16 bytes that lie
+        // within `block`.
+        unsafe {
+            classify_lanes!(
+                block, 16, _mm_loadu_si128, _mm_set1_epi8, _mm_cmpeq_epi8, _mm_and_si128, _mm_or_si128, _mm_max_epu8,
+                _mm_movemask_epi8, __m128i
+            )
+        }
+    }
+
+    /// Classifies a block with AVX2.
+    fn classify_avx2(block: &[u8; BLOCK]) -> BlockMasks {
+        // SAFETY: the AVX2 kernel is only handed out after `is_x86_feature_detected!("avx2")`.
+        unsafe { classify_avx2_unchecked(block) }
+    }
+
+    /// # Safety
+    ///
+    /// The CPU must support AVX2.
+    #[target_feature(enable = "avx2")]
+    unsafe fn classify_avx2_unchecked(block: &[u8; BLOCK]) -> BlockMasks {
+        // Every load reads 32 bytes that lie within `block`.
+        classify_lanes!(
+            block, 32, _mm256_loadu_si256, _mm256_set1_epi8, _mm256_cmpeq_epi8, _mm256_and_si256, _mm256_or_si256,
+            _mm256_max_epu8, _mm256_movemask_epi8, __m256i
+        )
+    }
+
+    // The error classes of the lookup algorithm. A byte pair is invalid if the three tables
+    // below, indexed by the high and low nibble of the first byte and the high nibble of the
+    // second, share a class.
+    const TOO_SHORT: u8 = 1 << 0;
+    const TOO_LONG: u8 = 1 << 1;
+    const OVERLONG_3: u8 = 1 << 2;
+    const TOO_LARGE: u8 = 1 << 3;
+    const SURROGATE: u8 = 1 << 4;
+    const OVERLONG_2: u8 = 1 << 5;
+    const TOO_LARGE_1000: u8 = 1 << 6;
+    const OVERLONG_4: u8 = 1 << 6;
+    /// Two continuation bytes in a row, which is only valid inside a three or four byte sequence.
+    const TWO_CONTINUATIONS: u8 = 1 << 7;
+    const CARRY: u8 = TOO_SHORT | TOO_LONG | TWO_CONTINUATIONS;
+
+    const FIRST_HIGH: [u8; 16] = [
+        TOO_LONG,
+        TOO_LONG,
+        TOO_LONG,
+        TOO_LONG,
+        TOO_LONG,
+        TOO_LONG,
+        TOO_LONG,
+        TOO_LONG,
+        TWO_CONTINUATIONS,
+        TWO_CONTINUATIONS,
+        TWO_CONTINUATIONS,
+        TWO_CONTINUATIONS,
+        TOO_SHORT | OVERLONG_2,
+        TOO_SHORT,
+        TOO_SHORT | OVERLONG_3 | SURROGATE,
+        TOO_SHORT | TOO_LARGE | TOO_LARGE_1000 | OVERLONG_4,
+    ];
+
+    const FIRST_LOW: [u8; 16] = [
+        CARRY | OVERLONG_3 | OVERLONG_2 | OVERLONG_4,
+        CARRY | OVERLONG_2,
+        CARRY,
+        CARRY,
+        CARRY | TOO_LARGE,
+        CARRY | TOO_LARGE | TOO_LARGE_1000,
+        CARRY | TOO_LARGE | TOO_LARGE_1000,
+        CARRY | TOO_LARGE | TOO_LARGE_1000,
+        CARRY | TOO_LARGE | TOO_LARGE_1000,
+        CARRY | TOO_LARGE | TOO_LARGE_1000,
+        CARRY | TOO_LARGE | TOO_LARGE_1000,
+        CARRY | TOO_LARGE | TOO_LARGE_1000,
+        CARRY | TOO_LARGE | TOO_LARGE_1000,
+        CARRY | TOO_LARGE | TOO_LARGE_1000 | SURROGATE,
+        CARRY | TOO_LARGE | TOO_LARGE_1000,
+        CARRY | TOO_LARGE | TOO_LARGE_1000,
+    ];
+
+    const SECOND_HIGH: [u8; 16] = [
+        TOO_SHORT,
+        TOO_SHORT,
+        TOO_SHORT,
+        TOO_SHORT,
+        TOO_SHORT,
+        TOO_SHORT,
+        TOO_SHORT,
+        TOO_SHORT,
+        TOO_LONG | OVERLONG_2 | TWO_CONTINUATIONS | OVERLONG_3 | TOO_LARGE_1000 | OVERLONG_4,
+        TOO_LONG | OVERLONG_2 | TWO_CONTINUATIONS | OVERLONG_3 | TOO_LARGE,
+        TOO_LONG | OVERLONG_2 | TWO_CONTINUATIONS | SURROGATE | TOO_LARGE,
+        TOO_LONG | OVERLONG_2 | TWO_CONTINUATIONS | SURROGATE | TOO_LARGE,
+        TOO_SHORT,
+        TOO_SHORT,
+        TOO_SHORT,
+        TOO_SHORT,
+    ];
+
+    /// The largest byte that does not start a sequence needing more bytes than are left in a
+    /// 32-byte lane, for each position of the lane.
+    const COMPLETE: [u8; 32] = {
+        let mut complete = [0xFF; 32];
+        complete[29] = 0xF0 - 1;
+        complete[30] = 0xE0 - 1;
+        complete[31] = 0xC0 - 1;
+        complete
+    };
+
+    /// Validates UTF-8 with AVX2.
+    fn validate_avx2(bytes: &[u8]) -> Result<(), usize> {
+        // SAFETY: the AVX2 kernel is only handed out after `is_x86_feature_detected!("avx2")`.
+        unsafe { validate_avx2_unchecked(bytes) }
+    }
+
+    /// Validates `bytes` a block at a time, padding the tail with zeros.
+    ///
+    /// # Returns
+    ///
+    /// `Err` with the offset of the first block in which an error was found.
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support AVX2.
+    #[target_feature(enable = "avx2")]
+    unsafe fn validate_avx2_unchecked(bytes: &[u8]) -> Result<(), usize> {
+        let mut state = Utf8State { previous: _mm256_setzero_si256(), incomplete: _mm256_setzero_si256() };
+        let mut chunks = bytes.chunks_exact(BLOCK);
+        let mut offset = 0;
+        for chunk in &mut chunks {
+            if !state.check(chunk) {
+                return Err(offset);
+            }
+            offset += BLOCK;
+        }
+        // The padding is ASCII, so it also reports a sequence cut short by the end of input.
+        let mut tail = [0; BLOCK];
+        tail[..chunks.remainder().len()].copy_from_slice(chunks.remainder());
+        match state.check(&tail) {
+            true => Ok(()),
+            false => Err(offset),
+        }
+    }
+
+    /// What the lookup algorithm carries from one block to the next.
+    struct Utf8State {
+        /// The last 32 bytes checked.
+        previous: __m256i,
+        /// Nonzero if `previous` ends with a sequence that the next block must finish.
+        incomplete: __m256i,
+    }
+
+    impl Utf8State {
+        /// Checks the block at the start of `block`, which is at least `BLOCK` bytes long.
+        ///
+        /// # Safety
+        ///
+        /// The CPU must support AVX2.
+        #[target_feature(enable = "avx2")]
+        unsafe fn check(&mut self, block: &[u8]) -> bool {
+            // Both loads read 32 bytes that lie within the first `BLOCK` bytes of `block`.
+            let first = _mm256_loadu_si256(block.as_ptr() as *const __m256i);
+            let second = _mm256_loadu_si256(block.as_ptr().add(32) as *const __m256i);
+            let error = if _mm256_movemask_epi8(_mm256_or_si256(first, second)) == 0 {
+                // An ASCII block cannot finish a sequence that the last block started.
+                std::mem::replace(&mut self.incomplete, _mm256_setzero_si256())
+            } else {
+                let error = _mm256_or_si256(check_lane(first, self.previous), check_lane(second, first));
+                self.incomplete = _mm256_subs_epu8(second, _mm256_loadu_si256(COMPLETE.as_ptr() as *const __m256i));
+                error
+            };
+            self.previous = second;
+            _mm256_testz_si256(error, error) == 1
+        }
+    }
+
+    /// Returns a vector that is nonzero where `input`, preceded by `previous`, is not UTF-8.
+    #[target_feature(enable = "avx2")]
+    unsafe fn check_lane(input: __m256i, previous: __m256i) -> __m256i {
+        // The bytes one, two and three positions before each byte of `input`.
+        let joined = _mm256_permute2x128_si256(previous, input, 0x21);
+        let previous_1 = _mm256_alignr_epi8(input, joined, 15);
+        let previous_2 = _mm256_alignr_epi8(input, joined, 14);
+        let previous_3 = _mm256_alignr_epi8(input, joined, 13);
+
+        let low_nibble = _mm256_set1_epi8(0x0F);
+        let first_high = lookup(&FIRST_HIGH, _mm256_and_si256(_mm256_srli_epi16(previous_1, 4), low_nibble));
+        let first_low = lookup(&FIRST_LOW, _mm256_and_si256(previous_1, low_nibble));
+        let second_high = lookup(&SECOND_HIGH, _mm256_and_si256(_mm256_srli_epi16(input, 4), low_nibble));
+        let special_cases = _mm256_and_si256(_mm256_and_si256(first_high, first_low), second_high);
+
+        // Only bytes two or three places after a three or four byte lead must be the
+        // continuation bytes that the tables flag as `TWO_CONTINUATIONS`.
+        let third = _mm256_subs_epu8(previous_2, _mm256_set1_epi8((0xE0u8 - 0x80) as i8));
+        let fourth = _mm256_subs_epu8(previous_3, _mm256_set1_epi8((0xF0u8 - 0x80) as i8));
+        let must_continue = _mm256_and_si256(_mm256_or_si256(third, fourth), _mm256_set1_epi8(0x80u8 as i8));
+        _mm256_xor_si256(must_continue, special_cases)
+    }
+
+    /// Looks up each byte of `indices`, which must be below 16, in `table`.
+    #[target_feature(enable = "avx2")]
+    unsafe fn lookup(table: &[u8; 16], indices: __m256i) -> __m256i {
+        let table = _mm256_broadcastsi128_si256(_mm_loadu_si128(table.as_ptr() as *const __m128i));
+        _mm256_shuffle_epi8(table, indices)
+    }
+}
+
+/// Returns the index of the first byte selected by `select` in the blocks of `bytes`, or by
+/// `scalar` in the first `SHORT_RUN` bytes and in the tail, or `bytes.len()` if there is none.
+fn find(bytes: &[u8], select: impl Fn(&BlockMasks) -> u64, scalar: impl Fn(u8) -> bool) -> usize {
+    let short = bytes.len().min(SHORT_RUN);
+    if let Some(found) = bytes[..short].iter().position(|&b| scalar(b)) {
+        return found;
+    }
+    let mut offset = short;
+    let mut chunks = bytes[short..].chunks_exact(BLOCK);
+    if chunks.len() > 0 {
+        let kernel = BlockKernel::best();
+        for chunk in &mut chunks {
+            let block = chunk.try_into().expect("chunks are exactly one block long");
+            let found = select(&kernel.classify(block));
+            if found != 0 {
+                return offset + found.trailing_zeros() as usize;
+            }
+            offset += BLOCK;
+        }
+    }
+    let tail = chunks.remainder();
+    offset + tail.iter().position(|&b| scalar(b)).unwrap_or(tail.len())
+}
+
+/// Returns the length of the plain string content at the start of `bytes`, which ends at the
+/// first quote, backslash or, unless `control_characters` allows them, control character.
+pub(crate) fn string_content_len(bytes: &[u8], control_characters: bool) -> usize {
+    let controls = if control_characters { 0 } else { u64::MAX };
+    find(
+        bytes,
+        |masks| masks.quote | masks.backslash | (masks.control & controls),
+        |b| b == b'"' || b == b'\\' || (b < 0x20 && !control_characters),
+    )
+}
+
+/// Returns the length of the whitespace at the start of `bytes`, that is, the offset of the
+/// next structural character or value.
+pub(crate) fn whitespace_len(bytes: &[u8]) -> usize {
+    find(bytes, |masks| !masks.whitespace, |b| !matches!(b, b' ' | b'\t' | b'\n' | b'\r'))
+}
+
+/// Returns the length of the number or literal token at the start of `bytes`, which ends at
+/// the first whitespace, structural character or quote.
+pub(crate) fn token_len(bytes: &[u8]) -> usize {
+    find(
+        bytes,
+        |masks| masks.whitespace | masks.structural | masks.quote,
+        |b| matches!(b, b' ' | b'\t' | b'\n' | b'\r' | b'{' | b'}' | b'[' | b']' | b':' | b',' | b'"'),
+    )
+}
+
+/// What a run of bytes does to a line and column position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct LineAdvance {
+    /// The number of line feeds.
+    pub(crate) newlines: usize,
+    /// The offset just past the last line feed, or `0` if there is none.
+    pub(crate) line_start: usize,
+    /// The number of characters after the last line feed, counted as non-continuation bytes.
+    pub(crate) columns: usize,
+}
+
+/// Counts the line feeds of `bytes` and the characters of its last line.
+pub(crate) fn line_advance(bytes: &[u8]) -> LineAdvance {
+    let mut advance = LineAdvance { newlines: 0, line_start: 0, columns: 0 };
+    let mut count = |offset: usize, newline: u64, characters: u64| {
+        advance.newlines += newline.count_ones() as usize;
+        if newline != 0 {
+            let last = 63 - newline.leading_zeros() as usize;
+            advance.line_start = offset + last + 1;
+            advance.columns = (characters >> last >> 1).count_ones() as usize;
+        } else {
+            advance.columns += characters.count_ones() as usize;
+        }
+    };
+
+    let mut chunks = bytes.chunks_exact(BLOCK);
+    let mut offset = 0;
+    if chunks.len() > 0 {
+        let kernel = BlockKernel::best();
+        for chunk in &mut chunks {
+            let masks = kernel.classify(chunk.try_into().expect("chunks are exactly one block long"));
+            count(offset, masks.newline, !masks.continuation & !masks.newline);
+            offset += BLOCK;
+        }
+    }
+    for (index, &byte) in chunks.remainder().iter().enumerate() {
+        count(offset + index, u64::from(byte == b'\n'), u64::from(byte & 0xC0 != 0x80 && byte != b'\n'));
+    }
+    advance
+}
+
+/// Validates `bytes` as UTF-8 a block at a time, leaving inputs shorter than a block to the
+/// standard library.
+///
+/// # Returns
+///
+/// The text, or the offset of the first byte that is not valid UTF-8.
+pub(crate) fn to_str(bytes: &[u8]) -> Result<&str, usize> {
+    if bytes.len() < BLOCK {
+        return std::str::from_utf8(bytes).map_err(|error| error.valid_up_to());
+    }
+    BlockKernel::best().to_str(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use super::BlockKernel;
+
+    /// A deterministic xorshift generator, so that failures can be reproduced.
+    struct Random(u64);
+
+    impl Random {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+            items[self.next() as usize % items.len()]
+        }
+    }
+
+    #[test]
+    fn simd_kernels_classify_like_the_scalar_kernel() -> Result<(), Box<dyn Error>> {
+        let special = b" \t\r\n\"\\{}[]:,ax0\x00\x1F\x7F\x80\xBF\xC3\xE2\xF0\xFF";
+        let mut random = Random(0x9E37_79B9_7F4A_7C15);
+        for round in 0..20_000 {
+            let mut block = [0u8; 64];
+            for byte in &mut block {
+                *byte = match round % 2 {
+                    0 => random.pick(special),
+                    _ => random.next() as u8,
+                };
+            }
+            let expected = BlockKernel::SCALAR.classify(&block);
+            for kernel in BlockKernel::supported() {
+                assert_eq!(kernel.classify(&block), expected, "{} kernel on {:?}", kernel.name(), block);
+            }
+        }
+
+        let mut block = [b'a'; 64];
+        block[..7].copy_from_slice(b"{}[]:,\"");
+        assert_eq!(BlockKernel::SCALAR.classify(&block).structural, 0b11_1111);
+        Ok(())
+    }
+
+    #[test]
+    fn utf8_validation_matches_the_standard_library() -> Result<(), Box<dyn Error>> {
+        let valid: [&[u8]; 9] = [
+            b"a", b"abcdefghijklmnopqrstuvwxyz0123456789", "é".as_bytes(), "€".as_bytes(), "😀".as_bytes(),
+            "\u{7FF}".as_bytes(), "\u{D7FF}".as_bytes(), "\u{E000}".as_bytes(), "\u{10FFFF}".as_bytes(),
+        ];
+        let invalid: [&[u8]; 12] = [
+            &[0x80], &[0xBF, 0x80], &[0xC0, 0x80], &[0xC1, 0xBF], &[0xE0, 0x80, 0x80], &[0xED, 0xA0, 0x80],
+            &[0xF0, 0x80, 0x80, 0x80], &[0xF4, 0x90, 0x80, 0x80], &[0xF5, 0x80, 0x80, 0x80], &[0xFF],
+            &[0xE2, 0x82], &[0xF0, 0x9F, 0x98],
+        ];
+        let mut random = Random(0x2545_F491_4F6C_DD1D);
+        for round in 0..20_000 {
+            let mut bytes = Vec::new();
+            let target = random.next() as usize % 300;
+            while bytes.len() < target {
+                match random.next() % 64 {
+                    0 if round % 4 != 0 => bytes.extend_from_slice(random.pick(&invalid)),
+                    _ => bytes.extend_from_slice(random.pick(&valid)),
+                }
+            }
+            let expected = std::str::from_utf8(&bytes).map_err(|error| error.valid_up_to());
+            for kernel in BlockKernel::supported() {
+                assert_eq!(kernel.to_str(&bytes), expected, "{} kernel on {:?}", kernel.name(), bytes);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn utf8_sequences_across_block_boundaries() -> Result<(), Box<dyn Error>> {
+        let followers = [0x00, 0x41, 0x7F, 0x80, 0x8F, 0x90, 0x9F, 0xA0, 0xBF, 0xC0, 0xFF];
+        for offset in [0, 29, 31, 32, 60, 61, 62, 63, 64, 127] {
+            for lead in 0x80..=0xFF {
+                for &second in &followers {
+                    for (rest, count) in [(0x80, 1), (0x80, 2), (0xBF, 1), (0xBF, 2), (0x41, 1)] {
+                        let mut bytes = vec![b'a'; offset];
+                        bytes.extend_from_slice(&[lead, second]);
+                        bytes.extend(std::iter::repeat_n(rest, count));
+                        let length = bytes.len();
+                        for padding in [0, 70] {
+                            bytes.resize(length + padding, b'a');
+                            let expected = std::str::from_utf8(&bytes).map_err(|error| error.valid_up_to());
+                            for kernel in BlockKernel::supported() {
+                                assert_eq!(kernel.to_str(&bytes), expected, "{} kernel on {:?}", kernel.name(), bytes);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
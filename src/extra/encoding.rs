@@ -0,0 +1,58 @@
+use crate::extra::block_scan::to_str;
+use crate::extra::parse_error::{ParseError, ParseErrorKind};
+
+/// The UTF-8 encoding of U+FEFF, which some producers put at the start of a document.
+pub(crate) const BYTE_ORDER_MARK: &[u8] = b"\xEF\xBB\xBF";
+
+/// Recognizes a document encoded in UTF-16 or UTF-32 from its first four bytes.
+///
+/// A byte order mark identifies the encoding on its own. Without one, the encoding is only
+/// guessed from the pattern of zero bytes among the first four, as described in RFC 4627,
+/// section 3, when the first character it implies is one that can start a JSON text and
+/// the second is ASCII. Other input with zero bytes, such as UTF-8 text that starts with a
+/// raw NUL, is left to the UTF-8 checks and the parser.
+fn detect_wide_encoding(bytes: &[u8]) -> Option<&'static str> {
+    let start = |byte: &u8| starts_value(*byte);
+    let ascii = |byte: &u8| (0x01..0x80).contains(byte);
+    match bytes {
+        [0x00, 0x00, 0xFE, 0xFF, ..] => Some("UTF-32BE"),
+        [0xFF, 0xFE, 0x00, 0x00, ..] => Some("UTF-32LE"),
+        [0xFE, 0xFF, ..] => Some("UTF-16BE"),
+        [0xFF, 0xFE, ..] => Some("UTF-16LE"),
+        [0x00, 0x00, 0x00, a, ..] if start(a) => Some("UTF-32BE"),
+        [a, 0x00, 0x00, 0x00, ..] if start(a) => Some("UTF-32LE"),
+        [0x00, a, 0x00, b, ..] if start(a) && ascii(b) => Some("UTF-16BE"),
+        [a, 0x00, b, 0x00, ..] if start(a) && ascii(b) => Some("UTF-16LE"),
+        [0x00, a] if start(a) => Some("UTF-16BE"),
+        [a, 0x00] if start(a) => Some("UTF-16LE"),
+        _ => None,
+    }
+}
+
+/// Returns `true` if `byte` is whitespace or a character that starts a JSON value.
+fn starts_value(byte: u8) -> bool {
+    matches!(byte, b' ' | b'\t' | b'\n' | b'\r' | b'{' | b'[' | b'"' | b'-' | b'0'..=b'9' | b't' | b'f' | b'n')
+}
+
+/// Checks that `bytes` is a UTF-8 document and returns it as text.
+///
+/// # Errors
+///
+/// Returns a `ParseErrorKind::InvalidEncoding` error at offset `0` if the document is
+/// encoded in UTF-16 or UTF-32, or at the first byte that is not valid UTF-8 otherwise.
+pub(crate) fn decode_utf8(bytes: &[u8]) -> Result<&str, ParseError> {
+    if let Some(encoding) = detect_wide_encoding(bytes) {
+        return Err(ParseError::new(0, 1, 1, "UTF-8", format!("{} text", encoding), "", 1)
+            .with_kind(ParseErrorKind::InvalidEncoding));
+    }
+    to_str(bytes).map_err(|offset| {
+        // Everything before `offset` is valid, so the position is derived from that prefix.
+        let valid = to_str(&bytes[..offset]).unwrap_or_default();
+        let line_start = valid.rfind('\n').map_or(0, |index| index + 1);
+        let line = valid.matches('\n').count() + 1;
+        let column = valid[line_start..].chars().count() + 1;
+        let found = format!("invalid UTF-8 byte 0x{:02X}", bytes[offset]);
+        ParseError::new(offset, line, column, "valid UTF-8", found, &valid[line_start..], column)
+            .with_kind(ParseErrorKind::InvalidEncoding)
+    })
+}
@@ -1,13 +1,35 @@
-#![allow(dead_code)]
+use std::fmt;
+use std::io::{self, BufWriter, Write as _};
 
-use std::ops::Sub;
-use crate::base::{JsonArray, JsonObject, JsonValue};
+use crate::base::{write_canonical, CanonicalError, Children, EscapeOptions, Formatted, JsonValue, NumberFormat, Quoted};
 
 /// A formatter for JSON values that allows customization of indentation.
+///
+/// Output is written piece by piece while the value is walked with an explicit stack, so
+/// documents of any size and depth can be streamed to a file, socket or stdout with
+/// `format_to_writer` without building the text in memory first.
+///
+/// With a maximum width, set by `JsonFormatterBuilder::with_max_width`, short arrays and
+/// objects stay on one line and only those that do not fit are broken, one child per line.
+///
+/// In canonical mode, selected with `JsonFormatter::canonical()`, the output follows RFC 8785
+/// instead: no whitespace, keys sorted by their UTF-16 code units and numbers written as
+/// ECMAScript doubles, ignoring every other setting.
+#[derive(Debug, Clone)]
 pub struct JsonFormatter {
-    indent: usize,
-    indent_char: char,
-    current_indent: usize,
+    indent_unit: String,
+    /// The width of `indent_unit` in columns.
+    indent_width: usize,
+    line_ending: LineEnding,
+    escape: EscapeOptions,
+    numbers: NumberFormat,
+    max_width: Option<usize>,
+    sort_keys: bool,
+    space_after_colon: bool,
+    space_after_comma: bool,
+    trailing_newline: bool,
+    compact: bool,
+    canonical: bool,
 }
 
 impl JsonFormatter {
@@ -16,6 +38,12 @@ impl JsonFormatter {
         JsonFormatterBuilder::new()
     }
 
+    /// Returns a `JsonFormatter` that writes the RFC 8785 canonical form, the same bytes as
+    /// `JsonValue::canonical_bytes`.
+    pub fn canonical() -> JsonFormatter {
+        JsonFormatterBuilder::new().with_canonical(true).build()
+    }
+
     /// Formats a JSON value into a pretty-printed string.
     ///
     /// # Arguments
@@ -24,99 +52,294 @@ impl JsonFormatter {
     ///
     /// # Returns
     ///
-    /// A formatted string representation of the JSON value.
-    pub fn format(&mut self, target: &JsonValue) -> String {
-        self.current_indent = self.indent;
-        self.format_value(target, self.current_indent, false)
+    /// A formatted string representation of the JSON value. In canonical mode, `NaN` and
+    /// infinities, which have no canonical form, are written as `null` like ECMAScript's
+    /// `JSON.stringify` does; `format_canonical` reports them as an error instead.
+    pub fn format(&self, target: &JsonValue) -> String {
+        let mut output = String::new();
+        // Writing to a `String` cannot fail, and non-finite numbers are written leniently.
+        let _ = self.write_document(&mut output, target, true);
+        output
     }
 
-    /// Formats a JSON object into a pretty-printed string.
+    /// Formats a JSON value in the RFC 8785 canonical form, like `JsonValue::canonical_bytes`.
+    ///
+    /// The other settings of the formatter do not apply, as in `JsonFormatter::canonical()`.
     ///
     /// # Arguments
     ///
-    /// * `json_object` - The JSON object to format.
-    /// * `indent_level` - The current indentation level.
-    /// * `is_child` - Whether this object is a child of another object or array.
+    /// * `target` - The JSON value to format.
     ///
-    /// # Returns
+    /// # Errors
     ///
-    /// A formatted string representation of the JSON object.
-    fn pretty_object(&mut self, json_object: &JsonObject, indent_level: usize, is_child: bool) -> String {
-        let mut s = String::new();
-        let mut data = json_object.iter().peekable();
-        s.push_str("{\n");
-        while let Some((k, v)) = data.next() {
-            self.current_indent = indent_level + self.indent;
-            s.push_str(&self.indent_str().repeat(indent_level));
-            s.push_str(&format!("\"{}\": {}", k, self.format_value(v, self.current_indent, true)));
-            if data.peek().is_some() {
-                s.push_str(",\n");
-            }
-        }
-        s.push('\n');
-        if is_child {
-            s.push_str(&self.indent_str().repeat(indent_level.sub(self.indent)));
-        }
-        s.push('}');
-        s
+    /// Returns a `CanonicalError` if `target` holds `NaN` or an infinity.
+    pub fn format_canonical(&self, target: &JsonValue) -> Result<String, CanonicalError> {
+        let mut output = String::new();
+        write_canonical(&mut output, target, false).map_err(|_| CanonicalError)?;
+        Ok(output)
     }
 
-    /// Formats a JSON array into a pretty-printed string.
+    /// Writes a JSON value, pretty-printed, to an I/O stream.
+    ///
+    /// The output is buffered internally and flushed before returning.
     ///
     /// # Arguments
     ///
-    /// * `json_array` - The JSON array to format.
-    /// * `indent_level` - The current indentation level.
-    /// * `is_child` - Whether this array is a child of another object or array.
+    /// * `target` - The JSON value to format.
+    /// * `writer` - The destination, such as a `File`, a `TcpStream` or `io::stdout()`.
     ///
-    /// # Returns
+    /// # Errors
     ///
-    /// A formatted string representation of the JSON array.
-    fn pretty_array(&mut self, json_array: &JsonArray, indent_level: usize, is_child: bool) -> String {
-        let mut s = String::new();
-        let mut data = json_array.iter().peekable();
-        s.push_str("[\n");
-        while let Some(json_value) = data.next() {
-            self.current_indent = indent_level + self.indent;
-            s.push_str(&self.indent_str().repeat(indent_level));
-            s.push_str(&self.format_value(json_value, self.current_indent, true));
-            if data.peek().is_some() {
-                s.push_str(",\n");
-            }
-        }
-        s.push('\n');
-        if is_child {
-            s.push_str(&self.indent_str().repeat(indent_level.sub(self.indent)));
-        }
-        s.push(']');
-        s
+    /// Returns the first error raised by `writer`, or an `InvalidData` error in canonical mode
+    /// if `target` holds `NaN` or an infinity.
+    pub fn format_to_writer<W: io::Write>(&self, target: &JsonValue, writer: W) -> io::Result<()> {
+        let mut adapter = IoAdapter::new(writer);
+        let result = self.write_document(&mut adapter, target, false);
+        adapter.check(result)?;
+        adapter.into_inner().map(drop)
     }
 
-    /// Formats a JSON value into a string.
+    /// Writes a JSON value, pretty-printed, to a `fmt::Write` destination such as a `String`
+    /// or a `Formatter`.
     ///
     /// # Arguments
     ///
-    /// * `json_value` - The JSON value to format.
-    /// * `indent_level` - The current indentation level.
-    /// * `is_child` - Whether this value is a child of an object or array.
+    /// * `target` - The JSON value to format.
+    /// * `writer` - The destination.
     ///
-    /// # Returns
+    /// # Errors
     ///
-    /// A formatted string representation of the JSON value.
-    fn format_value(&mut self, json_value: &JsonValue, indent_level: usize, is_child: bool) -> String {
-        match json_value {
-            JsonValue::String(v) => format!("\"{}\"", v),
-            JsonValue::Number(v) => v.to_string(),
-            JsonValue::Boolean(v) => v.to_string(),
-            JsonValue::Object(v) => self.pretty_object(v, indent_level, is_child),
-            JsonValue::Array(v) => self.pretty_array(v, indent_level, is_child),
-            JsonValue::Null => "null".to_string(),
+    /// Returns the first error raised by `writer`, or `fmt::Error` in canonical mode if
+    /// `target` holds `NaN` or an infinity.
+    pub fn format_to_fmt<W: fmt::Write>(&self, target: &JsonValue, mut writer: W) -> fmt::Result {
+        self.write_document(&mut writer, target, false)
+    }
+
+    /// Returns `true` if the formatter writes the RFC 8785 canonical form.
+    pub fn is_canonical(&self) -> bool {
+        self.canonical
+    }
+
+    /// Writes `target` as a whole document, followed by the trailing newline if enabled. In
+    /// canonical mode, `lenient` writes numbers without a canonical form as `null`.
+    fn write_document<W: fmt::Write + ?Sized>(&self, out: &mut W, target: &JsonValue, lenient: bool) -> fmt::Result {
+        match self.canonical {
+            true => write_canonical(out, target, lenient)?,
+            false => self.write_value(out, target, 0)?,
         }
+        self.write_end(out)
+    }
+
+    /// Writes `value` with one array element or object member per line, without recursion,
+    /// as if it were nested `depth` levels deep.
+    pub(crate) fn write_value<W: fmt::Write + ?Sized>(&self, out: &mut W, value: &JsonValue, depth: usize) -> fmt::Result {
+        self.write_layout(out, value, depth, self.compact)
     }
 
-    /// Returns the indentation string based on the formatter's settings.
-    fn indent_str(&self) -> String {
-        self.indent_char.to_string().repeat(self.indent)
+    /// Lays out `value` nested `depth` levels deep, writing each container on a single line if
+    /// `inline` is set or it fits within the maximum width, and one child per line otherwise.
+    fn write_layout<W: fmt::Write + ?Sized>(&self, out: &mut W, value: &JsonValue, depth: usize, inline: bool) -> fmt::Result {
+        let mut stack: Vec<Frame> = Vec::new();
+        // The next value, the column it starts at and the width of the comma after it.
+        let mut next = Some((value, depth * self.indent_width, 0));
+        loop {
+            if let Some((value, column, trailing)) = next.take() {
+                let inline = inline || stack.last().is_some_and(|frame| frame.inline);
+                match value {
+                    JsonValue::String(v) => write!(out, "{}", Quoted(v, self.escape))?,
+                    JsonValue::Number(v) => write!(out, "{}", Formatted(v, self.numbers))?,
+                    JsonValue::Boolean(v) => write!(out, "{}", v)?,
+                    JsonValue::Null => out.write_str("null")?,
+                    JsonValue::Array(v) if v.is_empty() => out.write_str("[]")?,
+                    JsonValue::Object(v) if v.is_empty() => out.write_str("{}")?,
+                    JsonValue::Array(v) => {
+                        let inline = inline || self.fits(value, column + trailing);
+                        out.write_char('[')?;
+                        stack.push(Frame { children: Children::Array(v.iter()), first: true, inline });
+                    }
+                    JsonValue::Object(v) => {
+                        let inline = inline || self.fits(value, column + trailing);
+                        let children = if self.sort_keys { Children::sorted(v, str::cmp) } else { Children::Object(v.pairs()) };
+                        out.write_char('{')?;
+                        stack.push(Frame { children, first: true, inline });
+                    }
+                }
+            }
+
+            let level = depth + stack.len();
+            let Some(frame) = stack.last_mut() else {
+                return Ok(());
+            };
+            match frame.children.next() {
+                Some((key, child)) => {
+                    self.write_separator(out, std::mem::take(&mut frame.first), frame.inline, level)?;
+                    let mut column = level * self.indent_width;
+                    if let Some(key) = key {
+                        self.write_key(out, key)?;
+                        if self.max_width.is_some() && !frame.inline {
+                            column += self.width_of(|measure| self.write_key(measure, key));
+                        }
+                    }
+                    let trailing = usize::from(frame.children.remaining() > 0);
+                    next = Some((child, column, trailing));
+                }
+                None => {
+                    let (close, inline) = (frame.children.close(), frame.inline);
+                    stack.pop();
+                    self.write_close(out, close, inline, level - 1)?;
+                }
+            }
+        }
+    }
+
+    /// Returns whether `value` fits on one line after `used` columns, giving up as soon as
+    /// the line runs past the maximum width.
+    fn fits(&self, value: &JsonValue, used: usize) -> bool {
+        let Some(limit) = self.max_width.and_then(|width| width.checked_sub(used)) else {
+            return false;
+        };
+        let mut measure = Measure { width: 0, limit };
+        self.write_layout(&mut measure, value, 0, true).is_ok()
+    }
+
+    /// Returns the number of columns `write` produces.
+    fn width_of(&self, write: impl FnOnce(&mut Measure) -> fmt::Result) -> usize {
+        let mut measure = Measure { width: 0, limit: usize::MAX };
+        let _ = write(&mut measure);
+        measure.width
+    }
+
+    /// Writes an object key and the colon after it.
+    pub(crate) fn write_key<W: fmt::Write + ?Sized>(&self, out: &mut W, key: &str) -> fmt::Result {
+        write!(out, "{}:", Quoted(key, self.escape))?;
+        if self.space_after_colon {
+            out.write_char(' ')?;
+        }
+        Ok(())
+    }
+
+    /// Writes what precedes a child of a container nested `depth` levels deep: a comma unless
+    /// it is the `first` child, then a space if the container is `inline` or a new line if not.
+    pub(crate) fn write_separator<W: fmt::Write + ?Sized>(&self, out: &mut W, first: bool, inline: bool, depth: usize) -> fmt::Result {
+        let inline = inline || self.compact;
+        if !first {
+            out.write_char(',')?;
+            if inline && self.space_after_comma {
+                out.write_char(' ')?;
+            }
+        }
+        if !inline {
+            self.write_newline(out, depth)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the `close` bracket of a container nested `depth` levels deep, on a line of its
+    /// own unless the container is `inline`.
+    pub(crate) fn write_close<W: fmt::Write + ?Sized>(&self, out: &mut W, close: char, inline: bool, depth: usize) -> fmt::Result {
+        if !inline {
+            self.write_newline(out, depth)?;
+        }
+        out.write_char(close)
+    }
+
+    /// Writes the trailing newline after a whole document, if enabled.
+    pub(crate) fn write_end<W: fmt::Write + ?Sized>(&self, out: &mut W) -> fmt::Result {
+        if self.trailing_newline && !self.canonical {
+            out.write_str(self.line_ending.as_str())?;
+        }
+        Ok(())
+    }
+
+    /// Starts a new line indented for `depth` levels of nesting.
+    fn write_newline<W: fmt::Write + ?Sized>(&self, out: &mut W, depth: usize) -> fmt::Result {
+        if self.compact {
+            return Ok(());
+        }
+        out.write_str(self.line_ending.as_str())?;
+        if !self.indent_unit.is_empty() {
+            for _ in 0..depth {
+                out.write_str(&self.indent_unit)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The characters that end each line of formatted output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LineEnding {
+    /// `\n`, as used on Unix.
+    #[default]
+    Lf,
+    /// `\r\n`, as used on Windows.
+    CrLf,
+}
+
+impl LineEnding {
+    /// Returns the line ending as a string.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
+
+/// An array or object being laid out by the formatter.
+struct Frame<'a> {
+    children: Children<'a>,
+    first: bool,
+    /// Whether the container is written on a single line.
+    inline: bool,
+}
+
+/// Counts the columns written to it, failing once they exceed `limit`.
+struct Measure {
+    width: usize,
+    limit: usize,
+}
+
+impl fmt::Write for Measure {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.width += s.chars().count();
+        if self.width > self.limit { Err(fmt::Error) } else { Ok(()) }
+    }
+}
+
+/// Lets the formatter write to a buffered `io::Write`, keeping the I/O error that `fmt::Error`
+/// cannot carry.
+pub(crate) struct IoAdapter<W: io::Write> {
+    inner: BufWriter<W>,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> IoAdapter<W> {
+    pub(crate) fn new(writer: W) -> Self {
+        IoAdapter { inner: BufWriter::new(writer), error: None }
+    }
+
+    /// Turns the result of writing through the adapter into the I/O error behind it. An error
+    /// that did not come from the writer is a number without a canonical form.
+    pub(crate) fn check(&mut self, result: fmt::Result) -> io::Result<()> {
+        match result {
+            Ok(()) => Ok(()),
+            Err(fmt::Error) => Err(self.error.take().unwrap_or_else(|| io::Error::new(io::ErrorKind::InvalidData, CanonicalError))),
+        }
+    }
+
+    /// Flushes the buffer and returns the underlying writer.
+    pub(crate) fn into_inner(self) -> io::Result<W> {
+        self.inner.into_inner().map_err(|error| error.into_error())
+    }
+}
+
+impl<W: io::Write> fmt::Write for IoAdapter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            fmt::Error
+        })
     }
 }
 
@@ -124,14 +347,37 @@ impl JsonFormatter {
 pub struct JsonFormatterBuilder {
     indent: usize,
     indent_char: char,
+    indent_unit: Option<String>,
+    line_ending: LineEnding,
+    escape: EscapeOptions,
+    numbers: NumberFormat,
+    max_width: Option<usize>,
+    sort_keys: bool,
+    space_after_colon: bool,
+    space_after_comma: bool,
+    trailing_newline: bool,
+    compact: bool,
+    canonical: bool,
 }
 
 impl JsonFormatterBuilder {
-    /// Creates a new `JsonFormatterBuilder` with default settings.
+    /// Creates a new `JsonFormatterBuilder` with default settings: one child per line without
+    /// indentation, `\n` line endings, a space after each colon and keys in insertion order.
     pub fn new() -> Self {
         JsonFormatterBuilder {
             indent: 0,
-            indent_char: '\0',
+            indent_char: ' ',
+            indent_unit: None,
+            line_ending: LineEnding::Lf,
+            escape: EscapeOptions::default(),
+            numbers: NumberFormat::default(),
+            max_width: None,
+            sort_keys: false,
+            space_after_colon: true,
+            space_after_comma: true,
+            trailing_newline: false,
+            compact: false,
+            canonical: false,
         }
     }
 
@@ -155,16 +401,150 @@ impl JsonFormatterBuilder {
         self
     }
 
+    /// Sets the text written once for each level of nesting, such as `"\t"` or `"  "`, which
+    /// takes precedence over `with_indent` and `with_indent_char`.
+    ///
+    /// # Arguments
+    ///
+    /// * `unit` - The indentation of a single level.
+    pub fn with_indent_unit(mut self, unit: &str) -> Self {
+        self.indent_unit = Some(unit.to_string());
+        self
+    }
+
+    /// Sets the characters that end each line.
+    ///
+    /// # Arguments
+    ///
+    /// * `line_ending` - `LineEnding::Lf`, the default, or `LineEnding::CrLf`.
+    pub fn with_line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// Sets whether the whole document is written on a single line, as `{"a": [1, 2]}`.
+    ///
+    /// # Arguments
+    ///
+    /// * `compact` - Whether to write no line breaks or indentation at all.
+    pub fn with_compact(mut self, compact: bool) -> Self {
+        self.compact = compact;
+        self
+    }
+
+    /// Sets whether object keys are written in sorted order rather than insertion order.
+    ///
+    /// # Arguments
+    ///
+    /// * `sort_keys` - Whether to sort keys by their characters.
+    pub fn with_sorted_keys(mut self, sort_keys: bool) -> Self {
+        self.sort_keys = sort_keys;
+        self
+    }
+
+    /// Sets whether a space follows the colon after each key.
+    ///
+    /// # Arguments
+    ///
+    /// * `space` - Whether to write `"key": value` rather than `"key":value`.
+    pub fn with_space_after_colon(mut self, space: bool) -> Self {
+        self.space_after_colon = space;
+        self
+    }
+
+    /// Sets whether a space follows each comma of arrays and objects written on one line, in
+    /// compact mode or when they fit the maximum width.
+    ///
+    /// # Arguments
+    ///
+    /// * `space` - Whether to write `[1, 2]` rather than `[1,2]`.
+    pub fn with_space_after_comma(mut self, space: bool) -> Self {
+        self.space_after_comma = space;
+        self
+    }
+
+    /// Sets whether the output ends with a line ending.
+    ///
+    /// # Arguments
+    ///
+    /// * `trailing_newline` - Whether to end the document with a line ending, as text files do.
+    pub fn with_trailing_newline(mut self, trailing_newline: bool) -> Self {
+        self.trailing_newline = trailing_newline;
+        self
+    }
+
+    /// Sets which characters are escaped in strings and keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `escape` - The escaping options, such as `EscapeOptions::new().with_ascii_only(true)`.
+    pub fn with_escape_options(mut self, escape: EscapeOptions) -> Self {
+        self.escape = escape;
+        self
+    }
+
+    /// Sets how floats are written.
+    ///
+    /// # Arguments
+    ///
+    /// * `numbers` - The number format, such as `NumberFormat::new().with_exponent_thresholds(-4, 16)`.
+    pub fn with_number_format(mut self, numbers: NumberFormat) -> Self {
+        self.numbers = numbers;
+        self
+    }
+
+    /// Sets the line width that arrays and objects are kept within.
+    ///
+    /// An array or object is written on a single line, like `[1.5, 2.5, 3.5]`, when that line
+    /// including its indentation, key and trailing comma fits in `width` columns, and with
+    /// one element or member per line otherwise. Without a width, which is the default, every
+    /// non-empty container is broken.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - The maximum line width in characters, such as `80`.
+    pub fn with_max_width(mut self, width: usize) -> Self {
+        self.max_width = Some(width);
+        self
+    }
+
+    /// Sets whether the output is the RFC 8785 canonical form, which overrides every other
+    /// setting.
+    ///
+    /// # Arguments
+    ///
+    /// * `canonical` - Whether to write canonical JSON for hashing and signing.
+    pub fn with_canonical(mut self, canonical: bool) -> Self {
+        self.canonical = canonical;
+        self
+    }
+
     /// Builds and returns a `JsonFormatter` with the configured settings.
     pub fn build(self) -> JsonFormatter {
+        let indent_unit = self.indent_unit.unwrap_or_else(|| self.indent_char.to_string().repeat(self.indent));
         JsonFormatter {
-            indent: self.indent,
-            indent_char: self.indent_char,
-            current_indent: 0,
+            indent_width: indent_unit.chars().count(),
+            indent_unit,
+            line_ending: self.line_ending,
+            escape: self.escape,
+            numbers: self.numbers,
+            max_width: self.max_width,
+            sort_keys: self.sort_keys,
+            space_after_colon: self.space_after_colon,
+            space_after_comma: self.space_after_comma,
+            trailing_newline: self.trailing_newline,
+            compact: self.compact,
+            canonical: self.canonical,
         }
     }
 }
 
+impl Default for JsonFormatterBuilder {
+    fn default() -> Self {
+        JsonFormatterBuilder::new()
+    }
+}
+
 impl Default for JsonFormatter {
     /// Returns a `JsonFormatter` with default settings (2 spaces for indentation).
     fn default() -> Self {
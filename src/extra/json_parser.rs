@@ -1,29 +1,49 @@
 use std::convert::Infallible;
+use std::io::{BufRead, BufReader, Read};
 
-use indexmap::IndexMap;
-use nom::{branch::alt, bytes::complete::tag, character::complete::{char, multispace0}, combinator::map, Finish, IResult, multi::separated_list0, number::complete::double, sequence::{delimited, separated_pair}};
-use nom::bytes::complete::{escaped_transform, is_not, take_while_m_n};
-use nom::combinator::{map_opt, value};
-use nom::error::Error as NomError;
-use nom::sequence::preceded;
 use thiserror::Error;
 
 use crate::base::casting::CastError;
-use crate::base::JsonValue;
+#[cfg(feature = "arena")]
+use crate::base::{ArenaValue, JsonArena};
+use crate::base::{DuplicateKeyError, DuplicateKeyPolicy, JsonValue, JsonValueRef};
+use crate::extra::encoding::decode_utf8;
+use crate::extra::json_reader::JsonReader;
+use crate::extra::json_tape::JsonTape;
+use crate::extra::json_visitor::JsonVisitor;
+use crate::extra::parse_error::ParseError;
+use crate::extra::parser_options::{Extensions, ParserOptions, SurrogatePolicy};
 
-pub struct JsonParser;
+/// A JSON parser.
+///
+/// The associated functions such as `JsonParser::parse` use the default settings. A parser
+/// with its own limits, duplicate-key policy and extensions is created with
+/// `JsonParser::builder()` and used through its methods, which mirror the associated
+/// functions: `read*` for `parse*` and `walk*` for `visit*`, such as `parser.read(text)`.
+#[derive(Debug, Clone, Default)]
+pub struct JsonParser {
+    options: ParserOptions,
+}
 
 /// Error type representing various conversion errors during JSON parsing.
 #[derive(Debug, Error)]
 pub enum ConversationError {
-    /// Parsing error with a specific message.
+    /// Parsing error carrying the location and cause of the failure.
     #[error("Parsing error: {0}")]
-    ParsingError(String),
+    ParsingError(#[from] ParseError),
+
+    /// I/O error raised by the underlying reader while streaming a document.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
 
     /// Generic conversation error with a specific message.
     #[error("Conversation error: {0}")]
     GenericError(String),
 
+    /// A repeated key while building a `JsonObject` that rejects duplicates.
+    #[error(transparent)]
+    DuplicateKey(#[from] DuplicateKeyError),
+
     /// Conversion error from CastError.
     #[error(transparent)]
     Cast(#[from] CastError),
@@ -33,14 +53,37 @@ pub enum ConversationError {
     Infallible(#[from] Infallible),
 }
 
-impl From<NomError<&str>> for ConversationError {
-    fn from(error: NomError<&str>) -> Self {
-        ConversationError::ParsingError(error.to_string())
+impl ConversationError {
+    /// Returns the structured parse error if this error was caused by malformed input.
+    pub fn parse_error(&self) -> Option<&ParseError> {
+        match self {
+            ConversationError::ParsingError(error) => Some(error),
+            _ => None,
+        }
     }
 }
 
 
 impl JsonParser {
+    /// Returns a new `JsonParserBuilder` to construct a configured `JsonParser`.
+    pub fn builder() -> JsonParserBuilder {
+        JsonParserBuilder::new()
+    }
+
+    /// Returns a parser in strict mode, accepting exactly the documents RFC 8259 accepts.
+    ///
+    /// No syntax extensions are enabled, so leading `+` signs, `.5`, `1.`, leading zeros,
+    /// `nan`, `inf` and raw control characters in strings are all rejected. The default
+    /// nesting limit still applies, as RFC 8259 allows.
+    pub fn strict() -> JsonParser {
+        JsonParser::builder().with_extensions(Extensions::NONE).build()
+    }
+
+    /// Returns the settings of this parser.
+    pub fn options(&self) -> &ParserOptions {
+        &self.options
+    }
+
     /// Parses a JSON string into a `JsonValue`.
     ///
     /// # Arguments
@@ -49,111 +92,335 @@ impl JsonParser {
     ///
     /// # Returns
     ///
-    /// A `Result` containing a `JsonValue` if parsing is successful, otherwise a `ConversationError`.
+    /// A `Result` containing a `JsonValue` if parsing is successful, otherwise a `ConversationError`
+    /// whose `ParseError` points at the offending position. Documents nested deeper than
+    /// `ParserOptions::DEFAULT_MAX_DEPTH` fail with `ParseErrorKind::DepthLimitExceeded`.
     pub fn parse(json_str: &str) -> Result<JsonValue, ConversationError> {
-        // Attempt to parse the JSON string into a `JsonValue`
-        let (remaining, result) = parse_json_value(json_str).finish().map_err(|e| ConversationError::ParsingError(e.to_string()))?;
-
-        // Check if there are any remaining unparsed characters
-        if !remaining.trim().is_empty() {
-            // If there are remaining characters, return an error indicating incomplete parsing
-            return Err(ConversationError::ParsingError(format!(
-                "Incomplete parsing: {} characters were left unparsed. Remaining input: '{}'",
-                remaining.len(),
-                &remaining[..std::cmp::min(20, remaining.len())]
-            )));
-        }
+        JsonParser::default().read(json_str)
+    }
+
+    /// Parses a JSON document given as bytes, such as a buffer received from a socket, into a
+    /// `JsonValue`, without first converting it to a `String`.
+    ///
+    /// The bytes are validated as UTF-8 before parsing. A leading UTF-8 byte order mark is only
+    /// skipped by parsers that accept `Extensions::BYTE_ORDER_MARK`.
+    ///
+    /// # Arguments
+    ///
+    /// * `json_bytes` - The UTF-8 encoded document.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the same `JsonValue` that `parse` would produce for the same text.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ParseErrorKind::InvalidEncoding` error located at the byte offset of the first
+    /// invalid UTF-8 sequence, or at offset `0` for input recognized as UTF-16 or UTF-32, which
+    /// RFC 8259 does not allow outside closed ecosystems.
+    pub fn parse_bytes(json_bytes: &[u8]) -> Result<JsonValue, ConversationError> {
+        JsonParser::default().read_bytes(json_bytes)
+    }
 
-        // Return the successfully parsed `JsonValue`
-        Ok(result)
+    /// Parses a JSON string into a `JsonValueRef` whose strings and keys borrow from `json_str`.
+    ///
+    /// Strings and keys without escape sequences are not copied; only those that had to be
+    /// unescaped are allocated. Use `JsonValueRef::into_owned` to keep the value beyond the
+    /// lifetime of the input.
+    ///
+    /// # Arguments
+    ///
+    /// * `json_str` - A JSON string to parse.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a `JsonValueRef` equal to the `JsonValue` that `parse` would
+    /// produce, otherwise the same `ConversationError`.
+    pub fn parse_borrowed(json_str: &str) -> Result<JsonValueRef<'_>, ConversationError> {
+        JsonParser::default().read_borrowed(json_str)
+    }
+
+    /// Parses a JSON string into an `ArenaValue` whose strings, arrays and objects are allocated
+    /// in `arena` rather than individually on the heap.
+    ///
+    /// The document is freed as a whole when the arena is reset or dropped, which makes
+    /// parsing many short-lived documents into one reused arena cheap. Use
+    /// `ArenaValue::to_json_value` to keep a value beyond that.
+    ///
+    /// # Arguments
+    ///
+    /// * `arena` - The arena to allocate the document in.
+    /// * `json_str` - A JSON string to parse.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing an `ArenaValue` equal to the `JsonValue` that `parse` would
+    /// produce, otherwise the same `ConversationError`.
+    #[cfg(feature = "arena")]
+    pub fn parse_in<'a>(arena: &'a JsonArena, json_str: &str) -> Result<ArenaValue<'a>, ConversationError> {
+        JsonParser::default().read_in(arena, json_str)
     }
-}
 
+    /// Indexes a JSON string into a `JsonTape`, whose values are decoded only when read.
+    ///
+    /// The whole document is validated up front, so reading values from the tape cannot fail
+    /// on malformed input.
+    ///
+    /// # Arguments
+    ///
+    /// * `json_str` - A JSON string to parse.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `JsonTape` if `json_str` is valid, otherwise the same
+    /// `ConversationError` that `parse` would return.
+    pub fn parse_tape(json_str: &str) -> Result<JsonTape<'_>, ConversationError> {
+        JsonParser::default().read_tape(json_str)
+    }
 
+    /// Parses a JSON document read incrementally from `reader` into a `JsonValue`.
+    ///
+    /// The input is consumed through a fixed-size buffer, so the raw document never has to be
+    /// held in memory as a whole. Failures of the reader are reported as
+    /// `ConversationError::Io`, malformed input as `ConversationError::ParsingError`.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The source of the JSON document.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the same `JsonValue` that `parse` would produce for the same text.
+    pub fn parse_reader<R: Read>(reader: R) -> Result<JsonValue, ConversationError> {
+        JsonParser::default().read_reader(reader)
+    }
 
-type JsonObject = IndexMap<String, JsonValue>;
-type JsonArray = Vec<JsonValue>;
+    /// Parses a JSON document from an already buffered reader, reusing its buffer.
+    ///
+    /// See `parse_reader` for details.
+    pub fn parse_buf_reader<R: BufRead>(reader: R) -> Result<JsonValue, ConversationError> {
+        JsonParser::default().read_buf_reader(reader)
+    }
 
-fn ws<'a, F: 'a, O, E>(inner: F) -> impl FnMut(&'a str) -> IResult<&'a str, O, E>
-    where
-        F: FnMut(&'a str) -> IResult<&'a str, O, E>,
-        E: nom::error::ParseError<&'a str>,
-{
-    delimited(multispace0, inner, multispace0)
-}
+    /// Parses a JSON string, reporting every token to `visitor` instead of building a `JsonValue`.
+    ///
+    /// # Arguments
+    ///
+    /// * `json_str` - A JSON string to parse.
+    /// * `visitor` - The callbacks to drive.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` once the whole document has been visited, otherwise the first error raised by
+    /// the input or by the visitor.
+    pub fn visit<'de, V>(json_str: &'de str, visitor: &mut V) -> Result<(), ConversationError>
+        where
+            V: JsonVisitor<'de> + ?Sized,
+    {
+        JsonParser::default().walk(json_str, visitor)
+    }
 
-fn parse_str(input: &str) -> IResult<&str, String> {
-    let transform_escaped = escaped_transform(
-        is_not("\\\""),
-        '\\',
-        alt((
-            value('\"', char('\"')),
-            value('\\', char('\\')),
-            value('/', char('/')),
-            value('\x08', char('b')),  // \b
-            value('\x0C', char('f')),  // \f
-            value('\n', char('n')),
-            value('\r', char('r')),
-            value('\t', char('t')),
-            map_opt(
-                preceded(char('u'), take_while_m_n(4, 4, |c: char| c.is_digit(16))),
-                |s| u16::from_str_radix(s, 16).ok().map(|cp| std::char::from_u32(cp as u32).unwrap())
-            )
-        )),
-    );
-    delimited(char('"'), transform_escaped, char('"'))(input)
+    /// Parses a JSON document read incrementally from `reader`, reporting every token to `visitor`.
+    ///
+    /// See `visit` and `parse_reader` for details.
+    pub fn visit_reader<'de, R, V>(reader: R, visitor: &mut V) -> Result<(), ConversationError>
+        where
+            R: Read,
+            V: JsonVisitor<'de> + ?Sized,
+    {
+        JsonParser::default().walk_reader(reader, visitor)
+    }
 }
 
+impl JsonParser {
+    /// Parses a JSON string into a `JsonValue` with the settings of this parser.
+    ///
+    /// See `JsonParser::parse` for details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rusty_json::extra::JsonParser;
+    ///
+    /// let parser = JsonParser::builder().with_max_depth(4).build();
+    /// assert!(parser.read("[[[[[1]]]]]").is_err());
+    /// assert!(JsonParser::parse("[[[[[1]]]]]").is_ok());
+    /// ```
+    pub fn read(&self, json_str: &str) -> Result<JsonValue, ConversationError> {
+        JsonReader::from_text(json_str).with_options(self.options.clone()).read_document()
+    }
+
+    /// Parses a UTF-8 encoded JSON document into a `JsonValue` with the settings of this parser.
+    ///
+    /// See `JsonParser::parse_bytes` for details.
+    pub fn read_bytes(&self, json_bytes: &[u8]) -> Result<JsonValue, ConversationError> {
+        self.read(decode_utf8(json_bytes)?)
+    }
 
+    /// Parses a JSON string into a `JsonValueRef` borrowing from it, with the settings of this
+    /// parser.
+    ///
+    /// See `JsonParser::parse_borrowed` for details.
+    pub fn read_borrowed<'a>(&self, json_str: &'a str) -> Result<JsonValueRef<'a>, ConversationError> {
+        JsonReader::from_text(json_str).with_options(self.options.clone()).read_borrowed_document()
+    }
 
+    /// Parses a JSON string into an `ArenaValue` allocated in `arena`, with the settings of this
+    /// parser.
+    ///
+    /// See `JsonParser::parse_in` for details.
+    #[cfg(feature = "arena")]
+    pub fn read_in<'a>(&self, arena: &'a JsonArena, json_str: &str) -> Result<ArenaValue<'a>, ConversationError> {
+        JsonReader::from_text(json_str).with_options(self.options.clone()).read_arena_document(arena)
+    }
 
-fn parse_bool(input: &str) -> IResult<&str, bool> {
-    alt((
-        value(true, tag("true")),
-        value(false, tag("false")),
-    ))(input)
-}
+    /// Indexes a JSON string into a lazily decoded `JsonTape`, with the settings of this parser.
+    ///
+    /// See `JsonParser::parse_tape` for details.
+    pub fn read_tape<'a>(&self, json_str: &'a str) -> Result<JsonTape<'a>, ConversationError> {
+        JsonTape::build(json_str, self.options.clone())
+    }
 
-fn parse_null(input: &str) -> IResult<&str, ()> {
-    value((), tag("null"))(input)
+    /// Parses a JSON document read incrementally from `reader` with the settings of this parser.
+    ///
+    /// See `JsonParser::parse_reader` for details.
+    pub fn read_reader<R: Read>(&self, reader: R) -> Result<JsonValue, ConversationError> {
+        self.read_buf_reader(BufReader::new(reader))
+    }
+
+    /// Parses a JSON document from an already buffered reader with the settings of this parser,
+    /// reusing its buffer.
+    ///
+    /// See `JsonParser::parse_buf_reader` for details.
+    pub fn read_buf_reader<R: BufRead>(&self, reader: R) -> Result<JsonValue, ConversationError> {
+        JsonReader::from_buf_reader(reader).with_options(self.options.clone()).read_document()
+    }
+
+    /// Parses a JSON string with the settings of this parser, reporting every token to `visitor`.
+    ///
+    /// See `JsonParser::visit` for details.
+    pub fn walk<'de, V>(&self, json_str: &'de str, visitor: &mut V) -> Result<(), ConversationError>
+        where
+            V: JsonVisitor<'de> + ?Sized,
+    {
+        JsonReader::from_text(json_str).with_options(self.options.clone()).visit_document(visitor)
+    }
+
+    /// Parses a JSON document read incrementally from `reader` with the settings of this parser,
+    /// reporting every token to `visitor`.
+    ///
+    /// See `JsonParser::visit_reader` for details.
+    pub fn walk_reader<'de, R, V>(&self, reader: R, visitor: &mut V) -> Result<(), ConversationError>
+        where
+            R: Read,
+            V: JsonVisitor<'de> + ?Sized,
+    {
+        JsonReader::from_reader(reader).with_options(self.options.clone()).visit_document(visitor)
+    }
 }
 
-fn parse_array(input: &str) -> IResult<&str, JsonArray> {
-    delimited(
-        ws(char('[')),
-        separated_list0(
-            ws(char(',')),
-            parse_json_value
-        ),
-        ws(char(']'))
-    )(input)
+/// A builder for constructing `JsonParser` instances.
+pub struct JsonParserBuilder {
+    options: ParserOptions,
 }
 
-fn parse_object(input: &str) -> IResult<&str, JsonObject> {
-    map(
-        delimited(
-            ws(char('{')),
-            separated_list0(
-                ws(char(',')),
-                separated_pair(
-                    parse_str,
-                    ws(char(':')),
-                    parse_json_value
-                )
-            ),
-            ws(char('}'))
-        ),
-        |pairs| pairs.into_iter().map(|(k, v)| (k.to_string(), v)).collect()
-    )(input)
+impl JsonParserBuilder {
+    /// Creates a new `JsonParserBuilder` with default settings: nesting limited to
+    /// `ParserOptions::DEFAULT_MAX_DEPTH` levels, no other limits, the last value of a repeated
+    /// key wins, the extensions of `Extensions::default()` are accepted, lone surrogates
+    /// are rejected and keys are not interned.
+    pub fn new() -> Self {
+        JsonParserBuilder {
+            options: ParserOptions::default(),
+        }
+    }
+
+    /// Sets the maximum nesting depth.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_depth` - The number of arrays and objects that may be open at the same time,
+    ///   `usize::MAX` to lift the limit.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.options.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Sets the maximum string length.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_length` - The maximum length of a string or key, in bytes after unescaping.
+    pub fn with_max_string_length(mut self, max_length: usize) -> Self {
+        self.options.max_string_length = Some(max_length);
+        self
+    }
+
+    /// Sets the maximum document size.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_size` - The maximum size of the input, in bytes, including whitespace.
+    pub fn with_max_document_size(mut self, max_size: usize) -> Self {
+        self.options.max_document_size = Some(max_size);
+        self
+    }
+
+    /// Sets what happens when an object contains the same key twice.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - The policy to apply to repeated keys.
+    pub fn with_duplicate_keys(mut self, policy: DuplicateKeyPolicy) -> Self {
+        self.options.duplicate_keys = policy;
+        self
+    }
+
+    /// Sets the accepted syntax extensions, replacing the default ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `extensions` - The extensions to accept, `Extensions::NONE` for standard JSON only.
+    pub fn with_extensions(mut self, extensions: Extensions) -> Self {
+        self.options.extensions = extensions;
+        self
+    }
+
+    /// Sets what happens to a `\u` escape of a lone UTF-16 surrogate.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - `SurrogatePolicy::Reject` to fail, `SurrogatePolicy::Replace` to decode it as U+FFFD.
+    pub fn with_lone_surrogates(mut self, policy: SurrogatePolicy) -> Self {
+        self.options.lone_surrogates = policy;
+        self
+    }
+
+    /// Sets whether equal object keys of a document share one allocation.
+    ///
+    /// Arrays of records repeat the same few keys thousands of times; with interning, every
+    /// occurrence of a key shares one reference-counted string, so each distinct key is
+    /// stored once per document. Lookups and iteration are unaffected. The table of keys is
+    /// kept for one document only, and costs a hash lookup per key while parsing.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - `true` to share equal keys, `false` to allocate every key on its own.
+    pub fn with_key_interning(mut self, enabled: bool) -> Self {
+        self.options.intern_keys = enabled;
+        self
+    }
+
+    /// Builds and returns a `JsonParser` with the configured settings.
+    pub fn build(self) -> JsonParser {
+        JsonParser {
+            options: self.options,
+        }
+    }
 }
 
-fn parse_json_value(input: &str) -> IResult<&str, JsonValue> {
-    ws(alt((
-        map(parse_str, |s| JsonValue::String(s.to_string())),
-        map(double, JsonValue::Number),
-        map(parse_bool, JsonValue::Boolean),
-        map(parse_null, |_| JsonValue::Null),
-        map(parse_array, |v| JsonValue::from(v)),
-        map(parse_object, |v| JsonValue::from(v)),
-    )))(input)
+impl Default for JsonParserBuilder {
+    fn default() -> Self {
+        JsonParserBuilder::new()
+    }
 }
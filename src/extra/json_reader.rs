@@ -0,0 +1,846 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read};
+use std::ops::Range;
+
+#[cfg(feature = "arena")]
+use crate::base::{ArenaValue, JsonArena};
+use crate::base::{DuplicateKeyPolicy, JsonNumber, JsonValue, JsonValueRef};
+use crate::extra::block_scan::{string_content_len, to_str, token_len};
+use crate::extra::encoding::BYTE_ORDER_MARK;
+use crate::extra::json_parser::ConversationError;
+use crate::extra::json_source::{IoSource, JsonSource, SliceSource};
+#[cfg(feature = "arena")]
+use crate::extra::json_visitor::ArenaTreeBuilder;
+use crate::extra::json_visitor::{dispatch, JsonVisitor, RefTreeBuilder, TreeBuilder};
+use crate::extra::number_scanner::scan_number;
+use crate::extra::parse_error::{ParseErrorKind, Position};
+use crate::extra::parser_options::{Extensions, ParserOptions, SurrogatePolicy};
+
+/// A single token of a JSON document, as produced by `JsonReader`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonEvent<'de> {
+    StartObject,
+    EndObject,
+    StartArray,
+    EndArray,
+    Key(Cow<'de, str>),
+    String(Cow<'de, str>),
+    Number(JsonNumber),
+    Boolean(bool),
+    Null,
+}
+
+impl JsonEvent<'_> {
+    /// Returns `true` if this event begins a value (a scalar or the start of a container).
+    pub fn starts_value(&self) -> bool {
+        !matches!(self, JsonEvent::EndObject | JsonEvent::EndArray | JsonEvent::Key(_))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Container {
+    Array,
+    Object,
+}
+
+/// What the reader expects to see next.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Expect {
+    Value,
+    FirstElement,
+    FirstKey,
+    Key,
+    ArraySeparator,
+    ObjectSeparator,
+    Finished,
+    Eof,
+}
+
+/// A pull reader that yields a document as a sequence of `JsonEvent`s.
+///
+/// Nesting is tracked on an explicit stack, so documents are read with constant stack
+/// usage and memory proportional to the nesting depth only. Whole values can be skipped
+/// without decoding them (`skip_value`) or materialized on demand (`read_value`).
+///
+/// # Example
+///
+/// ```
+/// use rusty_json::extra::{JsonEvent, JsonReader};
+///
+/// let data = r#"{"id": 7, "payload": {"large": [1, 2, 3]}, "name": "Ammar"}"#;
+/// let mut reader = JsonReader::from_reader(data.as_bytes());
+///
+/// while let Some(event) = reader.next_event().unwrap() {
+///     match event {
+///         JsonEvent::Key(key) if key == "payload" => reader.skip_value().unwrap(),
+///         JsonEvent::Key(key) if key == "name" => {
+///             assert_eq!(reader.read_value().unwrap().parse::<String>().unwrap(), "Ammar");
+///         }
+///         _ => {}
+///     }
+/// }
+/// ```
+pub struct JsonReader<S> {
+    source: S,
+    scratch: Vec<u8>,
+    stack: Vec<Container>,
+    expect: Expect,
+    peeked: Option<PeekedEvent>,
+    options: ParserOptions,
+    seen_keys: Vec<HashMap<String, Position>>,
+    /// The byte offset where the token of the last event started.
+    token_start: usize,
+}
+
+/// An event that was looked at with `peek_event` but not consumed yet.
+struct PeekedEvent {
+    event: JsonEvent<'static>,
+    depth: usize,
+}
+
+impl<R: Read> JsonReader<IoSource<BufReader<R>>> {
+    /// Creates a reader pulling the document from `reader` through a fixed-size buffer.
+    pub fn from_reader(reader: R) -> Self {
+        JsonReader::from_buf_reader(BufReader::new(reader))
+    }
+}
+
+impl<R: BufRead> JsonReader<IoSource<R>> {
+    /// Creates a reader over an already buffered reader, reusing its buffer.
+    pub fn from_buf_reader(reader: R) -> Self {
+        JsonReader::new(IoSource::new(reader))
+    }
+}
+
+impl<'de> JsonReader<SliceSource<'de>> {
+    /// Creates a reader over a document that is already in memory.
+    pub fn from_text(input: &'de str) -> Self {
+        JsonReader::new(SliceSource::new(input))
+    }
+}
+
+impl<'de, S: JsonSource<'de>> JsonReader<S> {
+    pub(crate) fn new(source: S) -> Self {
+        JsonReader {
+            source,
+            scratch: Vec::new(),
+            stack: Vec::new(),
+            expect: Expect::Value,
+            peeked: None,
+            options: ParserOptions::default(),
+            seen_keys: Vec::new(),
+            token_start: 0,
+        }
+    }
+
+    /// Applies the limits, duplicate-key policy and extensions of `options`.
+    ///
+    /// This should be called before the first event is read.
+    pub fn with_options(mut self, options: ParserOptions) -> Self {
+        self.source.set_limit(options.max_document_size.unwrap_or(usize::MAX));
+        self.options = options;
+        self
+    }
+
+    /// Returns the number of containers enclosing the reader's current position.
+    pub fn depth(&self) -> usize {
+        match &self.peeked {
+            Some(peeked) => peeked.depth,
+            None => self.stack.len(),
+        }
+    }
+
+    /// Reads the next event, or `None` once the document has been read completely.
+    ///
+    /// Anything but whitespace after the top-level value is reported as a parse error.
+    pub fn next_event(&mut self) -> Result<Option<JsonEvent<'de>>, ConversationError> {
+        if let Some(peeked) = self.peeked.take() {
+            return Ok(Some(peeked.event));
+        }
+        self.advance(true)
+    }
+
+    /// Returns the next event without consuming it.
+    pub fn peek_event(&mut self) -> Result<Option<&JsonEvent<'de>>, ConversationError> {
+        if self.peeked.is_none() {
+            let depth = self.stack.len();
+            if let Some(event) = self.advance(true)? {
+                self.peeked = Some(PeekedEvent { event: into_static(event), depth });
+            }
+        }
+        Ok(self.peeked.as_ref().map(|peeked| &peeked.event))
+    }
+
+    /// Skips the next value, including everything nested inside it.
+    ///
+    /// Strings and numbers of the skipped value are checked for well-formedness but not
+    /// decoded, so no memory is allocated for them.
+    pub fn skip_value(&mut self) -> Result<(), ConversationError> {
+        let first = match self.peeked.take() {
+            Some(peeked) => Some(peeked.event),
+            None => self.advance(false)?,
+        };
+        let mut depth = match first {
+            Some(JsonEvent::StartObject | JsonEvent::StartArray) => 1,
+            Some(event) if event.starts_value() => return Ok(()),
+            _ => return Err(self.unexpected()),
+        };
+        while depth > 0 {
+            match self.advance(false)? {
+                Some(JsonEvent::StartObject | JsonEvent::StartArray) => depth += 1,
+                Some(JsonEvent::EndObject | JsonEvent::EndArray) => depth -= 1,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the next value, including everything nested inside it, into a `JsonValue`.
+    pub fn read_value(&mut self) -> Result<JsonValue, ConversationError> {
+        let mut builder = TreeBuilder::new(self.options.duplicate_keys, self.options.intern_keys);
+        self.visit_value(&mut builder)?;
+        builder.finish().ok_or_else(|| self.source.error("a value"))
+    }
+
+    /// Reads the next value, including everything nested inside it, reporting each of its
+    /// events to `visitor` instead of building a `JsonValue`.
+    ///
+    /// Reading stops at the first error, whether it comes from the input or from the visitor.
+    pub fn visit_value<V>(&mut self, visitor: &mut V) -> Result<(), ConversationError>
+        where
+            V: JsonVisitor<'de> + ?Sized,
+    {
+        let mut depth = match self.next_event()? {
+            Some(event @ (JsonEvent::StartObject | JsonEvent::StartArray)) => {
+                dispatch(visitor, event)?;
+                1
+            }
+            Some(event) if event.starts_value() => return dispatch(visitor, event),
+            _ => return Err(self.unexpected()),
+        };
+        while depth > 0 {
+            let event = match self.next_event()? {
+                Some(event) => event,
+                None => return Err(self.source.error("a value")),
+            };
+            match event {
+                JsonEvent::StartObject | JsonEvent::StartArray => depth += 1,
+                JsonEvent::EndObject | JsonEvent::EndArray => depth -= 1,
+                _ => {}
+            }
+            dispatch(visitor, event)?;
+        }
+        Ok(())
+    }
+
+    /// Builds the error for an event that does not start a value, located at its token.
+    fn unexpected(&mut self) -> ConversationError {
+        let count = self.source.offset() - self.token_start;
+        self.source.error_before(count, "a value")
+    }
+
+    /// Reads a complete document: a single value followed by nothing but whitespace.
+    pub(crate) fn read_document(mut self) -> Result<JsonValue, ConversationError> {
+        let value = self.read_value()?;
+        self.advance(false)?;
+        Ok(value)
+    }
+
+    /// Reads a complete document into a `JsonValueRef` that keeps the strings borrowed from
+    /// the source where possible.
+    pub(crate) fn read_borrowed_document(mut self) -> Result<JsonValueRef<'de>, ConversationError> {
+        let mut builder = RefTreeBuilder::new(self.options.duplicate_keys);
+        self.visit_value(&mut builder)?;
+        let value = builder.finish().ok_or_else(|| self.source.error("a value"))?;
+        self.advance(false)?;
+        Ok(value)
+    }
+
+    /// Reads a complete document into `arena`, rejecting anything after the top-level value.
+    #[cfg(feature = "arena")]
+    pub(crate) fn read_arena_document<'a>(mut self, arena: &'a JsonArena) -> Result<ArenaValue<'a>, ConversationError> {
+        let mut builder = ArenaTreeBuilder::new(arena, self.options.duplicate_keys);
+        self.visit_value(&mut builder)?;
+        let value = builder.finish().ok_or_else(|| self.source.error("a value"))?;
+        self.advance(false)?;
+        Ok(value)
+    }
+
+    /// Reports a complete document to `visitor`, rejecting anything after the top-level value.
+    pub(crate) fn visit_document<V>(mut self, visitor: &mut V) -> Result<(), ConversationError>
+        where
+            V: JsonVisitor<'de> + ?Sized,
+    {
+        self.visit_value(visitor)?;
+        self.advance(false)?;
+        Ok(())
+    }
+
+    /// Reads the next event for a `JsonTape` together with the byte range of its token.
+    ///
+    /// Keys are decoded, so that repeated keys are still detected, while strings and numbers
+    /// are only validated and must be decoded from their range later.
+    pub(crate) fn next_token(&mut self) -> Result<Option<(JsonEvent<'de>, Range<usize>)>, ConversationError> {
+        let decode = matches!(self.expect, Expect::FirstKey | Expect::Key | Expect::ObjectSeparator);
+        let event = self.advance(decode)?;
+        Ok(event.map(|event| (event, self.token_start..self.source.offset())))
+    }
+
+    fn advance(&mut self, decode: bool) -> Result<Option<JsonEvent<'de>>, ConversationError> {
+        if self.source.offset() == 0
+            && self.allows(Extensions::BYTE_ORDER_MARK)
+            && self.source.buffer()?.starts_with(BYTE_ORDER_MARK)
+        {
+            self.source.consume(BYTE_ORDER_MARK.len());
+        }
+        loop {
+            self.skip_ignored()?;
+            self.token_start = self.source.offset();
+            match self.expect {
+                Expect::Value => return self.read_scalar_or_start(decode).map(Some),
+                Expect::FirstElement => {
+                    if self.source.eat(b']')? {
+                        return Ok(Some(self.close(JsonEvent::EndArray)));
+                    }
+                    self.expect = Expect::Value;
+                }
+                Expect::FirstKey => {
+                    if self.source.eat(b'}')? {
+                        return Ok(Some(self.close(JsonEvent::EndObject)));
+                    }
+                    self.expect = Expect::Key;
+                }
+                Expect::Key => {
+                    if self.source.peek()? != Some(b'"') {
+                        return Err(self.source.error("a string key"));
+                    }
+                    let start = match decode && self.options.duplicate_keys == DuplicateKeyPolicy::Reject {
+                        true => Some(self.source.position()),
+                        false => None,
+                    };
+                    let key = self.read_str(decode)?;
+                    if let Some(start) = start {
+                        self.check_unique(&key, start)?;
+                    }
+                    self.skip_ignored()?;
+                    if !self.source.eat(b':')? {
+                        return Err(self.source.error("':'"));
+                    }
+                    self.expect = Expect::Value;
+                    return Ok(Some(JsonEvent::Key(key)));
+                }
+                Expect::ArraySeparator => match self.source.peek()? {
+                    Some(b',') => {
+                        self.source.consume(1);
+                        self.expect = match self.allows(Extensions::TRAILING_COMMAS) {
+                            true => Expect::FirstElement,
+                            false => Expect::Value,
+                        };
+                    }
+                    Some(b']') => {
+                        self.source.consume(1);
+                        return Ok(Some(self.close(JsonEvent::EndArray)));
+                    }
+                    _ => return Err(self.source.error("',' or ']'")),
+                },
+                Expect::ObjectSeparator => match self.source.peek()? {
+                    Some(b',') => {
+                        self.source.consume(1);
+                        self.expect = match self.allows(Extensions::TRAILING_COMMAS) {
+                            true => Expect::FirstKey,
+                            false => Expect::Key,
+                        };
+                    }
+                    Some(b'}') => {
+                        self.source.consume(1);
+                        return Ok(Some(self.close(JsonEvent::EndObject)));
+                    }
+                    _ => return Err(self.source.error("',' or '}'")),
+                },
+                Expect::Finished => {
+                    if self.source.peek()?.is_some() {
+                        return Err(self.source.error("end of input"));
+                    }
+                    self.expect = Expect::Eof;
+                    return Ok(None);
+                }
+                Expect::Eof => return Ok(None),
+            }
+        }
+    }
+
+    fn allows(&self, extension: Extensions) -> bool {
+        self.options.extensions.contains(extension)
+    }
+
+    /// Skips whitespace, and comments if they are enabled.
+    fn skip_ignored(&mut self) -> Result<(), ConversationError> {
+        self.source.skip_whitespace()?;
+        while self.allows(Extensions::COMMENTS) && self.source.peek()? == Some(b'/') {
+            self.source.consume(1);
+            match self.source.peek()? {
+                Some(b'/') => loop {
+                    let buffer = self.source.buffer()?;
+                    if buffer.is_empty() {
+                        break;
+                    }
+                    match buffer.iter().position(|&b| b == b'\n') {
+                        Some(end) => {
+                            self.source.consume(end);
+                            break;
+                        }
+                        None => {
+                            let length = buffer.len();
+                            self.source.consume(length);
+                        }
+                    }
+                },
+                Some(b'*') => {
+                    self.source.consume(1);
+                    let mut star = false;
+                    loop {
+                        match self.source.peek()? {
+                            Some(b'/') if star => break,
+                            Some(b) => star = b == b'*',
+                            None => return Err(self.source.error("'*/' to close the comment")),
+                        }
+                        self.source.consume(1);
+                    }
+                    self.source.consume(1);
+                }
+                _ => return Err(self.source.error("'/' or '*' to start a comment")),
+            }
+            self.source.skip_whitespace()?;
+        }
+        Ok(())
+    }
+
+    /// Fails if `key`, which starts at `start`, was already seen in the innermost object.
+    fn check_unique(&mut self, key: &str, start: Position) -> Result<(), ConversationError> {
+        let Some(seen) = self.seen_keys.last_mut() else {
+            return Ok(());
+        };
+        let Some(&first) = seen.get(key) else {
+            seen.insert(key.to_owned(), start);
+            return Ok(());
+        };
+        let back = self.source.offset() - start.offset();
+        match self.source.report(back, ParseErrorKind::DuplicateKey, "a unique key") {
+            ConversationError::ParsingError(error) => Err(error.with_duplicate(key.to_owned(), first).into()),
+            other => Err(other),
+        }
+    }
+
+    /// Pops the innermost container and returns its closing event.
+    fn close(&mut self, event: JsonEvent<'de>) -> JsonEvent<'de> {
+        if self.stack.pop() == Some(Container::Object) && self.options.duplicate_keys == DuplicateKeyPolicy::Reject {
+            self.seen_keys.pop();
+        }
+        self.after_value();
+        event
+    }
+
+    fn open(&mut self, container: Container) -> Result<JsonEvent<'de>, ConversationError> {
+        if let Some(max_depth) = self.options.max_depth {
+            if self.stack.len() >= max_depth {
+                let expected = format!("at most {} nested arrays or objects", max_depth);
+                return Err(self.source.report(0, ParseErrorKind::DepthLimitExceeded, &expected));
+            }
+        }
+        self.source.consume(1);
+        self.stack.push(container);
+        match container {
+            Container::Array => {
+                self.expect = Expect::FirstElement;
+                Ok(JsonEvent::StartArray)
+            }
+            Container::Object => {
+                if self.options.duplicate_keys == DuplicateKeyPolicy::Reject {
+                    self.seen_keys.push(HashMap::new());
+                }
+                self.expect = Expect::FirstKey;
+                Ok(JsonEvent::StartObject)
+            }
+        }
+    }
+
+    fn after_value(&mut self) {
+        self.expect = match self.stack.last() {
+            None => Expect::Finished,
+            Some(Container::Array) => Expect::ArraySeparator,
+            Some(Container::Object) => Expect::ObjectSeparator,
+        };
+    }
+
+    fn read_scalar_or_start(&mut self, decode: bool) -> Result<JsonEvent<'de>, ConversationError> {
+        let lenient_numbers = self.allows(Extensions::LENIENT_NUMBERS);
+        let nan_and_infinity = self.allows(Extensions::NAN_AND_INFINITY);
+        let event = match self.source.peek()? {
+            Some(b'[') => return self.open(Container::Array),
+            Some(b'{') => return self.open(Container::Object),
+            Some(b'"') => JsonEvent::String(self.read_str(decode)?),
+            Some(b't') => self.read_literal(b"true", 0, JsonEvent::Boolean(true))?,
+            Some(b'f') => self.read_literal(b"false", 0, JsonEvent::Boolean(false))?,
+            Some(b'n') if !nan_and_infinity => self.read_literal(b"null", 0, JsonEvent::Null)?,
+            Some(first @ (b'n' | b'N')) if nan_and_infinity => {
+                self.source.consume(1);
+                match self.source.peek()? {
+                    Some(b'u') if first == b'n' => self.read_literal(b"null", 1, JsonEvent::Null)?,
+                    _ => self.read_exception(b"nan", 1, f64::NAN)?,
+                }
+            }
+            Some(b'i' | b'I') if nan_and_infinity => self.read_exception(b"inf", 0, f64::INFINITY)?,
+            Some(b'+' | b'.') if lenient_numbers => JsonEvent::Number(self.read_number(decode)?),
+            Some(b'-' | b'0'..=b'9') => JsonEvent::Number(self.read_number(decode)?),
+            _ => return Err(self.source.error("a value")),
+        };
+        self.after_value();
+        Ok(event)
+    }
+
+    /// Reads the rest of `literal`, of which the first `matched` bytes were already consumed.
+    /// A mismatch is reported at the start of the token.
+    fn read_literal(&mut self, literal: &[u8], matched: usize, event: JsonEvent<'de>) -> Result<JsonEvent<'de>, ConversationError> {
+        for (index, &expected) in literal.iter().enumerate().skip(matched) {
+            if !self.source.eat(expected)? {
+                return Err(self.source.error_before(index, "a value"));
+            }
+        }
+        Ok(event)
+    }
+
+    /// Reads the case-insensitive `nan` and `inf` spellings accepted by `JsonParser::parse`.
+    fn read_exception(&mut self, spelling: &[u8], matched: usize, value: f64) -> Result<JsonEvent<'de>, ConversationError> {
+        for (index, &expected) in spelling.iter().enumerate().skip(matched) {
+            match self.source.peek()? {
+                Some(b) if b.eq_ignore_ascii_case(&expected) => self.source.consume(1),
+                _ => return Err(self.source.error_before(index, "a value")),
+            }
+        }
+        Ok(JsonEvent::Number(JsonNumber::from_f64(value)))
+    }
+
+    fn read_number(&mut self, decode: bool) -> Result<JsonNumber, ConversationError> {
+        let lenient = self.allows(Extensions::LENIENT_NUMBERS);
+        self.scratch.clear();
+        // Plain integers, the common case, are taken in one piece when their whole token is
+        // in the buffer; everything else goes through the checks below.
+        let buffer = self.source.buffer()?;
+        let length = token_len(buffer);
+        if length < buffer.len() && is_plain_integer(&buffer[..length]) {
+            self.scratch.extend_from_slice(&buffer[..length]);
+            self.source.consume(length);
+            return self.decode_number(decode);
+        }
+        if let Some(sign @ (b'-' | b'+')) = self.source.peek()? {
+            self.scratch.push(sign);
+            self.source.consume(1);
+            let infinity = matches!(self.source.peek()?, Some(b'i' | b'I'));
+            if sign == b'-' && infinity && self.allows(Extensions::NAN_AND_INFINITY) {
+                self.read_exception(b"inf", 0, f64::NEG_INFINITY)?;
+                return Ok(JsonNumber::from_f64(f64::NEG_INFINITY));
+            }
+        }
+        let integer_digits = self.take_digits()?;
+        if !lenient && integer_digits == 0 {
+            return Err(self.source.error("a digit"));
+        }
+        if !lenient && integer_digits > 1 && self.scratch[self.scratch.len() - integer_digits] == b'0' {
+            return Err(self.source.error_before(integer_digits - 1, "a number without leading zeros"));
+        }
+        if self.source.eat(b'.')? {
+            self.scratch.push(b'.');
+            let fraction_digits = self.take_digits()?;
+            if fraction_digits == 0 && (!lenient || integer_digits == 0) {
+                return Err(self.source.error("a digit"));
+            }
+        } else if integer_digits == 0 {
+            return Err(self.source.error("a number"));
+        }
+        if let Some(e @ (b'e' | b'E')) = self.source.peek()? {
+            self.scratch.push(e);
+            self.source.consume(1);
+            if let Some(sign @ (b'-' | b'+')) = self.source.peek()? {
+                self.scratch.push(sign);
+                self.source.consume(1);
+            }
+            if self.take_digits()? == 0 {
+                return Err(self.source.error("a digit"));
+            }
+        }
+        self.decode_number(decode)
+    }
+
+    /// Decodes the number just read into the scratch buffer, unless `decode` is false.
+    fn decode_number(&mut self, decode: bool) -> Result<JsonNumber, ConversationError> {
+        if !decode {
+            return Ok(JsonNumber::from(0));
+        }
+        scan_number(&self.scratch).ok_or_else(|| self.source.error("a number"))
+    }
+
+    fn take_digits(&mut self) -> Result<usize, ConversationError> {
+        let mut count = 0;
+        while let Some(digit @ b'0'..=b'9') = self.source.peek()? {
+            self.scratch.push(digit);
+            self.source.consume(1);
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Reads a string token. When `decode` is false the string is validated but not
+    /// returned, and an empty string is handed back instead.
+    fn read_str(&mut self, decode: bool) -> Result<Cow<'de, str>, ConversationError> {
+        let control_characters = self.allows(Extensions::CONTROL_CHARACTERS);
+        let max_length = self.options.max_string_length.unwrap_or(usize::MAX);
+        let mut length = 0;
+        self.source.consume(1);
+        self.scratch.clear();
+        // Until the first escape, a string of an in-memory document is borrowed from it
+        // rather than copied into the scratch buffer.
+        let start = self.source.offset();
+        let in_memory = self.source.borrow(start, start).is_some();
+        let mut borrowing = decode && in_memory;
+        // Text borrowed from memory is already valid UTF-8; bytes from a reader are checked
+        // here, before they are consumed, so errors point at the offending byte.
+        let mut utf8 = Utf8Stream::default();
+        loop {
+            let buffer = self.source.buffer()?;
+            if buffer.is_empty() {
+                return Err(self.source.error("'\"' to close the string"));
+            }
+            let plain = string_content_len(buffer, control_characters);
+            let stop = buffer.get(plain).copied();
+            if !in_memory {
+                let checked = utf8.check(&buffer[..plain]).and_then(|()| match stop {
+                    Some(_) => utf8.finish(),
+                    None => Ok(()),
+                });
+                if let Err(back) = checked {
+                    return Err(self.encoding_error(plain, back));
+                }
+            }
+            if decode && !borrowing {
+                self.scratch.extend_from_slice(&buffer[..plain]);
+            }
+            self.source.consume(plain);
+            length += plain;
+            if length > max_length {
+                let expected = format!("a string of at most {} bytes", max_length);
+                return Err(self.source.report(0, ParseErrorKind::StringTooLong, &expected));
+            }
+
+            match stop {
+                Some(b'"') => {
+                    if borrowing {
+                        let end = self.source.offset();
+                        self.source.consume(1);
+                        return Ok(Cow::Borrowed(self.source.borrow(start, end).unwrap_or_default()));
+                    }
+                    self.source.consume(1);
+                    break;
+                }
+                Some(b'\\') => {
+                    if std::mem::take(&mut borrowing) {
+                        let plain = self.source.borrow(start, self.source.offset()).unwrap_or_default();
+                        self.scratch.extend_from_slice(plain.as_bytes());
+                    }
+                    self.source.consume(1);
+                    length += self.read_escape()?;
+                }
+                Some(_) => return Err(self.source.error("an escaped control character")),
+                None => {}
+            }
+        }
+        if !decode {
+            return Ok(Cow::Borrowed(""));
+        }
+
+        match to_str(&self.scratch) {
+            Ok(string) => Ok(Cow::Owned(string.to_owned())),
+            Err(_) => Err(self.source.report(0, ParseErrorKind::InvalidEncoding, "valid UTF-8")),
+        }
+    }
+
+    /// Builds the error for a string byte that is not valid UTF-8, found `back` bytes before
+    /// the end of the `checked` bytes that follow the current position.
+    fn encoding_error(&mut self, checked: usize, back: usize) -> ConversationError {
+        if back > checked {
+            return self.source.report(back - checked, ParseErrorKind::InvalidEncoding, "valid UTF-8");
+        }
+        self.source.consume(checked - back);
+        self.source.report(0, ParseErrorKind::InvalidEncoding, "valid UTF-8")
+    }
+
+    /// Reads an escape sequence after its backslash, returning the length of the decoded text.
+    fn read_escape(&mut self) -> Result<usize, ConversationError> {
+        let unescaped = match self.source.peek()? {
+            Some(b'"') => b'"',
+            Some(b'\\') => b'\\',
+            Some(b'/') => b'/',
+            Some(b'b') => b'\x08',
+            Some(b'f') => b'\x0C',
+            Some(b'n') => b'\n',
+            Some(b'r') => b'\r',
+            Some(b't') => b'\t',
+            Some(b'u') => {
+                self.source.consume(1);
+                return self.read_unicode_escape();
+            }
+            _ => return Err(self.source.error("an escape sequence")),
+        };
+        self.source.consume(1);
+        self.scratch.push(unescaped);
+        Ok(1)
+    }
+
+    /// Reads the rest of a `\u` escape, combining a surrogate pair into a single character, and
+    /// returns the decoded length. Lone surrogates are rejected or replaced, as configured.
+    fn read_unicode_escape(&mut self) -> Result<usize, ConversationError> {
+        let replace = self.options.lone_surrogates == SurrogatePolicy::Replace;
+        let mut length = 0;
+        let mut code = self.read_hex_escape()?;
+        loop {
+            if !(0xD800..0xDC00).contains(&code) {
+                let c = match char::from_u32(code) {
+                    Some(c) => c,
+                    None if replace => char::REPLACEMENT_CHARACTER,
+                    None => return Err(self.source.error_before(6, "a valid unicode escape")),
+                };
+                return Ok(length + self.push_char(c));
+            }
+
+            // A high surrogate must be followed by an escaped low surrogate.
+            let escaped = self.source.eat(b'\\')?;
+            if !(escaped && self.source.eat(b'u')?) {
+                if !replace {
+                    return Err(self.source.error("'\\u' and a low surrogate"));
+                }
+                length += self.push_char(char::REPLACEMENT_CHARACTER);
+                if escaped {
+                    length += self.read_escape()?;
+                }
+                return Ok(length);
+            }
+            let low = self.read_hex_escape()?;
+            if (0xDC00..0xE000).contains(&low) {
+                let pair = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                let c = char::from_u32(pair).unwrap_or(char::REPLACEMENT_CHARACTER);
+                return Ok(length + self.push_char(c));
+            }
+            if !replace {
+                return Err(self.source.error_before(6, "a low surrogate"));
+            }
+            // The second escape starts over, it may itself begin a pair.
+            length += self.push_char(char::REPLACEMENT_CHARACTER);
+            code = low;
+        }
+    }
+
+    /// Appends `c` to the scratch buffer and returns its length in bytes.
+    fn push_char(&mut self, c: char) -> usize {
+        self.scratch.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+        c.len_utf8()
+    }
+
+    /// Reads the four hex digits of a `\u` escape.
+    fn read_hex_escape(&mut self) -> Result<u32, ConversationError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = match self.source.peek()? {
+                Some(b) => (b as char).to_digit(16),
+                None => None,
+            };
+            match digit {
+                Some(digit) => code = code * 16 + digit,
+                None => return Err(self.source.error("an escape sequence")),
+            }
+            self.source.consume(1);
+        }
+        Ok(code)
+    }
+}
+
+/// Checks the raw bytes of a string chunk by chunk, carrying a character that a buffer
+/// refill split over to the next chunk.
+#[derive(Default)]
+struct Utf8Stream {
+    pending: [u8; 4],
+    len: usize,
+}
+
+impl Utf8Stream {
+    /// Checks `chunk`, which directly follows the previously checked bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns how many bytes before the end of `chunk` the first invalid byte is, which
+    /// exceeds the length of `chunk` if it belongs to a character carried over.
+    fn check(&mut self, chunk: &[u8]) -> Result<(), usize> {
+        let carried = self.len;
+        let mut rest = chunk;
+        if carried > 0 {
+            let take = rest.len().min(self.pending.len() - carried);
+            self.pending[carried..carried + take].copy_from_slice(&rest[..take]);
+            match std::str::from_utf8(&self.pending[..carried + take]) {
+                Ok(_) => rest = &rest[take..],
+                Err(error) if error.error_len().is_some() => return Err(carried + chunk.len() - error.valid_up_to()),
+                Err(error) if error.valid_up_to() < carried => {
+                    // The chunk was too short to complete the character.
+                    self.len = carried + take;
+                    return Ok(());
+                }
+                Err(error) => rest = &rest[error.valid_up_to() - carried..],
+            }
+            self.len = 0;
+        }
+
+        let at = match to_str(rest) {
+            Ok(_) => return Ok(()),
+            Err(at) => at,
+        };
+        let tail = &rest[at..];
+        let incomplete = std::str::from_utf8(tail).is_err_and(|error| error.error_len().is_none());
+        if !incomplete {
+            return Err(rest.len() - at);
+        }
+        self.pending[..tail.len()].copy_from_slice(tail);
+        self.len = tail.len();
+        Ok(())
+    }
+
+    /// Checks that no character is left incomplete at the end of the plain text.
+    ///
+    /// # Errors
+    ///
+    /// Returns how many bytes before the end of the checked bytes the incomplete character
+    /// starts.
+    fn finish(&self) -> Result<(), usize> {
+        match self.len {
+            0 => Ok(()),
+            len => Err(len),
+        }
+    }
+}
+
+/// Returns `true` if `token` is an optionally negative integer without leading zeros.
+fn is_plain_integer(token: &[u8]) -> bool {
+    let digits = token.strip_prefix(b"-").unwrap_or(token);
+    !digits.is_empty() && digits.iter().all(u8::is_ascii_digit) && (digits.len() == 1 || digits[0] != b'0')
+}
+
+fn into_static(event: JsonEvent<'_>) -> JsonEvent<'static> {
+    match event {
+        JsonEvent::Key(key) => JsonEvent::Key(Cow::Owned(key.into_owned())),
+        JsonEvent::String(string) => JsonEvent::String(Cow::Owned(string.into_owned())),
+        JsonEvent::StartObject => JsonEvent::StartObject,
+        JsonEvent::EndObject => JsonEvent::EndObject,
+        JsonEvent::StartArray => JsonEvent::StartArray,
+        JsonEvent::EndArray => JsonEvent::EndArray,
+        JsonEvent::Number(number) => JsonEvent::Number(number),
+        JsonEvent::Boolean(boolean) => JsonEvent::Boolean(boolean),
+        JsonEvent::Null => JsonEvent::Null,
+    }
+}
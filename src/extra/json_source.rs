@@ -0,0 +1,271 @@
+use std::io::{BufRead, ErrorKind};
+
+use crate::extra::block_scan::{line_advance, whitespace_len};
+use crate::extra::json_parser::ConversationError;
+use crate::extra::parse_error::{describe_char, ParseError, ParseErrorKind, Position};
+
+/// Maximum number of bytes of the current line kept around for error snippets.
+const LINE_MEMORY: usize = 256;
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// Input that a `JsonReader` can pull bytes from.
+///
+/// The trait is sealed; the crate provides implementations for in-memory documents
+/// (`SliceSource`) and for readers (`IoSource`).
+/// The lifetime `'de` is the lifetime of data that strings read from the source may borrow.
+pub trait JsonSource<'de>: private::Sealed {
+    /// Returns the currently available bytes, reading more input if needed.
+    /// An empty slice means the end of input has been reached.
+    #[doc(hidden)]
+    fn buffer(&mut self) -> Result<&[u8], ConversationError>;
+
+    /// Consumes the next `count` bytes, which must be part of the last returned buffer.
+    #[doc(hidden)]
+    fn consume(&mut self, count: usize);
+
+    /// Returns the byte offset of the next byte.
+    #[doc(hidden)]
+    fn offset(&self) -> usize;
+
+    /// Returns the position of the next byte.
+    #[doc(hidden)]
+    fn position(&mut self) -> Position;
+
+    /// Returns the input between the byte offsets `start` and `end` if it lives for `'de`, so
+    /// that strings without escapes can be borrowed instead of copied.
+    #[doc(hidden)]
+    fn borrow(&self, _start: usize, _end: usize) -> Option<&'de str> {
+        None
+    }
+
+    /// Limits the input to its first `limit` bytes; reading past them is a
+    /// `ParseErrorKind::DocumentTooLarge` error.
+    #[doc(hidden)]
+    fn set_limit(&mut self, limit: usize);
+
+    /// Builds a parse error of the given kind located `count` bytes before the current
+    /// position. The skipped bytes must belong to the current line.
+    #[doc(hidden)]
+    fn report(&mut self, count: usize, kind: ParseErrorKind, expected: &str) -> ConversationError;
+
+    /// Builds a syntax error located `count` bytes before the current position.
+    #[doc(hidden)]
+    fn error_before(&mut self, count: usize, expected: &str) -> ConversationError {
+        self.report(count, ParseErrorKind::Syntax, expected)
+    }
+
+    /// Builds a syntax error at the current position.
+    #[doc(hidden)]
+    fn error(&mut self, expected: &str) -> ConversationError {
+        self.report(0, ParseErrorKind::Syntax, expected)
+    }
+
+    /// Returns the next byte without consuming it.
+    #[doc(hidden)]
+    fn peek(&mut self) -> Result<Option<u8>, ConversationError> {
+        Ok(self.buffer()?.first().copied())
+    }
+
+    /// Consumes the next byte if it is `expected`.
+    #[doc(hidden)]
+    fn eat(&mut self, expected: u8) -> Result<bool, ConversationError> {
+        if self.peek()? == Some(expected) {
+            self.consume(1);
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Skips JSON whitespace.
+    #[doc(hidden)]
+    fn skip_whitespace(&mut self) -> Result<(), ConversationError> {
+        loop {
+            let buffer = self.buffer()?;
+            let count = whitespace_len(buffer);
+            let exhausted = count == buffer.len();
+            if count > 0 {
+                self.consume(count);
+            }
+            if !exhausted || count == 0 {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// A source over a document held in memory as a whole.
+pub struct SliceSource<'de> {
+    input: &'de str,
+    offset: usize,
+    limit: usize,
+    /// The last position computed by `position`, so that lines and columns are only
+    /// counted once however often positions are requested.
+    known: Position,
+}
+
+impl<'de> SliceSource<'de> {
+    pub(crate) fn new(input: &'de str) -> Self {
+        SliceSource { input, offset: 0, limit: usize::MAX, known: Position::new(0, 1, 1) }
+    }
+}
+
+impl private::Sealed for SliceSource<'_> {}
+
+impl<'de> JsonSource<'de> for SliceSource<'de> {
+    fn buffer(&mut self) -> Result<&[u8], ConversationError> {
+        if self.offset >= self.limit && self.offset < self.input.len() {
+            return Err(size_error(self, self.limit));
+        }
+        Ok(&self.input.as_bytes()[self.offset..self.input.len().min(self.limit)])
+    }
+
+    fn consume(&mut self, count: usize) {
+        self.offset += count;
+    }
+
+    fn borrow(&self, start: usize, end: usize) -> Option<&'de str> {
+        self.input.get(start..end)
+    }
+
+    fn offset(&self) -> usize {
+        self.offset
+    }
+
+    fn position(&mut self) -> Position {
+        if self.offset < self.known.offset() {
+            self.known = Position::new(0, 1, 1);
+        }
+        let advance = line_advance(&self.input.as_bytes()[self.known.offset()..self.offset]);
+        let (line, column) = match advance.newlines {
+            0 => (self.known.line(), self.known.column() + advance.columns),
+            newlines => (self.known.line() + newlines, 1 + advance.columns),
+        };
+        self.known = Position::new(self.offset, line, column);
+        self.known
+    }
+
+    fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+    }
+
+    fn report(&mut self, count: usize, kind: ParseErrorKind, expected: &str) -> ConversationError {
+        ParseError::at(self.input, self.offset - count, expected).with_kind(kind).into()
+    }
+}
+
+/// A source over a `BufRead` that tracks the position of the next byte.
+///
+/// Only the reader's own buffer and a short tail of the current line are held in memory.
+pub struct IoSource<R> {
+    reader: R,
+    offset: usize,
+    line: usize,
+    column: usize,
+    line_prefix: Vec<u8>,
+    limit: usize,
+}
+
+impl<R: BufRead> IoSource<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        IoSource {
+            reader,
+            offset: 0,
+            line: 1,
+            column: 1,
+            line_prefix: Vec::new(),
+            limit: usize::MAX,
+        }
+    }
+
+    /// Fills the reader's buffer, retrying interrupted reads, regardless of the size limit.
+    fn fill(&mut self) -> Result<&[u8], ConversationError> {
+        loop {
+            match self.reader.fill_buf() {
+                Ok(_) => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(self.reader.fill_buf()?)
+    }
+}
+
+impl<R> private::Sealed for IoSource<R> {}
+
+impl<R: BufRead> JsonSource<'_> for IoSource<R> {
+    fn buffer(&mut self) -> Result<&[u8], ConversationError> {
+        let available = self.limit.saturating_sub(self.offset);
+        if available == 0 && !self.fill()?.is_empty() {
+            return Err(size_error(self, self.limit));
+        }
+        let buffer = self.fill()?;
+        Ok(&buffer[..buffer.len().min(available)])
+    }
+
+    fn consume(&mut self, count: usize) {
+        let buffer = self.reader.fill_buf().unwrap_or_default();
+        let consumed = &buffer[..count];
+        let advance = line_advance(consumed);
+        if advance.newlines > 0 {
+            self.line += advance.newlines;
+            self.column = 1;
+            self.line_prefix.clear();
+        }
+        self.column += advance.columns;
+        // Only the end of a long line can survive the trimming below.
+        let last_line = &consumed[advance.line_start..];
+        self.line_prefix.extend_from_slice(&last_line[last_line.len().saturating_sub(LINE_MEMORY * 2 + 1)..]);
+        if self.line_prefix.len() > LINE_MEMORY * 2 {
+            self.line_prefix.drain(..self.line_prefix.len() - LINE_MEMORY);
+        }
+        self.offset += count;
+        self.reader.consume(count);
+    }
+
+    fn offset(&self) -> usize {
+        self.offset
+    }
+
+    fn position(&mut self) -> Position {
+        Position::new(self.offset, self.line, self.column)
+    }
+
+    fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+    }
+
+    fn report(&mut self, count: usize, kind: ParseErrorKind, expected: &str) -> ConversationError {
+        let count = count.min(self.line_prefix.len());
+        let split = self.line_prefix.len() - count;
+        // Continuation bytes of a multi-byte character do not start a new column.
+        let columns = self.line_prefix[split..].iter().filter(|&&byte| byte & 0xC0 != 0x80).count();
+        let (offset, line, column) = (self.offset - count, self.line, self.column - columns);
+        let mut rest = self.line_prefix[split..].to_vec();
+        let upcoming = match self.fill() {
+            Ok(buffer) => buffer,
+            Err(error) => return error,
+        };
+        rest.extend_from_slice(&upcoming[..upcoming.iter().position(|&b| b == b'\n').unwrap_or(upcoming.len())]);
+        let upcoming = String::from_utf8_lossy(&rest).into_owned();
+        let found = match upcoming.chars().next() {
+            Some(char::REPLACEMENT_CHARACTER) => "invalid UTF-8".to_string(),
+            c => describe_char(c),
+        };
+
+        let prefix = String::from_utf8_lossy(&self.line_prefix[..split]);
+        let prefix = prefix.trim_start_matches(char::REPLACEMENT_CHARACTER);
+        let text_column = prefix.chars().count() + 1;
+        let line_text = format!("{}{}", prefix, upcoming);
+
+        ParseError::new(offset, line, column, expected, found, &line_text, text_column).with_kind(kind).into()
+    }
+}
+
+/// Builds the error for input that goes on past the size limit.
+fn size_error<'de, S: JsonSource<'de> + ?Sized>(source: &mut S, limit: usize) -> ConversationError {
+    source.report(0, ParseErrorKind::DocumentTooLarge, &format!("a document of at most {} bytes", limit))
+}
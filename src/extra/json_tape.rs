@@ -0,0 +1,377 @@
+use std::borrow::Cow;
+use std::ops::Range;
+
+use crate::base::casting::CastError;
+use crate::base::{DuplicateKeyPolicy, JsonValue, JsonValueRef};
+use crate::extra::json_parser::ConversationError;
+use crate::extra::json_reader::{JsonEvent, JsonReader};
+use crate::extra::json_visitor::{dispatch, RefTreeBuilder};
+use crate::extra::parser_options::ParserOptions;
+
+/// One entry of the tape.
+#[derive(Debug)]
+enum Node<'a> {
+    /// A string, kept as the range of its quoted token.
+    String(Range<usize>),
+    /// A number, kept as the range of its token.
+    Number(Range<usize>),
+    Boolean(bool),
+    Null,
+    /// An object key, decoded while building the tape so that lookups can compare it.
+    Key(Cow<'a, str>),
+    /// An array of `len` elements, whose last descendant is followed by the node at `end`.
+    Array { len: usize, end: usize },
+    /// An object of `len` pairs, whose last descendant is followed by the node at `end`.
+    Object { len: usize, end: usize },
+}
+
+/// A read-only document that is indexed in one pass and decoded lazily.
+///
+/// Building the tape validates the whole document and records the position of every token
+/// in a flat list, in document order, where each array and object knows where it ends. Strings
+/// and numbers are only decoded when they are read through navigation, and whole subtrees
+/// that are never visited are never decoded at all, which makes the tape suited to large
+/// documents of which only a few values are needed.
+///
+/// Values are extracted with `parse`, which accepts the same target types as
+/// `JsonValue::parse`.
+///
+/// # Example
+///
+/// ```
+/// use rusty_json::extra::JsonParser;
+///
+/// let doc = JsonParser::parse_tape(r#"{"a": [1, 2, 3, {"b": "deep"}], "c": null}"#).unwrap();
+/// assert_eq!(doc.get("a").unwrap().at(1).unwrap().parse::<i32>().unwrap(), 2);
+/// assert_eq!(doc.get("a").unwrap().at(3).unwrap().get("b").unwrap().parse::<String>().unwrap(), "deep");
+/// assert!(doc.get("missing").is_err());
+/// ```
+#[derive(Debug)]
+pub struct JsonTape<'a> {
+    text: &'a str,
+    nodes: Vec<Node<'a>>,
+    options: ParserOptions,
+}
+
+impl<'a> JsonTape<'a> {
+    /// Validates `text` and builds its tape.
+    pub(crate) fn build(text: &'a str, options: ParserOptions) -> Result<Self, ConversationError> {
+        let mut reader = JsonReader::from_text(text).with_options(options.clone());
+        let mut nodes = Vec::new();
+        let mut open: Vec<usize> = Vec::new();
+        while let Some((event, range)) = reader.next_token()? {
+            let node = match event {
+                JsonEvent::EndArray | JsonEvent::EndObject => {
+                    let end = nodes.len();
+                    if let Some(Node::Array { end: last, .. } | Node::Object { end: last, .. }) =
+                        open.pop().map(|index| &mut nodes[index])
+                    {
+                        *last = end;
+                    }
+                    continue;
+                }
+                JsonEvent::Key(key) => {
+                    nodes.push(Node::Key(key));
+                    continue;
+                }
+                JsonEvent::StartArray => Node::Array { len: 0, end: 0 },
+                JsonEvent::StartObject => Node::Object { len: 0, end: 0 },
+                JsonEvent::String(_) => Node::String(range),
+                JsonEvent::Number(_) => Node::Number(range),
+                JsonEvent::Boolean(value) => Node::Boolean(value),
+                JsonEvent::Null => Node::Null,
+            };
+            if let Some(Node::Array { len, .. } | Node::Object { len, .. }) = open.last().map(|&index| &mut nodes[index]) {
+                *len += 1;
+            }
+            if matches!(node, Node::Array { .. } | Node::Object { .. }) {
+                open.push(nodes.len());
+            }
+            nodes.push(node);
+        }
+        Ok(JsonTape { text, nodes, options })
+    }
+
+    /// Returns the top-level value.
+    pub fn root(&self) -> TapeValue<'_, 'a> {
+        TapeValue::new(self, 0)
+    }
+
+    /// Looks up `key` in the top-level object. See `TapeValue::get`.
+    pub fn get(&self, key: &str) -> Result<TapeValue<'_, 'a>, CastError> {
+        self.root().get(key)
+    }
+
+    /// Returns the element at `index` of the top-level array. See `TapeValue::at`.
+    pub fn at(&self, index: usize) -> Result<TapeValue<'_, 'a>, CastError> {
+        self.root().at(index)
+    }
+
+    /// Parses the whole document into a specified type `T`. See `TapeValue::parse`.
+    pub fn parse<T>(&self) -> Result<T, CastError>
+        where
+            T: for<'b> TryFrom<&'b JsonValue, Error = CastError>,
+    {
+        self.root().parse()
+    }
+
+    /// Returns the index of the node that follows the value at `index` and its descendants.
+    fn skip(&self, index: usize) -> usize {
+        match self.nodes[index] {
+            Node::Array { end, .. } | Node::Object { end, .. } => end,
+            _ => index + 1,
+        }
+    }
+
+    /// Returns the indices of the values of the pairs of the object at `object`.
+    fn pairs(&self, object: usize) -> impl Iterator<Item = usize> + '_ {
+        let count = match self.nodes[object] {
+            Node::Object { len, .. } => len,
+            _ => 0,
+        };
+        let mut index = object + 1;
+        (0..count).map(move |_| {
+            let value = index + 1;
+            index = self.skip(value);
+            value
+        })
+    }
+
+    /// Returns the indices of the values of `key` in the object at `object`, in document
+    /// order.
+    fn values_of<'s>(&'s self, object: usize, key: &'s str) -> impl Iterator<Item = usize> + 's {
+        self.pairs(object).filter(move |&value| self.key_of(value) == key)
+    }
+
+    /// Returns the key of the pair whose value is at `value`.
+    fn key_of(&self, value: usize) -> &str {
+        match &self.nodes[value - 1] {
+            Node::Key(key) => key,
+            _ => "",
+        }
+    }
+
+    /// Decodes the string or number token in `range`, which was validated when the tape was
+    /// built.
+    fn decode(&self, range: &Range<usize>) -> JsonEvent<'a> {
+        let mut reader = JsonReader::from_text(&self.text[range.clone()]).with_options(self.options.clone());
+        match reader.next_event() {
+            Ok(Some(event)) => event,
+            _ => unreachable!("the tape only holds validated tokens"),
+        }
+    }
+}
+
+/// A value of a `JsonTape`, decoded only when it is read.
+#[derive(Debug, Clone, Copy)]
+pub struct TapeValue<'t, 'a> {
+    tape: &'t JsonTape<'a>,
+    index: usize,
+    /// Set for the array of the values of a repeated key under
+    /// `DuplicateKeyPolicy::Collect`, in which case `index` is the object holding them.
+    collected: Option<&'t str>,
+}
+
+impl<'t, 'a> TapeValue<'t, 'a> {
+    fn new(tape: &'t JsonTape<'a>, index: usize) -> Self {
+        TapeValue { tape, index, collected: None }
+    }
+
+    /// Returns the node of this value, or `None` for collected values, which have none.
+    fn node(&self) -> Option<&'t Node<'a>> {
+        match self.collected {
+            Some(_) => None,
+            None => Some(&self.tape.nodes[self.index]),
+        }
+    }
+
+    /// Looks up the value of `key` in this object without decoding any other value.
+    ///
+    /// When a key is repeated, the first value is returned under
+    /// `DuplicateKeyPolicy::KeepFirst`, an array of all its values under
+    /// `DuplicateKeyPolicy::Collect`, and the last one otherwise, as in a `JsonValue`.
+    ///
+    /// The tape keeps no index of keys, so every lookup compares `key` with the keys of the
+    /// object, in time proportional to its number of pairs. To read many fields of a large
+    /// object, walk `entries` once instead of calling `get` for each field.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CastError::FieldNotFound` if the key is absent, or `CastError::InvalidType` if
+    /// this value is not an object.
+    pub fn get(&self, key: &str) -> Result<TapeValue<'t, 'a>, CastError> {
+        if !self.is_object() {
+            return Err(CastError::InvalidType);
+        }
+        let tape = self.tape;
+        let mut values = tape.values_of(self.index, key);
+        let first = values.next().ok_or_else(|| CastError::FieldNotFound(key.to_string()))?;
+        let index = match tape.options.duplicate_keys {
+            DuplicateKeyPolicy::KeepFirst => first,
+            DuplicateKeyPolicy::Collect if values.next().is_some() => {
+                return Ok(TapeValue { tape, index: self.index, collected: Some(tape.key_of(first)) });
+            }
+            _ => values.last().unwrap_or(first),
+        };
+        Ok(TapeValue::new(tape, index))
+    }
+
+    /// Returns the element at `index` of this array, skipping the elements before it without
+    /// decoding them.
+    ///
+    /// Skipping takes time proportional to `index`, so an array is best read in order through
+    /// `elements`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CastError::OutOfRange` if the array is too short, or `CastError::InvalidType`
+    /// if this value is not an array.
+    pub fn at(&self, index: usize) -> Result<TapeValue<'t, 'a>, CastError> {
+        if !self.is_array() {
+            return Err(CastError::InvalidType);
+        }
+        self.elements().nth(index).ok_or(CastError::OutOfRange)
+    }
+
+    /// Returns the number of elements of an array or pairs of an object, and `0` for any
+    /// other value.
+    pub fn len(&self) -> usize {
+        match (self.collected, self.node()) {
+            (Some(key), _) => self.tape.values_of(self.index, key).count(),
+            (_, Some(Node::Array { len, .. } | Node::Object { len, .. })) => *len,
+            _ => 0,
+        }
+    }
+
+    /// Returns `true` if this value is not an array or object with at least one element.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if this value is `null`.
+    pub fn is_null(&self) -> bool {
+        matches!(self.node(), Some(Node::Null))
+    }
+
+    /// Returns `true` if this value is a string.
+    pub fn is_string(&self) -> bool {
+        matches!(self.node(), Some(Node::String(_)))
+    }
+
+    /// Returns `true` if this value is a number.
+    pub fn is_number(&self) -> bool {
+        matches!(self.node(), Some(Node::Number(_)))
+    }
+
+    /// Returns `true` if this value is `true` or `false`.
+    pub fn is_boolean(&self) -> bool {
+        matches!(self.node(), Some(Node::Boolean(_)))
+    }
+
+    /// Returns `true` if this value is an array.
+    pub fn is_array(&self) -> bool {
+        self.collected.is_some() || matches!(self.node(), Some(Node::Array { .. }))
+    }
+
+    /// Returns `true` if this value is an object.
+    pub fn is_object(&self) -> bool {
+        matches!(self.node(), Some(Node::Object { .. }))
+    }
+
+    /// Returns the elements of this array, or nothing if this value is not an array.
+    pub fn elements(&self) -> impl Iterator<Item = TapeValue<'t, 'a>> + 't {
+        let tape = self.tape;
+        let mut collected = self.collected.map(|key| tape.values_of(self.index, key));
+        let mut remaining = match self.node() {
+            Some(Node::Array { len, .. }) => *len,
+            _ => 0,
+        };
+        let mut index = self.index + 1;
+        std::iter::from_fn(move || {
+            if let Some(values) = &mut collected {
+                return values.next().map(|index| TapeValue::new(tape, index));
+            }
+            if remaining == 0 {
+                return None;
+            }
+            remaining -= 1;
+            let element = TapeValue::new(tape, index);
+            index = tape.skip(index);
+            Some(element)
+        })
+    }
+
+    /// Returns the keys and values of this object in document order, or nothing if this
+    /// value is not an object. Repeated keys are returned every time they appear.
+    pub fn entries(&self) -> impl Iterator<Item = (&'t str, TapeValue<'t, 'a>)> + 't {
+        let tape = self.tape;
+        let pairs = if self.is_object() { Some(tape.pairs(self.index)) } else { None };
+        pairs.into_iter().flatten().map(move |index| (tape.key_of(index), TapeValue::new(tape, index)))
+    }
+
+    /// Parses the value into a specified type `T`, decoding only this value and what is
+    /// nested inside it.
+    ///
+    /// Any type that `JsonValue::parse` produces can be extracted, including types that
+    /// implement `TryFrom<&JsonValue>` themselves, so code written against a `JsonValue` keeps
+    /// working on a tape. `to_value_ref` avoids copying strings when that is not needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `CastError` if parsing fails or the type conversion is not possible.
+    pub fn parse<T>(&self) -> Result<T, CastError>
+        where
+            T: for<'b> TryFrom<&'b JsonValue, Error = CastError>,
+    {
+        T::try_from(&self.to_json_value())
+    }
+
+    /// Decodes this value and what is nested inside it into a `JsonValueRef`, borrowing
+    /// strings and keys without escapes from the document.
+    pub fn to_value_ref(&self) -> JsonValueRef<'a> {
+        if self.collected.is_some() {
+            return JsonValueRef::Array(self.elements().map(|element| element.to_value_ref()).collect());
+        }
+        let tape = self.tape;
+        let mut builder = RefTreeBuilder::new(tape.options.duplicate_keys);
+        let mut open: Vec<(usize, bool)> = Vec::new();
+        let end = tape.skip(self.index);
+        for index in self.index..end {
+            close_containers(&mut builder, &mut open, index);
+            let event = match &tape.nodes[index] {
+                Node::String(range) | Node::Number(range) => tape.decode(range),
+                Node::Boolean(value) => JsonEvent::Boolean(*value),
+                Node::Null => JsonEvent::Null,
+                Node::Key(key) => JsonEvent::Key(key.clone()),
+                Node::Array { end, .. } => {
+                    open.push((*end, false));
+                    JsonEvent::StartArray
+                }
+                Node::Object { end, .. } => {
+                    open.push((*end, true));
+                    JsonEvent::StartObject
+                }
+            };
+            dispatch(&mut builder, event).expect("building a JsonValueRef accepts every event");
+        }
+        close_containers(&mut builder, &mut open, end);
+        builder.finish().expect("the tape holds complete values")
+    }
+
+    /// Decodes this value and what is nested inside it into a `JsonValue`.
+    pub fn to_json_value(&self) -> JsonValue {
+        self.to_value_ref().into_owned()
+    }
+}
+
+/// Reports the end of every open container that ends before the node at `index`.
+fn close_containers(builder: &mut RefTreeBuilder<'_>, open: &mut Vec<(usize, bool)>, index: usize) {
+    while let Some(&(end, object)) = open.last() {
+        if end > index {
+            break;
+        }
+        open.pop();
+        let event = if object { JsonEvent::EndObject } else { JsonEvent::EndArray };
+        dispatch(builder, event).expect("building a JsonValueRef accepts every event");
+    }
+}
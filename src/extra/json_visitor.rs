@@ -0,0 +1,444 @@
+use std::borrow::Cow;
+#[cfg(feature = "arena")]
+use std::collections::HashMap;
+
+#[cfg(feature = "arena")]
+use crate::base::{ArenaValue, JsonArena};
+use crate::base::{
+    DuplicateKeyPolicy, JsonArray, JsonKey, JsonNumber, JsonValue, JsonValueRef, KeyInterner, ObjectBuilder, ObjectRefBuilder,
+};
+use crate::extra::json_parser::ConversationError;
+use crate::extra::json_reader::JsonEvent;
+
+/// Callbacks driven by the parser for every token of a document, SAX style.
+///
+/// Every callback has an empty default implementation, so a visitor only overrides the
+/// ones it is interested in. Returning an error from a callback stops parsing and the
+/// error is handed back to the caller. No `JsonValue` is built unless the visitor builds
+/// one itself, which allows counting, filtering or validating documents in constant memory.
+///
+/// Strings are passed by value, so a visitor can keep them without copying.
+///
+/// # Example
+///
+/// ```
+/// use rusty_json::base::JsonNumber;
+/// use rusty_json::extra::{ConversationError, JsonParser, JsonVisitor};
+///
+/// #[derive(Default)]
+/// struct NumberSum(f64);
+///
+/// impl JsonVisitor<'_> for NumberSum {
+///     fn on_number(&mut self, value: JsonNumber) -> Result<(), ConversationError> {
+///         self.0 += value.as_f64();
+///         Ok(())
+///     }
+/// }
+///
+/// let mut sum = NumberSum::default();
+/// JsonParser::visit(r#"{"a": [1, 2], "b": {"c": 3.5}}"#, &mut sum).unwrap();
+/// assert_eq!(sum.0, 6.5);
+/// ```
+pub trait JsonVisitor<'de> {
+    /// Called when an object starts.
+    fn on_object_start(&mut self) -> Result<(), ConversationError> {
+        Ok(())
+    }
+
+    /// Called when an object ends.
+    fn on_object_end(&mut self) -> Result<(), ConversationError> {
+        Ok(())
+    }
+
+    /// Called when an array starts.
+    fn on_array_start(&mut self) -> Result<(), ConversationError> {
+        Ok(())
+    }
+
+    /// Called when an array ends.
+    fn on_array_end(&mut self) -> Result<(), ConversationError> {
+        Ok(())
+    }
+
+    /// Called for every object key, right before the callbacks for its value.
+    fn on_key(&mut self, _key: Cow<'de, str>) -> Result<(), ConversationError> {
+        Ok(())
+    }
+
+    /// Called for every string value.
+    fn on_string(&mut self, _value: Cow<'de, str>) -> Result<(), ConversationError> {
+        Ok(())
+    }
+
+    /// Called for every number.
+    fn on_number(&mut self, _value: JsonNumber) -> Result<(), ConversationError> {
+        Ok(())
+    }
+
+    /// Called for every `true` or `false`.
+    fn on_boolean(&mut self, _value: bool) -> Result<(), ConversationError> {
+        Ok(())
+    }
+
+    /// Called for every `null`.
+    fn on_null(&mut self) -> Result<(), ConversationError> {
+        Ok(())
+    }
+}
+
+/// Forwards a single event to the matching visitor callback.
+pub(crate) fn dispatch<'de, V>(visitor: &mut V, event: JsonEvent<'de>) -> Result<(), ConversationError>
+    where
+        V: JsonVisitor<'de> + ?Sized,
+{
+    match event {
+        JsonEvent::StartObject => visitor.on_object_start(),
+        JsonEvent::EndObject => visitor.on_object_end(),
+        JsonEvent::StartArray => visitor.on_array_start(),
+        JsonEvent::EndArray => visitor.on_array_end(),
+        JsonEvent::Key(key) => visitor.on_key(key),
+        JsonEvent::String(value) => visitor.on_string(value),
+        JsonEvent::Number(value) => visitor.on_number(value),
+        JsonEvent::Boolean(value) => visitor.on_boolean(value),
+        JsonEvent::Null => visitor.on_null(),
+    }
+}
+
+/// A container that is still being filled while building a tree.
+enum Partial {
+    Array(JsonArray),
+    Object(ObjectBuilder, Option<JsonKey>),
+}
+
+/// The visitor behind `JsonParser::parse`, assembling a `JsonValue` without recursion.
+pub(crate) struct TreeBuilder {
+    stack: Vec<Partial>,
+    root: Option<JsonValue>,
+    duplicate_keys: DuplicateKeyPolicy,
+    /// The keys of the document so far, when repeated keys share one allocation.
+    interner: Option<KeyInterner>,
+}
+
+impl TreeBuilder {
+    pub(crate) fn new(duplicate_keys: DuplicateKeyPolicy, intern_keys: bool) -> Self {
+        let interner = intern_keys.then(KeyInterner::default);
+        TreeBuilder { stack: Vec::new(), root: None, duplicate_keys, interner }
+    }
+
+    /// Returns the built value once the outermost value is complete.
+    pub(crate) fn finish(self) -> Option<JsonValue> {
+        self.root
+    }
+
+    fn add(&mut self, value: JsonValue) {
+        match self.stack.last_mut() {
+            None => self.root = Some(value),
+            Some(Partial::Array(array)) => array.push(value),
+            Some(Partial::Object(object, pending)) => {
+                // Repeated keys are rejected by the reader before they get here.
+                let _ = object.insert(pending.take().unwrap_or_default(), value);
+            }
+        }
+    }
+
+    fn close(&mut self) {
+        let value = match self.stack.pop() {
+            Some(Partial::Array(array)) => JsonValue::Array(array),
+            Some(Partial::Object(object, _)) => JsonValue::Object(object.finish()),
+            None => return,
+        };
+        self.add(value);
+    }
+}
+
+impl JsonVisitor<'_> for TreeBuilder {
+    fn on_object_start(&mut self) -> Result<(), ConversationError> {
+        self.stack.push(Partial::Object(ObjectBuilder::new(self.duplicate_keys), None));
+        Ok(())
+    }
+
+    fn on_object_end(&mut self) -> Result<(), ConversationError> {
+        self.close();
+        Ok(())
+    }
+
+    fn on_array_start(&mut self) -> Result<(), ConversationError> {
+        self.stack.push(Partial::Array(JsonArray::new()));
+        Ok(())
+    }
+
+    fn on_array_end(&mut self) -> Result<(), ConversationError> {
+        self.close();
+        Ok(())
+    }
+
+    fn on_key(&mut self, key: Cow<'_, str>) -> Result<(), ConversationError> {
+        if let Some(Partial::Object(_, pending)) = self.stack.last_mut() {
+            *pending = Some(match &mut self.interner {
+                Some(interner) => interner.intern(key),
+                None => JsonKey::from(key),
+            });
+        }
+        Ok(())
+    }
+
+    fn on_string(&mut self, value: Cow<'_, str>) -> Result<(), ConversationError> {
+        self.add(JsonValue::String(value.into_owned()));
+        Ok(())
+    }
+
+    fn on_number(&mut self, value: JsonNumber) -> Result<(), ConversationError> {
+        self.add(JsonValue::Number(value));
+        Ok(())
+    }
+
+    fn on_boolean(&mut self, value: bool) -> Result<(), ConversationError> {
+        self.add(JsonValue::Boolean(value));
+        Ok(())
+    }
+
+    fn on_null(&mut self) -> Result<(), ConversationError> {
+        self.add(JsonValue::Null);
+        Ok(())
+    }
+}
+
+/// A container that is still being filled while building a borrowed tree.
+enum PartialRef<'de> {
+    Array(Vec<JsonValueRef<'de>>),
+    Object(ObjectRefBuilder<'de>, Option<Cow<'de, str>>),
+}
+
+/// The visitor behind `JsonParser::parse_borrowed`, assembling a `JsonValueRef` that keeps
+/// the strings handed over by the reader, borrowed or not, without recursion.
+pub(crate) struct RefTreeBuilder<'de> {
+    stack: Vec<PartialRef<'de>>,
+    root: Option<JsonValueRef<'de>>,
+    duplicate_keys: DuplicateKeyPolicy,
+}
+
+impl<'de> RefTreeBuilder<'de> {
+    pub(crate) fn new(duplicate_keys: DuplicateKeyPolicy) -> Self {
+        RefTreeBuilder { stack: Vec::new(), root: None, duplicate_keys }
+    }
+
+    /// Returns the built value once the outermost value is complete.
+    pub(crate) fn finish(self) -> Option<JsonValueRef<'de>> {
+        self.root
+    }
+
+    fn add(&mut self, value: JsonValueRef<'de>) {
+        match self.stack.last_mut() {
+            None => self.root = Some(value),
+            Some(PartialRef::Array(array)) => array.push(value),
+            Some(PartialRef::Object(object, pending)) => {
+                object.insert(pending.take().unwrap_or_default(), value);
+            }
+        }
+    }
+
+    fn close(&mut self) {
+        let value = match self.stack.pop() {
+            Some(PartialRef::Array(array)) => JsonValueRef::Array(array),
+            Some(PartialRef::Object(object, _)) => object.finish(),
+            None => return,
+        };
+        self.add(value);
+    }
+}
+
+impl<'de> JsonVisitor<'de> for RefTreeBuilder<'de> {
+    fn on_object_start(&mut self) -> Result<(), ConversationError> {
+        self.stack.push(PartialRef::Object(ObjectRefBuilder::new(self.duplicate_keys), None));
+        Ok(())
+    }
+
+    fn on_object_end(&mut self) -> Result<(), ConversationError> {
+        self.close();
+        Ok(())
+    }
+
+    fn on_array_start(&mut self) -> Result<(), ConversationError> {
+        self.stack.push(PartialRef::Array(Vec::new()));
+        Ok(())
+    }
+
+    fn on_array_end(&mut self) -> Result<(), ConversationError> {
+        self.close();
+        Ok(())
+    }
+
+    fn on_key(&mut self, key: Cow<'de, str>) -> Result<(), ConversationError> {
+        if let Some(PartialRef::Object(_, pending)) = self.stack.last_mut() {
+            *pending = Some(key);
+        }
+        Ok(())
+    }
+
+    fn on_string(&mut self, value: Cow<'de, str>) -> Result<(), ConversationError> {
+        self.add(JsonValueRef::String(value));
+        Ok(())
+    }
+
+    fn on_number(&mut self, value: JsonNumber) -> Result<(), ConversationError> {
+        self.add(JsonValueRef::Number(value));
+        Ok(())
+    }
+
+    fn on_boolean(&mut self, value: bool) -> Result<(), ConversationError> {
+        self.add(JsonValueRef::Boolean(value));
+        Ok(())
+    }
+
+    fn on_null(&mut self) -> Result<(), ConversationError> {
+        self.add(JsonValueRef::Null);
+        Ok(())
+    }
+}
+
+/// An array or object being assembled by `ArenaTreeBuilder`, whose children so far are the
+/// values or pairs from `start` on.
+#[cfg(feature = "arena")]
+enum PartialArena<'a> {
+    Array(usize),
+    Object {
+        start: usize,
+        key: Option<&'a str>,
+        /// The position of every key, built once the object is too large to search linearly.
+        positions: Option<HashMap<&'a str, usize>>,
+        /// The later values of repeated keys under `DuplicateKeyPolicy::Collect`, by position.
+        collected: Vec<(usize, Vec<ArenaValue<'a>>)>,
+    },
+}
+
+/// The visitor behind `JsonParser::parse_in`, assembling an `ArenaValue` in a `JsonArena`.
+///
+/// Children are gathered on two stacks shared by all open containers and moved into the
+/// arena in one piece when their container ends, so the stacks are the only heap memory used
+/// and their capacity is reused from one container to the next.
+#[cfg(feature = "arena")]
+pub(crate) struct ArenaTreeBuilder<'a> {
+    arena: &'a JsonArena,
+    stack: Vec<PartialArena<'a>>,
+    values: Vec<ArenaValue<'a>>,
+    pairs: Vec<(&'a str, ArenaValue<'a>)>,
+    root: Option<ArenaValue<'a>>,
+    duplicate_keys: DuplicateKeyPolicy,
+}
+
+#[cfg(feature = "arena")]
+impl<'a> ArenaTreeBuilder<'a> {
+    /// Objects with more pairs than this look up repeated keys in a map instead of a scan.
+    const LINEAR_KEYS: usize = 16;
+
+    pub(crate) fn new(arena: &'a JsonArena, duplicate_keys: DuplicateKeyPolicy) -> Self {
+        ArenaTreeBuilder { arena, stack: Vec::new(), values: Vec::new(), pairs: Vec::new(), root: None, duplicate_keys }
+    }
+
+    /// Returns the built value once the outermost value is complete.
+    pub(crate) fn finish(self) -> Option<ArenaValue<'a>> {
+        self.root
+    }
+
+    fn add(&mut self, value: ArenaValue<'a>) {
+        let Some(PartialArena::Object { start, key, positions, collected }) = self.stack.last_mut() else {
+            match self.stack.last() {
+                None => self.root = Some(value),
+                _ => self.values.push(value),
+            }
+            return;
+        };
+        let key = key.take().unwrap_or_default();
+        let existing = match positions {
+            Some(positions) => positions.get(key).copied(),
+            None => self.pairs[*start..].iter().position(|(name, _)| *name == key).map(|index| *start + index),
+        };
+        let Some(position) = existing else {
+            if let Some(positions) = positions {
+                positions.insert(key, self.pairs.len());
+            } else if self.pairs.len() - *start >= Self::LINEAR_KEYS {
+                let indexed = self.pairs[*start..].iter().enumerate().map(|(index, (name, _))| (*name, *start + index));
+                *positions = Some(indexed.chain([(key, self.pairs.len())]).collect());
+            }
+            self.pairs.push((key, value));
+            return;
+        };
+        // Repeated keys under `DuplicateKeyPolicy::Reject` are rejected by the reader before
+        // they get here, and are otherwise treated as `KeepLast`.
+        match self.duplicate_keys {
+            DuplicateKeyPolicy::KeepLast | DuplicateKeyPolicy::Reject => self.pairs[position].1 = value,
+            DuplicateKeyPolicy::KeepFirst => {}
+            DuplicateKeyPolicy::Collect => match collected.iter_mut().find(|(index, _)| *index == position) {
+                Some((_, values)) => values.push(value),
+                None => collected.push((position, vec![value])),
+            },
+        }
+    }
+
+    fn close(&mut self) {
+        let value = match self.stack.pop() {
+            Some(PartialArena::Array(start)) => ArenaValue::Array(self.arena.alloc_array(self.values.drain(start..))),
+            Some(PartialArena::Object { start, collected, .. }) => {
+                for (position, later) in collected {
+                    let first = std::mem::replace(&mut self.pairs[position].1, ArenaValue::Null);
+                    let values: Vec<_> = std::iter::once(first).chain(later).collect();
+                    self.pairs[position].1 = ArenaValue::Array(self.arena.alloc_array(values.into_iter()));
+                }
+                ArenaValue::Object(self.arena.alloc_object(self.pairs.drain(start..)))
+            }
+            None => return,
+        };
+        self.add(value);
+    }
+}
+
+#[cfg(feature = "arena")]
+impl<'de> JsonVisitor<'de> for ArenaTreeBuilder<'_> {
+    fn on_object_start(&mut self) -> Result<(), ConversationError> {
+        let start = self.pairs.len();
+        self.stack.push(PartialArena::Object { start, key: None, positions: None, collected: Vec::new() });
+        Ok(())
+    }
+
+    fn on_object_end(&mut self) -> Result<(), ConversationError> {
+        self.close();
+        Ok(())
+    }
+
+    fn on_array_start(&mut self) -> Result<(), ConversationError> {
+        self.stack.push(PartialArena::Array(self.values.len()));
+        Ok(())
+    }
+
+    fn on_array_end(&mut self) -> Result<(), ConversationError> {
+        self.close();
+        Ok(())
+    }
+
+    fn on_key(&mut self, key: Cow<'de, str>) -> Result<(), ConversationError> {
+        if let Some(PartialArena::Object { key: pending, .. }) = self.stack.last_mut() {
+            *pending = Some(self.arena.alloc_str(&key));
+        }
+        Ok(())
+    }
+
+    fn on_string(&mut self, value: Cow<'de, str>) -> Result<(), ConversationError> {
+        self.add(ArenaValue::String(self.arena.alloc_str(&value)));
+        Ok(())
+    }
+
+    fn on_number(&mut self, value: JsonNumber) -> Result<(), ConversationError> {
+        self.add(ArenaValue::Number(self.arena.alloc_number(value)));
+        Ok(())
+    }
+
+    fn on_boolean(&mut self, value: bool) -> Result<(), ConversationError> {
+        self.add(ArenaValue::Boolean(value));
+        Ok(())
+    }
+
+    fn on_null(&mut self) -> Result<(), ConversationError> {
+        self.add(ArenaValue::Null);
+        Ok(())
+    }
+}
@@ -0,0 +1,272 @@
+use std::fmt::Write as _;
+use std::io;
+
+use thiserror::Error;
+
+use crate::base::JsonValue;
+use crate::extra::json_formatter::{IoAdapter, JsonFormatter};
+
+/// Error type for a `JsonWriter`, raised by the destination or by a call that would produce
+/// malformed JSON.
+#[derive(Debug, Error)]
+pub enum JsonWriterError {
+    /// I/O error raised by the underlying writer.
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    /// A call that is not allowed at this point of the document, such as a value inside an
+    /// object without a key, or `end_array` while an object is open.
+    #[error("Invalid JSON sequence: {0}")]
+    InvalidSequence(&'static str),
+
+    /// `finish` was called before the document was complete.
+    #[error("Incomplete JSON document: {0}")]
+    Incomplete(&'static str),
+
+    /// The formatter writes canonical JSON, which sorts the keys of every object and so
+    /// cannot be produced while keys are pushed one at a time.
+    #[error("A JsonWriter cannot write canonical JSON; use JsonValue::canonical_bytes")]
+    CanonicalFormatter,
+}
+
+/// An open array or object.
+struct Frame {
+    object: bool,
+    empty: bool,
+    /// Whether a key was written that still waits for its value.
+    key_written: bool,
+}
+
+/// A push-based writer that streams one JSON document to an `io::Write`.
+///
+/// The document is built with calls such as `begin_object`, `key`, `value` and `end_object`,
+/// and each piece is written as soon as it is pushed, so documents of any size can be
+/// produced without building a `JsonValue` first. The writer tracks which containers are
+/// open and rejects calls that would make the output malformed, leaving the document
+/// unchanged so the caller can recover. The layout follows a `JsonFormatter`, so output is
+/// the same as formatting the equivalent `JsonValue`, except that containers opened with
+/// `begin_object` and `begin_array` are never kept inline by a maximum width, and keys
+/// pushed with `key` are written in the order given even if the formatter sorts keys. For
+/// the same reason a canonical formatter is not accepted.
+///
+/// # Example
+///
+/// ```
+/// use rusty_json::extra::JsonWriter;
+///
+/// let mut writer = JsonWriter::new(Vec::new());
+/// writer.begin_object().unwrap();
+/// writer.key("ids").unwrap();
+/// writer.begin_array().unwrap();
+/// for id in 1..=3 {
+///     writer.value(id).unwrap();
+/// }
+/// writer.end_array().unwrap();
+/// writer.end_object().unwrap();
+///
+/// let bytes = writer.finish().unwrap();
+/// assert_eq!(String::from_utf8(bytes).unwrap(), "{\n  \"ids\": [\n    1,\n    2,\n    3\n  ]\n}");
+/// ```
+pub struct JsonWriter<W: io::Write> {
+    out: IoAdapter<W>,
+    formatter: JsonFormatter,
+    stack: Vec<Frame>,
+    done: bool,
+}
+
+impl<W: io::Write> JsonWriter<W> {
+    /// Creates a writer with the layout of `JsonFormatter::default()`.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The destination, such as a `File`, a `TcpStream` or `io::stdout()`.
+    pub fn new(writer: W) -> Self {
+        JsonWriter { out: IoAdapter::new(writer), formatter: JsonFormatter::default(), stack: Vec::new(), done: false }
+    }
+
+    /// Creates a writer that lays out its output like `formatter`.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The destination.
+    /// * `formatter` - The formatter whose indentation, escaping and number format are used,
+    ///   typically built with `JsonFormatter::builder()`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `JsonWriterError::CanonicalFormatter` if `formatter` is canonical, since keys
+    /// pushed in document order cannot be sorted.
+    pub fn with_formatter(writer: W, formatter: JsonFormatter) -> Result<Self, JsonWriterError> {
+        if formatter.is_canonical() {
+            return Err(JsonWriterError::CanonicalFormatter);
+        }
+        Ok(JsonWriter { out: IoAdapter::new(writer), formatter, stack: Vec::new(), done: false })
+    }
+
+    /// Opens an object, which must be closed with `end_object`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a value is not allowed here or the destination fails.
+    pub fn begin_object(&mut self) -> Result<(), JsonWriterError> {
+        self.begin('{', true)
+    }
+
+    /// Opens an array, which must be closed with `end_array`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a value is not allowed here or the destination fails.
+    pub fn begin_array(&mut self) -> Result<(), JsonWriterError> {
+        self.begin('[', false)
+    }
+
+    /// Closes the innermost object.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the innermost container is not an object, its last key has no
+    /// value yet, or the destination fails.
+    pub fn end_object(&mut self) -> Result<(), JsonWriterError> {
+        match self.stack.last() {
+            Some(frame) if frame.object && frame.key_written => {
+                Err(JsonWriterError::InvalidSequence("end_object after a key without a value"))
+            }
+            Some(frame) if frame.object => self.end('}'),
+            _ => Err(JsonWriterError::InvalidSequence("end_object without an open object")),
+        }
+    }
+
+    /// Closes the innermost array.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the innermost container is not an array or the destination fails.
+    pub fn end_array(&mut self) -> Result<(), JsonWriterError> {
+        match self.stack.last() {
+            Some(frame) if !frame.object => self.end(']'),
+            _ => Err(JsonWriterError::InvalidSequence("end_array without an open array")),
+        }
+    }
+
+    /// Writes the key of the next member of the innermost object.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key, escaped like the formatter's strings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the innermost container is not an object, the previous key has no
+    /// value yet, or the destination fails.
+    pub fn key(&mut self, key: &str) -> Result<(), JsonWriterError> {
+        match self.stack.last() {
+            Some(frame) if frame.object && frame.key_written => {
+                return Err(JsonWriterError::InvalidSequence("a key after a key without a value"));
+            }
+            Some(frame) if frame.object => {}
+            _ => return Err(JsonWriterError::InvalidSequence("a key outside of an object")),
+        }
+        self.separate()?;
+        let result = self.formatter.write_key(&mut self.out, key);
+        self.out.check(result)?;
+        if let Some(frame) = self.stack.last_mut() {
+            frame.key_written = true;
+        }
+        Ok(())
+    }
+
+    /// Writes a complete value, such as a number, a string or a whole `JsonValue` tree.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Anything that converts into a `JsonValue`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a value is not allowed here or the destination fails.
+    pub fn value<V: Into<JsonValue>>(&mut self, value: V) -> Result<(), JsonWriterError> {
+        self.value_ref(&value.into())
+    }
+
+    /// Writes a complete value without taking ownership of it.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to write, indented to the current nesting level.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a value is not allowed here or the destination fails.
+    pub fn value_ref(&mut self, value: &JsonValue) -> Result<(), JsonWriterError> {
+        self.before_value()?;
+        let result = self.formatter.write_value(&mut self.out, value, self.stack.len());
+        self.out.check(result)?;
+        self.after_value();
+        Ok(())
+    }
+
+    /// Checks that the document is complete, flushes it and returns the destination.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no value was written, a container is still open, or the
+    /// destination fails.
+    pub fn finish(mut self) -> Result<W, JsonWriterError> {
+        if !self.stack.is_empty() {
+            return Err(JsonWriterError::Incomplete("a container is still open"));
+        }
+        if !self.done {
+            return Err(JsonWriterError::Incomplete("no value was written"));
+        }
+        let result = self.formatter.write_end(&mut self.out);
+        self.out.check(result)?;
+        Ok(self.out.into_inner()?)
+    }
+
+    fn begin(&mut self, open: char, object: bool) -> Result<(), JsonWriterError> {
+        self.before_value()?;
+        let result = self.out.write_char(open);
+        self.out.check(result)?;
+        self.stack.push(Frame { object, empty: true, key_written: false });
+        Ok(())
+    }
+
+    fn end(&mut self, close: char) -> Result<(), JsonWriterError> {
+        let empty = self.stack.last().is_some_and(|frame| frame.empty);
+        let result = self.formatter.write_close(&mut self.out, close, empty, self.stack.len() - 1);
+        self.out.check(result)?;
+        self.stack.pop();
+        self.after_value();
+        Ok(())
+    }
+
+    /// Checks that a value may follow and, inside an array, starts its line.
+    fn before_value(&mut self) -> Result<(), JsonWriterError> {
+        match self.stack.last() {
+            None if self.done => Err(JsonWriterError::InvalidSequence("a second top-level value")),
+            None => Ok(()),
+            Some(frame) if frame.object && !frame.key_written => {
+                Err(JsonWriterError::InvalidSequence("a value in an object without a key"))
+            }
+            Some(frame) if frame.object => Ok(()),
+            Some(_) => self.separate(),
+        }
+    }
+
+    /// Marks the innermost container, or the document, as holding one more value.
+    fn after_value(&mut self) {
+        match self.stack.last_mut() {
+            Some(frame) => frame.key_written = false,
+            None => self.done = true,
+        }
+    }
+
+    /// Writes the comma and line break that come before an array element or object member.
+    fn separate(&mut self) -> Result<(), JsonWriterError> {
+        let depth = self.stack.len();
+        let first = self.stack.last_mut().is_some_and(|frame| std::mem::take(&mut frame.empty));
+        let result = self.formatter.write_separator(&mut self.out, first, false, depth);
+        Ok(self.out.check(result)?)
+    }
+}
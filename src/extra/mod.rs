@@ -1,16 +1,44 @@
+mod block_scan;
+mod encoding;
 mod json_formatter;
 mod json_entity;
 pub mod macros;
 mod json_parser;
+mod json_reader;
+mod json_source;
+mod json_tape;
+mod json_visitor;
+mod json_writer;
+mod number_scanner;
+mod number_table;
+mod parse_error;
+mod parser_options;
 
 pub use json_entity::JsonEntity;
 
 pub use json_parser::ConversationError;
+pub use parse_error::ParseError;
+pub use parse_error::ParseErrorKind;
+pub use parse_error::Position;
 
 #[cfg(feature = "serialization")]
 pub use rusty_json_serialization::JsonEntity;
 
 pub use json_formatter::JsonFormatter;
 pub use json_formatter::JsonFormatterBuilder;
+pub use json_formatter::LineEnding;
 pub use json_parser::JsonParser;
-
+pub use json_parser::JsonParserBuilder;
+pub use parser_options::Extensions;
+pub use parser_options::ParserOptions;
+pub use parser_options::SurrogatePolicy;
+pub use json_reader::JsonEvent;
+pub use json_reader::JsonReader;
+pub use json_source::IoSource;
+pub use json_source::JsonSource;
+pub use json_source::SliceSource;
+pub use json_tape::JsonTape;
+pub use json_tape::TapeValue;
+pub use json_visitor::JsonVisitor;
+pub use json_writer::JsonWriter;
+pub use json_writer::JsonWriterError;
@@ -0,0 +1,254 @@
+//! Decodes the numbers read by `JsonReader`.
+//!
+//! Integers are accumulated directly into a `u64` and never pass through a float. Other
+//! numbers are decoded from their decimal mantissa and exponent: first with Clinger's fast
+//! path, which is exact when both fit in an `f64`, then with the Eisel-Lemire algorithm, and
+//! only in the rare cases those cannot decide, with the standard library parser.
+
+#[cfg(feature = "arbitrary_precision")]
+use crate::base::JsonDecimal;
+use crate::base::JsonNumber;
+use crate::extra::number_table::{LARGEST_POWER_OF_FIVE, POWER_OF_FIVE_128, SMALLEST_POWER_OF_FIVE};
+
+/// The number of significant digits that always fit in a `u64` mantissa.
+const MAX_MANTISSA_DIGITS: usize = 19;
+
+/// The powers of ten that an `f64` represents exactly.
+const EXACT_POWERS_OF_TEN: [f64; 23] = [
+    1e0, 1e1, 1e2, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10, 1e11, 1e12, 1e13, 1e14, 1e15, 1e16, 1e17, 1e18, 1e19, 1e20,
+    1e21, 1e22,
+];
+
+const MANTISSA_BITS: i32 = 52;
+const MINIMUM_EXPONENT: i32 = -1023;
+const INFINITE_POWER: i32 = 0x7FF;
+
+/// Decodes the text of a number that the reader has already validated.
+///
+/// Integers that fit in an `i64` or a `u64` are kept exact. Every other number, including
+/// `-0`, is decoded to the nearest `f64`, or to a `JsonDecimal` with the
+/// `arbitrary_precision` feature.
+///
+/// # Returns
+///
+/// The number, or `None` if `text` is not a number.
+pub(crate) fn scan_number(text: &[u8]) -> Option<JsonNumber> {
+    let (negative, unsigned) = match text.first() {
+        Some(b'-') => (true, &text[1..]),
+        Some(b'+') => (false, &text[1..]),
+        _ => (false, text),
+    };
+    if !unsigned.is_empty() && unsigned.iter().all(u8::is_ascii_digit) {
+        if let Some(number) = scan_integer(negative, unsigned) {
+            return Some(number);
+        }
+    }
+
+    let text = std::str::from_utf8(text).ok()?;
+    #[cfg(feature = "arbitrary_precision")]
+    if let Some(decimal) = JsonDecimal::parse(text) {
+        return Some(JsonNumber::from(decimal));
+    }
+    let value = match scan_float(unsigned) {
+        Some(magnitude) if negative => -magnitude,
+        Some(magnitude) => magnitude,
+        None => text.parse().ok()?,
+    };
+    Some(JsonNumber::from_f64(value))
+}
+
+/// Decodes a run of digits as an integer.
+///
+/// # Returns
+///
+/// The integer, or `None` if it is out of range or is `-0`, which has no integer form.
+fn scan_integer(negative: bool, digits: &[u8]) -> Option<JsonNumber> {
+    let mut value: u64 = 0;
+    for digit in digits {
+        value = value.checked_mul(10)?.checked_add(u64::from(digit - b'0'))?;
+    }
+    match (negative, value) {
+        (false, _) => Some(JsonNumber::from(value)),
+        (true, 0) => None,
+        (true, 1..=0x8000_0000_0000_0000) => Some(JsonNumber::from((value as i64).wrapping_neg())),
+        (true, _) => None,
+    }
+}
+
+/// Decodes an unsigned number with a fraction or an exponent with the fast paths.
+///
+/// # Returns
+///
+/// The nearest `f64`, or `None` if the fast paths cannot decide and the number must be
+/// parsed exactly.
+fn scan_float(text: &[u8]) -> Option<f64> {
+    let mut mantissa: u64 = 0;
+    let mut significant = 0;
+    let mut exponent: i64 = 0;
+    let mut truncated = false;
+    let mut rest = text;
+
+    let mut take_digit = |digit: u8, fraction: bool| {
+        if mantissa == 0 && digit == b'0' {
+            // Leading zeros are not significant.
+        } else if significant < MAX_MANTISSA_DIGITS {
+            mantissa = mantissa * 10 + u64::from(digit - b'0');
+            significant += 1;
+        } else {
+            truncated |= digit != b'0';
+            if !fraction {
+                exponent += 1;
+            }
+            return;
+        }
+        if fraction {
+            exponent -= 1;
+        }
+    };
+    while let Some((&digit @ b'0'..=b'9', tail)) = rest.split_first() {
+        take_digit(digit, false);
+        rest = tail;
+    }
+    if let Some((b'.', tail)) = rest.split_first() {
+        rest = tail;
+        while let Some((&digit @ b'0'..=b'9', tail)) = rest.split_first() {
+            take_digit(digit, true);
+            rest = tail;
+        }
+    }
+    if let Some((b'e' | b'E', tail)) = rest.split_first() {
+        let (negative, mut digits) = match tail.split_first() {
+            Some((b'-', digits)) => (true, digits),
+            Some((b'+', digits)) => (false, digits),
+            _ => (false, tail),
+        };
+        let mut explicit: i64 = 0;
+        while let Some((&digit @ b'0'..=b'9', tail)) = digits.split_first() {
+            // Saturate well beyond the range where every number is zero or infinite.
+            explicit = (explicit * 10 + i64::from(digit - b'0')).min(0x10000);
+            digits = tail;
+        }
+        exponent += if negative { -explicit } else { explicit };
+    }
+
+    if !truncated {
+        if let Some(value) = clinger(mantissa, exponent) {
+            return Some(value);
+        }
+    }
+    let value = eisel_lemire(exponent, mantissa)?;
+    // A truncated mantissa lies between `mantissa` and `mantissa + 1`, and the result only
+    // stands if both round to the same float.
+    if truncated && eisel_lemire(exponent, mantissa + 1) != Some(value) {
+        return None;
+    }
+    Some(value)
+}
+
+/// Clinger's fast path: when the mantissa and the power of ten are both exact in an `f64`,
+/// a single correctly rounded multiplication or division gives the exact result.
+fn clinger(mut mantissa: u64, mut exponent: i64) -> Option<f64> {
+    const MAX_EXACT_MANTISSA: u64 = 1 << 53;
+    if mantissa > MAX_EXACT_MANTISSA {
+        return None;
+    }
+    if (23..=22 + 15).contains(&exponent) {
+        // Move the excess of the exponent into the mantissa while it stays exact.
+        mantissa = mantissa.checked_mul(10u64.pow((exponent - 22) as u32)).filter(|&m| m <= MAX_EXACT_MANTISSA)?;
+        exponent = 22;
+    }
+    match exponent {
+        0..=22 => Some(mantissa as f64 * EXACT_POWERS_OF_TEN[exponent as usize]),
+        -22..=-1 => Some(mantissa as f64 / EXACT_POWERS_OF_TEN[-exponent as usize]),
+        _ => None,
+    }
+}
+
+/// The Eisel-Lemire algorithm: computes `mantissa × 10^exponent` from a 128-bit approximation
+/// of the power of ten, following Daniel Lemire, "Number Parsing at a Gigabyte per Second".
+///
+/// # Returns
+///
+/// The nearest `f64`, or `None` in the rare cases where the approximation cannot decide the
+/// rounding.
+fn eisel_lemire(exponent: i64, mantissa: u64) -> Option<f64> {
+    if mantissa == 0 || exponent < SMALLEST_POWER_OF_FIVE {
+        return Some(0.0);
+    }
+    if exponent > LARGEST_POWER_OF_FIVE {
+        return Some(f64::INFINITY);
+    }
+
+    let leading_zeros = mantissa.leading_zeros();
+    let normalized = mantissa << leading_zeros;
+    let (low, high) = product_approximation(exponent, normalized);
+    if low == u64::MAX && !(-27..=55).contains(&exponent) {
+        return None;
+    }
+
+    let upper_bit = (high >> 63) as i32;
+    let shift = upper_bit + 64 - MANTISSA_BITS - 3;
+    let mut bits = high >> shift;
+    let mut power = binary_exponent(exponent as i32) + upper_bit - leading_zeros as i32 - MINIMUM_EXPONENT;
+
+    if power <= 0 {
+        // A subnormal number, or one that rounds up to the smallest normal number.
+        if -power + 1 >= 64 {
+            return Some(0.0);
+        }
+        bits >>= -power + 1;
+        bits += bits & 1;
+        bits >>= 1;
+        power = i32::from(bits >= 1 << MANTISSA_BITS);
+        return Some(assemble(power, bits));
+    }
+
+    // An exact halfway case rounds to even. It can only be exact for small exponents.
+    if low <= 1 && (-4..=23).contains(&exponent) && bits & 3 == 1 && bits << shift == high {
+        bits &= !1;
+    }
+    bits += bits & 1;
+    bits >>= 1;
+    if bits >= 2 << MANTISSA_BITS {
+        bits = 1 << MANTISSA_BITS;
+        power += 1;
+    }
+    bits &= !(1 << MANTISSA_BITS);
+    if power >= INFINITE_POWER {
+        return Some(f64::INFINITY);
+    }
+    Some(assemble(power, bits))
+}
+
+/// Multiplies a normalized mantissa by the 128-bit approximation of `5^exponent`, returning
+/// the `(low, high)` words of the high half of the product. The second multiplication is only
+/// done when the bits that decide the rounding are not already known.
+fn product_approximation(exponent: i64, mantissa: u64) -> (u64, u64) {
+    const PRECISION_MASK: u64 = u64::MAX >> (MANTISSA_BITS + 3);
+    let (high5, low5) = POWER_OF_FIVE_128[(exponent - SMALLEST_POWER_OF_FIVE) as usize];
+    let (mut low, mut high) = multiply(mantissa, high5);
+    if high & PRECISION_MASK == PRECISION_MASK {
+        let (_, carry) = multiply(mantissa, low5);
+        low = low.wrapping_add(carry);
+        if carry > low {
+            high += 1;
+        }
+    }
+    (low, high)
+}
+
+/// Returns the `(low, high)` words of the full product of `a` and `b`.
+fn multiply(a: u64, b: u64) -> (u64, u64) {
+    let product = u128::from(a) * u128::from(b);
+    (product as u64, (product >> 64) as u64)
+}
+
+/// Returns `floor(log2(10^exponent)) + 63`, exact over the range of the table.
+fn binary_exponent(exponent: i32) -> i32 {
+    (exponent.wrapping_mul(152_170 + 65536) >> 16) + 63
+}
+
+/// Builds an `f64` from its biased exponent and explicit mantissa bits.
+fn assemble(power: i32, bits: u64) -> f64 {
+    f64::from_bits(bits | (power as u64) << MANTISSA_BITS)
+}
@@ -0,0 +1,247 @@
+use std::fmt::{Display, Formatter};
+
+/// Number of characters shown on each side of the error column in a snippet.
+const SNIPPET_CONTEXT: usize = 40;
+
+/// The category of a `ParseError`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ParseErrorKind {
+    /// The input is not valid JSON, or uses an extension the parser does not accept.
+    Syntax,
+    /// Containers are nested deeper than the parser's maximum depth.
+    DepthLimitExceeded,
+    /// A string or key is longer than the parser's maximum string length.
+    StringTooLong,
+    /// The document is larger than the parser's maximum document size.
+    DocumentTooLarge,
+    /// An object contains the same key twice and the parser rejects duplicates.
+    DuplicateKey,
+    /// Bytes given to `JsonParser::parse_bytes` are not valid UTF-8, or are encoded in UTF-16
+    /// or UTF-32.
+    InvalidEncoding,
+}
+
+/// A location in a JSON document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Position {
+    offset: usize,
+    line: usize,
+    column: usize,
+}
+
+impl Position {
+    pub(crate) fn new(offset: usize, line: usize, column: usize) -> Self {
+        Position { offset, line, column }
+    }
+
+    /// Returns the byte offset into the input.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the 1-based line.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns the 1-based column, counted in characters.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
+/// The key and first occurrence behind a `ParseErrorKind::DuplicateKey` error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Duplicate {
+    key: String,
+    first: Position,
+}
+
+/// Describes where and why a JSON document failed to parse.
+///
+/// Lines and columns are 1-based, columns are counted in characters and the offset is
+/// the byte offset into the input at which the problem was detected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    kind: ParseErrorKind,
+    offset: usize,
+    line: usize,
+    column: usize,
+    expected: String,
+    found: String,
+    snippet: String,
+    duplicate: Option<Box<Duplicate>>,
+}
+
+impl ParseError {
+    /// Builds a parse error for `input` at the given byte `offset`, deriving the line,
+    /// column, the offending character and the snippet from the input itself.
+    pub(crate) fn at(input: &str, offset: usize, expected: impl Into<String>) -> Self {
+        let mut offset = offset.min(input.len());
+        while !input.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let line_end = input[offset..].find('\n').map_or(input.len(), |i| offset + i);
+
+        let line = before.matches('\n').count() + 1;
+        let column = input[line_start..offset].chars().count() + 1;
+        let found = describe_char(input[offset..].chars().next());
+
+        ParseError::new(offset, line, column, expected, found, &input[line_start..line_end], column)
+    }
+
+    /// Builds a parse error from an already known position.
+    ///
+    /// `line_text` is the part of the offending line that is available to the caller and
+    /// `text_column` the 1-based character column of the error within that text.
+    pub(crate) fn new(
+        offset: usize,
+        line: usize,
+        column: usize,
+        expected: impl Into<String>,
+        found: String,
+        line_text: &str,
+        text_column: usize,
+    ) -> Self {
+        ParseError {
+            kind: ParseErrorKind::Syntax,
+            offset,
+            line,
+            column,
+            expected: expected.into(),
+            found,
+            snippet: render_snippet(line_text, line, text_column),
+            duplicate: None,
+        }
+    }
+
+    /// Changes the category of the error.
+    pub(crate) fn with_kind(mut self, kind: ParseErrorKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Turns the error into a duplicate-key error for `key`, which was first seen at `first`.
+    pub(crate) fn with_duplicate(mut self, key: String, first: Position) -> Self {
+        self.kind = ParseErrorKind::DuplicateKey;
+        self.duplicate = Some(Box::new(Duplicate { key, first }));
+        self
+    }
+
+    /// Returns the category of the error.
+    pub fn kind(&self) -> ParseErrorKind {
+        self.kind
+    }
+
+    /// Returns the byte offset at which the error was detected.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the 1-based line of the error.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns the 1-based column of the error, counted in characters.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Returns a description of what the parser expected at the error position.
+    pub fn expected(&self) -> &str {
+        &self.expected
+    }
+
+    /// Returns a description of what was actually found at the error position.
+    pub fn found(&self) -> &str {
+        &self.found
+    }
+
+    /// Returns the offending line with a caret under the column of the error.
+    pub fn snippet(&self) -> &str {
+        &self.snippet
+    }
+
+    /// Returns the position of the error.
+    pub fn position(&self) -> Position {
+        Position::new(self.offset, self.line, self.column)
+    }
+
+    /// Returns the repeated key of a `ParseErrorKind::DuplicateKey` error.
+    pub fn duplicate_key(&self) -> Option<&str> {
+        self.duplicate.as_ref().map(|duplicate| duplicate.key.as_str())
+    }
+
+    /// Returns where the repeated key of a `ParseErrorKind::DuplicateKey` error first appeared.
+    /// The error itself is located at its second occurrence.
+    pub fn first_position(&self) -> Option<Position> {
+        self.duplicate.as_ref().map(|duplicate| duplicate.first)
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(duplicate) = &self.duplicate {
+            return write!(
+                f,
+                "duplicate key {:?} at line {}, column {}, first defined at line {}, column {}\n{}",
+                duplicate.key, self.line, self.column, duplicate.first.line, duplicate.first.column, self.snippet
+            );
+        }
+        write!(
+            f,
+            "expected {}, found {} at line {}, column {}\n{}",
+            self.expected, self.found, self.line, self.column, self.snippet
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Describes a single character of the input for error messages.
+pub(crate) fn describe_char(c: Option<char>) -> String {
+    match c {
+        Some(c) => format!("{:?}", c),
+        None => "end of input".to_string(),
+    }
+}
+
+/// Renders `line_text` with a caret under `column`, trimming very long lines (such as
+/// minified documents) to a window around the error.
+pub(crate) fn render_snippet(line_text: &str, line: usize, column: usize) -> String {
+    let chars: Vec<char> = line_text.chars().collect();
+    let index = column.saturating_sub(1).min(chars.len());
+    let start = index.saturating_sub(SNIPPET_CONTEXT);
+    let end = (index + SNIPPET_CONTEXT).min(chars.len());
+
+    let mut text = String::new();
+    let mut marker = String::new();
+    if start > 0 {
+        text.push_str("...");
+        marker.push_str("   ");
+    }
+    for (i, c) in chars[start..end].iter().enumerate() {
+        let c = if c.is_control() && *c != '\t' { ' ' } else { *c };
+        text.push(c);
+        if start + i < index {
+            marker.push(if c == '\t' { '\t' } else { ' ' });
+        }
+    }
+    if end < chars.len() {
+        text.push_str("...");
+    }
+    marker.push('^');
+
+    let gutter = line.to_string();
+    format!(
+        "{} | {}\n{} | {}",
+        gutter,
+        text,
+        " ".repeat(gutter.len()),
+        marker
+    )
+}
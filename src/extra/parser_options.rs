@@ -0,0 +1,150 @@
+use std::ops::{BitOr, BitOrAssign};
+
+use crate::base::DuplicateKeyPolicy;
+
+/// A set of non-standard syntax extensions accepted by a `JsonParser`.
+///
+/// Sets are combined with `|`, e.g. `Extensions::COMMENTS | Extensions::TRAILING_COMMAS`.
+/// The default set is the one `JsonParser::parse` has always accepted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Extensions(u8);
+
+impl Extensions {
+    /// No extensions: only standard JSON is accepted.
+    pub const NONE: Extensions = Extensions(0);
+
+    /// Numbers with a leading `+`, a leading `.` or a trailing `.`, such as `+1`, `.5` and `1.`.
+    pub const LENIENT_NUMBERS: Extensions = Extensions(1);
+
+    /// The case-insensitive `nan`, `inf` and `-inf` number literals.
+    pub const NAN_AND_INFINITY: Extensions = Extensions(1 << 1);
+
+    /// Unescaped control characters, such as raw tabs and newlines, inside strings.
+    pub const CONTROL_CHARACTERS: Extensions = Extensions(1 << 2);
+
+    /// `// line` and `/* block */` comments wherever whitespace is allowed.
+    pub const COMMENTS: Extensions = Extensions(1 << 3);
+
+    /// A single trailing comma after the last element of an array or object.
+    pub const TRAILING_COMMAS: Extensions = Extensions(1 << 4);
+
+    /// A UTF-8 byte order mark, `EF BB BF`, at the very start of the document, which is skipped.
+    pub const BYTE_ORDER_MARK: Extensions = Extensions(1 << 5);
+
+    /// Every extension.
+    pub const ALL: Extensions = Extensions((1 << 6) - 1);
+
+    /// Returns `true` if every extension in `other` is also in `self`.
+    pub fn contains(self, other: Extensions) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns the extensions of `self` that are not in `other`.
+    pub fn without(self, other: Extensions) -> Extensions {
+        Extensions(self.0 & !other.0)
+    }
+}
+
+impl Default for Extensions {
+    /// Returns the extensions historically accepted by `JsonParser::parse`: lenient numbers,
+    /// `nan` and `inf`, and raw control characters in strings.
+    fn default() -> Self {
+        Extensions::LENIENT_NUMBERS | Extensions::NAN_AND_INFINITY | Extensions::CONTROL_CHARACTERS
+    }
+}
+
+impl BitOr for Extensions {
+    type Output = Extensions;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Extensions(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Extensions {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// What a `JsonParser` does with a `\u` escape of a lone UTF-16 surrogate, such as `"\ud83d"`.
+/// A high surrogate followed by an escaped low surrogate always decodes to a single character.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SurrogatePolicy {
+    /// Fails with a syntax error.
+    #[default]
+    Reject,
+    /// Decodes the lone surrogate as U+FFFD REPLACEMENT CHARACTER.
+    Replace,
+}
+
+/// The settings of a `JsonParser` or `JsonReader`, built with `JsonParser::builder()`.
+///
+/// By default nesting is limited to `DEFAULT_MAX_DEPTH` levels and the other limits are
+/// disabled (`None`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParserOptions {
+    pub(crate) max_depth: Option<usize>,
+    pub(crate) max_string_length: Option<usize>,
+    pub(crate) max_document_size: Option<usize>,
+    pub(crate) duplicate_keys: DuplicateKeyPolicy,
+    pub(crate) extensions: Extensions,
+    pub(crate) lone_surrogates: SurrogatePolicy,
+    pub(crate) intern_keys: bool,
+}
+
+impl ParserOptions {
+    /// The default maximum nesting depth. It keeps hostile input such as a long run of `[`
+    /// from producing trees that are expensive to process, and can be raised with
+    /// `JsonParserBuilder::with_max_depth`.
+    pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+    /// Returns the maximum number of nested arrays and objects.
+    pub fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    /// Returns the maximum length of a string or key, in bytes after unescaping.
+    pub fn max_string_length(&self) -> Option<usize> {
+        self.max_string_length
+    }
+
+    /// Returns the maximum size of a document, in bytes.
+    pub fn max_document_size(&self) -> Option<usize> {
+        self.max_document_size
+    }
+
+    /// Returns what happens when an object contains the same key twice.
+    pub fn duplicate_keys(&self) -> DuplicateKeyPolicy {
+        self.duplicate_keys
+    }
+
+    /// Returns the accepted syntax extensions.
+    pub fn extensions(&self) -> Extensions {
+        self.extensions
+    }
+
+    /// Returns what happens to escaped lone surrogates.
+    pub fn lone_surrogates(&self) -> SurrogatePolicy {
+        self.lone_surrogates
+    }
+
+    /// Returns `true` if equal keys of a document share one allocation.
+    pub fn intern_keys(&self) -> bool {
+        self.intern_keys
+    }
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        ParserOptions {
+            max_depth: Some(ParserOptions::DEFAULT_MAX_DEPTH),
+            max_string_length: None,
+            max_document_size: None,
+            duplicate_keys: DuplicateKeyPolicy::default(),
+            extensions: Extensions::default(),
+            lone_surrogates: SurrogatePolicy::default(),
+            intern_keys: false,
+        }
+    }
+}
@@ -8,6 +8,7 @@
 //!
 //! # Features
 //! - Serialization
+//! - `simd` (default): Scans input in 64-byte blocks with SSE2 or AVX2 on x86_64, detected at runtime.
 //!
 //! # Usage
 //!
@@ -59,8 +60,15 @@
 ///
 /// # Types
 /// - `JsonValue`: Represents a JSON value, which can be a string, number, boolean, null, object, or array.
+/// - `JsonValueRef`: A JSON value whose strings and keys borrow from the parsed text.
+/// - `JsonArena`, `ArenaValue` and `ArenaNumber`: Documents allocated in one region and freed at once, with the `arena` feature.
 /// - `JsonObject`: Represents a JSON object, which is a collection of key-value pairs.
 /// - `JsonArray`: Represents a JSON array, which is an ordered list of values.
+/// - `JsonNumber`: Represents a JSON number, keeping `i64` and `u64` integers exact.
+/// - `JsonDecimal`: An exact decimal number, with the `arbitrary_precision` feature.
+/// - `EscapeOptions`: Selects which characters are escaped when strings are written.
+/// - `NumberFormat`: Selects when floats are written in exponent notation.
+/// - `CanonicalError`: Raised by `JsonValue::canonical_bytes` for numbers without an RFC 8785 form.
 pub mod base;
 
 /// `extra` module for additional JSON utilities.
@@ -70,6 +78,11 @@ pub mod base;
 ///
 /// # Features
 /// - `JsonParser`: Provides functionality to parse JSON strings into `JsonValue`.
+/// - `JsonParserBuilder`: Configures parser limits, the duplicate-key policy and syntax extensions.
+/// - `JsonReader`: Pull reader yielding a document as a sequence of `JsonEvent`s.
+/// - `JsonTape`: Document indexed in one pass whose values are decoded only when accessed.
+/// - `JsonVisitor`: SAX-style callbacks driven by the parser without building a `JsonValue`.
+/// - `JsonWriter`: Push-based writer that streams a document to an `io::Write` call by call.
 /// - `JsonEntity`: Trait for types that can be converted to and from JSON.
 /// - `ConversationError`: Error type for handling conversion errors.
 pub mod extra;
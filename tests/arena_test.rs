@@ -0,0 +1,99 @@
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+    use std::error::Error;
+    use std::fs;
+
+    use rusty_json::base::{ArenaValue, DuplicateKeyPolicy, JsonArena, JsonValue};
+    use rusty_json::extra::{JsonParser};
+
+    #[test]
+    fn arena_documents_match_json_value() -> Result<(), Box<dyn Error>> {
+        let arena = JsonArena::new();
+        for file in ["tests/flats.json", "tests/complex_flats.json"] {
+            let content = fs::read_to_string(file)?;
+            let value = JsonParser::parse_in(&arena, &content)?;
+            let expected = JsonParser::parse(&content)?;
+            assert_eq!(value.to_json_value(), expected);
+            assert_eq!(value.parse::<JsonValue>()?, expected);
+            assert_eq!(value.to_string(), expected.to_string());
+        }
+
+        let content = r#"{"id": 18446744073709551615, "tags": ["a", "b\n"], "ok": true, "nested": {"n": null}}"#;
+        let value = JsonParser::parse_in(&arena, content)?;
+        assert_eq!(value["id"].parse::<u64>()?, u64::MAX);
+        assert_eq!(value["tags"].parse::<Vec<String>>()?, ["a", "b\n"]);
+        assert!(value["ok"].parse::<bool>()?);
+        assert_eq!(value["nested"].parse::<HashMap<String, JsonValue>>()?["n"], JsonValue::Null);
+        assert!(matches!(value["tags"][1], ArenaValue::String("b\n")));
+        assert!(matches!(value["ok"][3], ArenaValue::Null));
+        assert!(value.get("missing").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn numbers_keep_their_representation() -> Result<(), Box<dyn Error>> {
+        let content = "[0, -7, 18446744073709551615, -9223372036854775808, 1.50, 2.5e-3, 123456789012345678901234567890]";
+        let expected = JsonParser::parse(content)?;
+        let arena = JsonArena::new();
+        let value = JsonParser::parse_in(&arena, content)?;
+        assert_eq!(value.to_json_value(), expected);
+        assert_eq!(value.to_string(), expected.to_string());
+        let ArenaValue::Number(number) = value[4] else { panic!("expected a number") };
+        assert_eq!(number.to_string(), expected[4].to_string());
+
+        // Nothing in the arena needs dropping, so it can move to another thread and be reset there.
+        std::thread::spawn(move || {
+            let mut arena = arena;
+            arena.reset();
+        }).join().map_err(|_| "the arena thread panicked")?;
+        Ok(())
+    }
+
+    #[test]
+    fn duplicate_keys_follow_the_policy() -> Result<(), Box<dyn Error>> {
+        let wide: Vec<String> = (0..40).map(|i| format!("\"k{}\": {}", i % 25, i)).collect();
+        let wide = format!("{{{}}}", wide.join(", "));
+        let arena = JsonArena::new();
+        for content in [r#"{"a": 1, "b": [2], "a": "x", "a": {"c": 3}}"#, wide.as_str()] {
+            for policy in [DuplicateKeyPolicy::KeepLast, DuplicateKeyPolicy::KeepFirst, DuplicateKeyPolicy::Collect] {
+                let parser = JsonParser::builder().with_duplicate_keys(policy).build();
+                assert_eq!(parser.read_in(&arena, content)?.to_json_value(), parser.read(content)?);
+            }
+        }
+        let reject = JsonParser::builder().with_duplicate_keys(DuplicateKeyPolicy::Reject).build();
+        assert!(reject.read_in(&arena, r#"{"a": 1, "a": 2}"#).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn reset_reuses_the_arena() -> Result<(), Box<dyn Error>> {
+        let content = fs::read_to_string("tests/complex_flats.json")?;
+        let mut arena = JsonArena::new();
+        let mut reserved = Vec::new();
+        for _ in 0..8 {
+            let value = JsonParser::parse_in(&arena, &content)?;
+            assert!(matches!(value, ArenaValue::Array(_)));
+            arena.reset();
+            reserved.push(arena.allocated_bytes());
+        }
+        // Once a single chunk holds the whole document, the arena stops growing.
+        assert!(reserved.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(reserved[5..], [reserved[7]; 3]);
+        assert!(JsonParser::parse_in(&arena, "[1, 2").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn deep_documents_without_recursion() -> Result<(), Box<dyn Error>> {
+        let depth = 100_000;
+        let content = format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        let parser = JsonParser::builder().with_max_depth(usize::MAX).build();
+
+        let arena = JsonArena::new();
+        let value = parser.read_in(&arena, &content)?;
+        assert_eq!(value.to_string(), content);
+        assert_eq!(value.to_json_value().to_string(), content);
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+
+    use rusty_json::base::JsonValue;
+    use rusty_json::extra::JsonParser;
+
+    #[test]
+    fn special_bytes_at_every_block_offset() -> Result<(), Box<dyn Error>> {
+        for offset in 0..200 {
+            let prefix = "a".repeat(offset);
+            let content = format!(r#"["{prefix}\"q\\\n", "{prefix}é{prefix}"]"#);
+            let expected = JsonValue::Array(vec![
+                JsonValue::String(format!("{prefix}\"q\\\n")),
+                JsonValue::String(format!("{prefix}é{prefix}")),
+            ].into());
+            assert_eq!(JsonParser::parse(&content)?, expected);
+            assert_eq!(JsonParser::parse_reader(content.as_bytes())?, expected);
+
+            let raw = format!("[\"{prefix}\t\"]");
+            assert!(JsonParser::strict().read(&raw).is_err());
+            assert_eq!(JsonParser::parse(&raw)?[0], JsonValue::String(format!("{prefix}\t")));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn long_whitespace_and_lines_keep_positions() -> Result<(), Box<dyn Error>> {
+        for width in [0, 63, 64, 65, 200] {
+            let padding = " \t\r\n".repeat(width);
+            let text = format!("{}ü", "x".repeat(width));
+            let content = format!("[{padding}1,{padding}\"{text}\",{padding}2{padding}]");
+            let value = JsonParser::parse_reader(content.as_bytes())?;
+            assert_eq!(value, JsonParser::parse(&content)?);
+            assert_eq!(value[1].parse::<String>()?, text);
+            assert_eq!(value[2].parse::<i32>()?, 2);
+
+            let broken = format!("[{padding}\"{text}\", ]");
+            let before = &broken[..broken.len() - 1];
+            let error = JsonParser::parse_reader(broken.as_bytes()).unwrap_err();
+            let parse_error = error.parse_error().expect("expected a parse error");
+            assert_eq!(parse_error.line(), 1 + width);
+            assert_eq!(parse_error.column(), 1 + before.rsplit('\n').next().unwrap_or_default().chars().count());
+            assert_eq!(JsonParser::parse(&broken).unwrap_err().parse_error(), Some(parse_error));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_utf8_is_rejected_in_long_strings() -> Result<(), Box<dyn Error>> {
+        for offset in [0, 63, 64, 130] {
+            let mut content = format!("[\"{}", "a".repeat(offset)).into_bytes();
+            content.extend_from_slice(&[0xC3, 0x28]);
+            content.extend_from_slice(b"\"]");
+            assert!(JsonParser::parse_reader(content.as_slice()).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn long_numbers_at_every_block_offset() -> Result<(), Box<dyn Error>> {
+        for length in 1..150 {
+            let digits = "7".repeat(length);
+            let content = format!("[{digits},-{digits}, {digits}.5 ,{digits}e1]");
+            let value = JsonParser::parse(&content)?;
+            assert_eq!(value, JsonParser::parse_reader(content.as_bytes())?);
+            assert_eq!(value[0].parse::<f64>()?, digits.parse::<f64>()?);
+            assert_eq!(value[1].parse::<f64>()?, -digits.parse::<f64>()?);
+            if length < 19 {
+                assert_eq!(value[1].to_string(), format!("-{digits}"));
+            }
+            assert_eq!(value[2].parse::<f64>()?, format!("{digits}.5").parse::<f64>()?);
+
+            let error = JsonParser::strict().read(&format!("[0{digits}]")).unwrap_err();
+            assert_eq!(error.parse_error().map(|error| error.column()), Some(3));
+            assert!(JsonParser::parse(&format!("[{digits}x]")).is_err());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+#[cfg(test)]
+mod test {
+    use std::borrow::Cow;
+    use std::collections::HashMap;
+    use std::error::Error;
+
+    use rusty_json::base::{DuplicateKeyPolicy, JsonValue, JsonValueRef};
+    use rusty_json::extra::{JsonParser};
+
+    #[test]
+    fn strings_without_escapes_are_borrowed() -> Result<(), Box<dyn Error>> {
+        let content = r#"{"plain": "text", "esc\naped": "a\tb", "list": ["x", "\u00e9"]}"#;
+        let value = JsonParser::parse_borrowed(content)?;
+
+        let JsonValueRef::Object(pairs) = &value else { panic!("expected an object") };
+        let keys: Vec<_> = pairs.keys().map(|key| matches!(key, Cow::Borrowed(_))).collect();
+        assert_eq!(keys, [true, false, true]);
+
+        assert!(matches!(&value["plain"], JsonValueRef::String(Cow::Borrowed("text"))));
+        assert!(matches!(&value["esc\naped"], JsonValueRef::String(Cow::Owned(s)) if s == "a\tb"));
+        assert!(matches!(&value["list"][0], JsonValueRef::String(Cow::Borrowed("x"))));
+        assert!(matches!(&value["list"][1], JsonValueRef::String(Cow::Owned(s)) if s == "é"));
+        Ok(())
+    }
+
+    #[test]
+    fn navigation_and_casting_match_json_value() -> Result<(), Box<dyn Error>> {
+        let content = r#"{"id": 18446744073709551615, "ratio": 0.5, "tags": ["a", "b"], "ok": true, "nested": {"n": null}}"#;
+        let value = JsonParser::parse_borrowed(content)?;
+
+        assert_eq!(value["id"].parse::<u64>()?, u64::MAX);
+        assert_eq!(value["ratio"].parse::<f64>()?, 0.5);
+        assert_eq!(value["tags"].parse::<Vec<String>>()?, ["a", "b"]);
+        assert!(value["ok"].parse::<bool>()?);
+        assert_eq!(value["nested"].parse::<HashMap<String, JsonValue>>()?["n"], JsonValue::Null);
+        assert_eq!(value["ok"][5], JsonValueRef::Null);
+        assert_eq!(value.to_string(), JsonParser::parse(content)?.to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn into_owned_equals_parse() -> Result<(), Box<dyn Error>> {
+        let content = r#"{"a": [1, "two\"", {"b": -3.5e2}], "a": "again", "c": "x"}"#;
+        for policy in [DuplicateKeyPolicy::KeepLast, DuplicateKeyPolicy::KeepFirst, DuplicateKeyPolicy::Collect] {
+            let parser = JsonParser::builder().with_duplicate_keys(policy).build();
+            let borrowed = parser.read_borrowed(content)?;
+            assert_eq!(borrowed.clone(), borrowed);
+            assert_eq!(borrowed.parse::<JsonValue>()?, parser.read(content)?);
+            assert_eq!(borrowed.into_owned(), parser.read(content)?);
+        }
+
+        let owned: JsonValue = {
+            let text = String::from(r#"["short lived"]"#);
+            let borrowed = JsonParser::parse_borrowed(&text)?;
+            borrowed.into_owned()
+        };
+        assert_eq!(owned[0].parse::<String>()?, "short lived");
+        Ok(())
+    }
+
+    #[test]
+    fn payloads_move_out_without_copies() -> Result<(), Box<dyn Error>> {
+        let content = r#"{"name": "text", "list": ["x", "a\tb"], "nested": {"n": null}}"#;
+        let value = JsonParser::parse_borrowed(content)?;
+
+        let mut pairs = value.into_object().expect("expected an object");
+        let name = pairs.swap_remove("name").and_then(JsonValueRef::into_cow);
+        assert!(matches!(name, Some(Cow::Borrowed("text"))));
+
+        let list = pairs.swap_remove("list").and_then(JsonValueRef::into_array).expect("expected an array");
+        let strings: Vec<_> = list.into_iter().filter_map(JsonValueRef::into_cow).collect();
+        assert_eq!(strings, [Cow::Borrowed("x"), Cow::Owned("a\tb".to_string())]);
+
+        let nested = pairs.swap_remove("nested").expect("expected a value");
+        assert!(nested.clone().into_array().is_none());
+        assert_eq!(nested.into_object().map(|pairs| pairs.len()), Some(1));
+        assert!(JsonValueRef::Null.into_cow().is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn deep_documents_without_recursion() -> Result<(), Box<dyn Error>> {
+        let depth = 100_000;
+        let content = format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        let parser = JsonParser::builder().with_max_depth(usize::MAX).build();
+
+        let value = parser.read_borrowed(&content)?;
+        assert_eq!(value.to_string(), content);
+        assert_eq!(value.clone(), value);
+        assert_eq!(value.into_owned().to_string(), content);
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+    use std::fs;
+
+    use rusty_json::extra::{Extensions, JsonParser, ParseErrorKind};
+
+    #[test]
+    fn bytes_parse_like_text() -> Result<(), Box<dyn Error>> {
+        let bytes = fs::read("tests/complex_flats.json")?;
+        assert_eq!(JsonParser::parse_bytes(&bytes)?, JsonParser::parse(std::str::from_utf8(&bytes)?)?);
+
+        let content = "{\"unicode\": \"こんにちは\", \"n\": [1, 2.5]}".as_bytes();
+        assert_eq!(JsonParser::parse_bytes(content)?["unicode"].parse::<String>()?, "こんにちは");
+        assert!(JsonParser::strict().read_bytes(b"[NaN]").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_utf8_reports_its_byte_offset() -> Result<(), Box<dyn Error>> {
+        let mut content = "{\"a\": \"é\",\n \"b\": \"".as_bytes().to_vec();
+        content.extend_from_slice(&[b'x', 0xC3, 0x28, b'"', b'}']);
+
+        let error = JsonParser::parse_bytes(&content).unwrap_err();
+        let parse_error = error.parse_error().expect("expected a parse error");
+        assert_eq!(parse_error.kind(), ParseErrorKind::InvalidEncoding);
+        assert_eq!(parse_error.offset(), 20);
+        assert_eq!(parse_error.line(), 2);
+        assert_eq!(parse_error.column(), 9);
+        assert_eq!(parse_error.found(), "invalid UTF-8 byte 0xC3");
+
+        let long = [&b"[\""[..], &[b'a'; 200], &[0xFF], &b"\"]"[..]].concat();
+        assert_eq!(JsonParser::parse_bytes(&long).unwrap_err().parse_error().map(|e| e.offset()), Some(202));
+        Ok(())
+    }
+
+    #[test]
+    fn byte_order_mark_is_skipped_when_allowed() -> Result<(), Box<dyn Error>> {
+        let bytes = fs::read("tests/json_test_suite/i_structure_UTF-8_BOM_empty_object.json")?;
+        let parser = JsonParser::builder().with_extensions(Extensions::default() | Extensions::BYTE_ORDER_MARK).build();
+
+        assert!(JsonParser::parse_bytes(&bytes).is_err());
+        assert_eq!(parser.read_bytes(&bytes)?.to_string(), "{}");
+        assert_eq!(parser.read_reader(bytes.as_slice())?.to_string(), "{}");
+        assert_eq!(parser.read("\u{feff}[1]")?.to_string(), "[1]");
+        assert!(parser.read("[\u{feff}1]").is_err());
+        assert!(parser.read_bytes(&fs::read("tests/json_test_suite/n_structure_UTF8_BOM_no_data.json")?).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn wide_encodings_are_detected() -> Result<(), Box<dyn Error>> {
+        let cases = [
+            ("tests/json_test_suite/i_string_UTF-16LE_with_BOM.json", "UTF-16LE text"),
+            ("tests/json_test_suite/i_string_utf16LE_no_BOM.json", "UTF-16LE text"),
+            ("tests/json_test_suite/i_string_utf16BE_no_BOM.json", "UTF-16BE text"),
+        ];
+        for (file, found) in cases {
+            let error = JsonParser::parse_bytes(&fs::read(file)?).unwrap_err();
+            let parse_error = error.parse_error().expect("expected a parse error");
+            assert_eq!(parse_error.kind(), ParseErrorKind::InvalidEncoding);
+            assert_eq!((parse_error.offset(), parse_error.found()), (0, found));
+        }
+
+        let utf32 = "[1]".chars().flat_map(|c| (c as u32).to_le_bytes()).collect::<Vec<_>>();
+        let error = JsonParser::parse_bytes(&utf32).unwrap_err();
+        assert_eq!(error.parse_error().map(|e| e.found()), Some("UTF-32LE text"));
+        Ok(())
+    }
+
+    #[test]
+    fn zero_bytes_alone_do_not_mean_a_wide_encoding() -> Result<(), Box<dyn Error>> {
+        for input in [&b"\0a\0b"[..], b"a\0b\0", b"\0\0\0\x01", b"\0\x7F"] {
+            let error = JsonParser::parse_bytes(input).unwrap_err();
+            let parse_error = error.parse_error().expect("expected a parse error");
+            assert_eq!(parse_error.kind(), ParseErrorKind::Syntax, "{:?}", input);
+            assert_eq!(parse_error.offset(), 0, "{:?}", input);
+        }
+
+        let utf16 = "[1]".encode_utf16().flat_map(u16::to_be_bytes).collect::<Vec<_>>();
+        let error = JsonParser::parse_bytes(&utf16).unwrap_err();
+        assert_eq!(error.parse_error().map(|e| e.found()), Some("UTF-16BE text"));
+        Ok(())
+    }
+}
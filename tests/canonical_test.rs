@@ -0,0 +1,115 @@
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+    use std::io;
+
+    use rusty_json::base::{CanonicalError, JsonNumber, JsonValue};
+    use rusty_json::extra::{JsonFormatter, JsonParser, JsonWriter, JsonWriterError};
+
+    #[test]
+    fn rfc_8785_example() -> Result<(), Box<dyn Error>> {
+        // RFC 8785, section 3.2.2.
+        let input = r#"{
+            "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+            "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+            "literals": [null, true, false]
+        }"#;
+        let expected = r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#;
+
+        let value = JsonParser::parse(input)?;
+        assert_eq!(value.canonical_bytes()?, expected.as_bytes());
+        assert_eq!(JsonFormatter::canonical().format(&value), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn keys_are_sorted_by_utf16_code_units() -> Result<(), Box<dyn Error>> {
+        // RFC 8785, section 3.2.3.
+        let input = r#"{
+            "€": "Euro Sign",
+            "\r": "Carriage Return",
+            "דּ": "Hebrew Letter Dalet With Dagesh",
+            "1": "One",
+            "😀": "Emoji: Grinning Face",
+            "\u0080": "Control",
+            "ö": "Latin Small Letter O With Diaeresis"
+        }"#;
+        let order = ["\r", "1", "\u{80}", "ö", "€", "😀", "\u{fb33}"];
+
+        let canonical = String::from_utf8(JsonParser::parse(input)?.canonical_bytes()?)?;
+        let keys: Vec<String> = match JsonParser::parse(&canonical)? {
+            JsonValue::Object(object) => object.keys().map(|key| key.to_string()).collect(),
+            _ => Vec::new(),
+        };
+        assert_eq!(keys, order);
+        assert!(canonical.starts_with(r#"{"\r":"Carriage Return","1":"One","#));
+
+        let nested = JsonParser::parse(r#"[{"b": {"d": 1, "c": 2}, "a": []}]"#)?;
+        assert_eq!(nested.canonical_bytes()?, br#"[{"a":[],"b":{"c":2,"d":1}}]"#);
+        Ok(())
+    }
+
+    #[test]
+    fn numbers_follow_ecmascript() -> Result<(), Box<dyn Error>> {
+        // RFC 8785, appendix B.
+        let cases: [(u64, &str); 24] = [
+            (0x0000000000000000, "0"),
+            (0x8000000000000000, "0"),
+            (0x0000000000000001, "5e-324"),
+            (0x8000000000000001, "-5e-324"),
+            (0x7fefffffffffffff, "1.7976931348623157e+308"),
+            (0xffefffffffffffff, "-1.7976931348623157e+308"),
+            (0x4340000000000000, "9007199254740992"),
+            (0xc340000000000000, "-9007199254740992"),
+            (0x4430000000000000, "295147905179352830000"),
+            (0x44b52d02c7e14af5, "9.999999999999997e+22"),
+            (0x44b52d02c7e14af6, "1e+23"),
+            (0x44b52d02c7e14af7, "1.0000000000000001e+23"),
+            (0x444b1ae4d6e2ef4e, "999999999999999700000"),
+            (0x444b1ae4d6e2ef4f, "999999999999999900000"),
+            (0x444b1ae4d6e2ef50, "1e+21"),
+            (0x3eb0c6f7a0b5ed8c, "9.999999999999997e-7"),
+            (0x3eb0c6f7a0b5ed8d, "0.000001"),
+            (0x41b3de4355555553, "333333333.3333332"),
+            (0x41b3de4355555554, "333333333.33333325"),
+            (0x41b3de4355555555, "333333333.3333333"),
+            (0x41b3de4355555556, "333333333.3333334"),
+            (0x41b3de4355555557, "333333333.33333343"),
+            (0xbecbf647612f3696, "-0.0000033333333333333333"),
+            (0x43143ff3c1cb0959, "1424953923781206.2"),
+        ];
+        for (bits, expected) in cases {
+            let value = JsonValue::Number(JsonNumber::from_f64(f64::from_bits(bits)));
+            assert_eq!(String::from_utf8(value.canonical_bytes()?)?, expected, "{:#018x}", bits);
+        }
+
+        // Integers are numbers like any other, so those beyond 2^53 are rounded.
+        assert_eq!(JsonValue::from(u64::MAX).canonical_bytes()?, b"18446744073709552000");
+        assert_eq!(JsonValue::from(-42).canonical_bytes()?, b"-42");
+        Ok(())
+    }
+
+    #[test]
+    fn non_finite_numbers_are_rejected() -> Result<(), Box<dyn Error>> {
+        for bits in [0x7fffffffffffffff, 0x7ff0000000000000, 0xfff0000000000000] {
+            let value = JsonValue::from(vec![JsonValue::Number(JsonNumber::from_f64(f64::from_bits(bits)))]);
+            assert_eq!(value.canonical_bytes(), Err(CanonicalError));
+
+            let error = JsonFormatter::canonical().format_to_writer(&value, Vec::new()).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+            assert_eq!(JsonFormatter::canonical().format_canonical(&value), Err(CanonicalError));
+            assert_eq!(JsonFormatter::canonical().format(&value), "[null]");
+        }
+        let value = JsonParser::parse(r#"{"b": [1.0], "a": "x"}"#)?;
+        assert_eq!(JsonFormatter::default().format_canonical(&value)?, r#"{"a":"x","b":[1]}"#);
+        Ok(())
+    }
+
+    #[test]
+    fn writer_rejects_canonical_formatter() -> Result<(), Box<dyn Error>> {
+        let result = JsonWriter::with_formatter(Vec::new(), JsonFormatter::canonical());
+        assert!(matches!(result, Err(JsonWriterError::CanonicalFormatter)));
+        assert!(JsonWriter::with_formatter(Vec::new(), JsonFormatter::builder().with_sorted_keys(true).build()).is_ok());
+        Ok(())
+    }
+}
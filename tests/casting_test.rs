@@ -28,8 +28,8 @@ mod test {
         let true_value = true_json.parse::<bool>()?;
         let false_value = false_json.parse::<bool>()?;
 
-        assert_eq!(true_value, true);
-        assert_eq!(false_value, false);
+        assert!(true_value);
+        assert!(!false_value);
         assert!(is_null!(null_json));
 
         Ok(())
@@ -68,7 +68,7 @@ mod test {
         assert_eq!(name, "Ammar Dev");
         assert_eq!(age, 27);
         assert_eq!(hobbies, vec!["coding".to_string(), "IDK".to_string()]);
-        assert_eq!(is_developer, true);
+        assert!(is_developer);
         assert_eq!(street, "123 Main St");
         assert_eq!(city, "Anytown");
 
@@ -98,7 +98,7 @@ mod test {
                 assert_eq!(name, "Ammar Dev");
                 assert_eq!(age, 27);
                 assert_eq!(hobbies, vec!["coding".to_string(), "IDK".to_string()]);
-                assert_eq!(is_developer, true);
+                assert!(is_developer);
             },
             _ => panic!("Parsed JSON is not an object"),
         }
@@ -130,7 +130,7 @@ mod test {
     #[test]
     fn cast_to_various_types() -> Result<(), Box<dyn Error>> {
         let int_json = json!(42);
-        let float_json = json!(3.14);
+        let float_json = json!(2.5);
         let bool_json = json!(true);
         let string_json = json!("example");
 
@@ -140,8 +140,8 @@ mod test {
         let string_value = string_json.parse::<String>()?;
 
         assert_eq!(int_value, 42);
-        assert!((float_value - 3.14).abs() < f64::EPSILON);
-        assert_eq!(bool_value, true);
+        assert!((float_value - 2.5).abs() < f64::EPSILON);
+        assert!(bool_value);
         assert_eq!(string_value, "example");
 
         Ok(())
@@ -151,14 +151,14 @@ mod test {
     fn cast_json_with_numbers() -> Result<(), Box<dyn Error>> {
         let json_data = json!({
             integer: 42,
-            floating: 3.14
+            floating: 2.5
         });
 
         let integer = json_data["integer"].parse::<i32>()?;
         let floating = json_data["floating"].parse::<f64>()?;
 
         assert_eq!(integer, 42);
-        assert!((floating - 3.14).abs() < f64::EPSILON);
+        assert!((floating - 2.5).abs() < f64::EPSILON);
 
         Ok(())
     }
@@ -175,8 +175,8 @@ mod test {
         let false_value = json_data["false_value"].parse::<bool>()?;
         let null_value: JsonValue = json_data["null_value"].parse()?;
 
-        assert_eq!(true_value, true);
-        assert_eq!(false_value, false);
+        assert!(true_value);
+        assert!(!false_value);
         assert!(is_null!(null_value));
 
         Ok(())
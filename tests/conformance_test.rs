@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+    use std::fs;
+    use std::panic;
+
+    use rusty_json::extra::{JsonParser};
+
+    const SUITE: &str = "tests/json_test_suite";
+
+    /// The variable naming another `test_parsing` directory to run instead of the bundled one,
+    /// such as that of an upstream JSONTestSuite checkout.
+    const SUITE_VARIABLE: &str = "JSON_TEST_SUITE";
+
+    /// Parses `bytes` with a strict parser, from a reader and, when the bytes are valid
+    /// UTF-8, from a string, and returns whether the document was accepted.
+    fn accepts(bytes: &[u8]) -> Result<bool, String> {
+        let parser = JsonParser::strict();
+        let from_reader = parser.read_reader(bytes).is_ok();
+        if let Ok(text) = std::str::from_utf8(bytes) {
+            let from_str = parser.read(text).is_ok();
+            if from_str != from_reader {
+                return Err(format!("parse accepted: {}, parse_reader accepted: {}", from_str, from_reader));
+            }
+        }
+        Ok(from_reader)
+    }
+
+    #[test]
+    fn json_test_suite() -> Result<(), Box<dyn Error>> {
+        let external = std::env::var(SUITE_VARIABLE).ok();
+        let suite = external.as_deref().unwrap_or(SUITE);
+        let mut names: Vec<String> = fs::read_dir(suite)?
+            .map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().into_owned()))
+            .collect::<Result<_, _>>()?;
+        names.retain(|name| name.ends_with(".json"));
+        names.sort();
+
+        let mut failures = Vec::new();
+        for name in &names {
+            let bytes = fs::read(format!("{}/{}", suite, name))?;
+            let outcome = panic::catch_unwind(|| accepts(&bytes));
+            let failure = match (&name[..2], outcome) {
+                (_, Err(_)) => Some("panicked".to_string()),
+                (_, Ok(Err(message))) => Some(message),
+                ("y_", Ok(Ok(accepted))) => (!accepted).then(|| "rejected a valid document".to_string()),
+                ("n_", Ok(Ok(accepted))) => accepted.then(|| "accepted an invalid document".to_string()),
+                // Either outcome is allowed, as long as it is returned rather than a panic.
+                ("i_", Ok(Ok(_))) => None,
+                (prefix, _) => Some(format!("unknown prefix {}", prefix)),
+            };
+            if let Some(failure) = failure {
+                failures.push(format!("{}: {}", name, failure));
+            }
+        }
+
+        if external.is_none() {
+            assert_eq!(names.len(), 318, "unexpected number of files in {}", SUITE);
+        }
+        assert!(failures.is_empty(), "{} failures:\n{}", failures.len(), failures.join("\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn default_parser_keeps_its_extensions() -> Result<(), Box<dyn Error>> {
+        for input in ["[1.]", "[.5]", "[+1]", "[NaN]", "[\"a\tb\"]"] {
+            assert!(JsonParser::parse(input).is_ok(), "{}", input);
+            assert!(JsonParser::strict().read(input).is_err(), "{}", input);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+
+    use rusty_json::base::casting::CastError;
+    use rusty_json::base::{JsonDecimal, JsonNumber, JsonValue};
+    use rusty_json::extra::{JsonFormatter, JsonParser};
+
+    #[test]
+    fn round_trip_exact_amounts() -> Result<(), Box<dyn Error>> {
+        let content = r#"{"amount": 12345678901234567890.123456789, "rate": 1.50, "big": 1e400, "id": 42}"#;
+        let value = JsonParser::parse(content)?;
+
+        let JsonValue::Number(amount) = &value["amount"] else { panic!("expected a number") };
+        assert!(amount.is_decimal());
+        assert_eq!(amount.as_decimal().map(|d| d.as_str()), Some("12345678901234567890.123456789"));
+        assert_eq!(value["id"], JsonValue::Number(JsonNumber::from(42)));
+
+        let expected = r#"{"amount": 12345678901234567890.123456789, "rate": 1.50, "big": 1e400, "id": 42}"#;
+        assert_eq!(value.to_string(), expected);
+        assert_eq!(JsonParser::parse(&JsonFormatter::default().format(&value))?, value);
+        assert_eq!(JsonParser::parse_reader(content.as_bytes())?.to_string(), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn compare_and_compute_exactly() -> Result<(), Box<dyn Error>> {
+        let a: JsonDecimal = "0.1".parse()?;
+        let b: JsonDecimal = "0.2".parse()?;
+        assert_eq!(a.checked_add(&b)?, "0.3".parse()?);
+        assert_eq!(b.checked_sub(&a)?.to_string(), "0.1");
+        assert_eq!(a.checked_sub(&b)?.to_string(), "-0.1");
+        assert_eq!("1.5".parse::<JsonDecimal>()?.checked_mul(&"-2.25".parse()?)?.to_string(), "-3.375");
+        assert_eq!((-a.clone()).to_string(), "-0.1");
+
+        assert_eq!("1.50".parse::<JsonDecimal>()?, "1.5".parse()?);
+        assert_eq!("-0".parse::<JsonDecimal>()?, "0".parse()?);
+        assert!("9.99".parse::<JsonDecimal>()? < "10".parse()?);
+        assert!("-10".parse::<JsonDecimal>()? < "-9.99".parse()?);
+        assert_eq!("+.5".parse::<JsonDecimal>()?.to_string(), "0.5");
+
+        let huge: JsonDecimal = "99999999999999999999999999.99".parse()?;
+        assert_eq!(huge.checked_add(&"0.01".parse()?)?.to_string(), "1e26");
+        assert!("1x".parse::<JsonDecimal>().is_err());
+
+        assert_eq!(JsonParser::parse("[0.30]")?, JsonParser::parse("[0.3]")?);
+        assert_ne!(JsonParser::parse("[0.1000000000000000000001]")?, JsonParser::parse("[0.1]")?);
+        Ok(())
+    }
+
+    #[test]
+    fn explicit_casts_report_range_errors() -> Result<(), Box<dyn Error>> {
+        let value = JsonParser::parse("[1e400, 12.5, 170141183460469231731687303715884105728, 3e2, 12345678901234567890123]")?;
+
+        assert!(matches!(value[0].parse::<f64>(), Err(CastError::OutOfRange)));
+        assert_eq!(value[1].parse::<f64>()?, 12.5);
+        assert!(matches!(value[1].parse::<i64>(), Err(CastError::InvalidType)));
+        assert!(matches!(value[2].parse::<i128>(), Err(CastError::OutOfRange)));
+        assert_eq!(value[3].parse::<u16>()?, 300);
+        assert!(matches!(value[4].parse::<u64>(), Err(CastError::OutOfRange)));
+        assert_eq!(value[4].parse::<i128>()?, 12345678901234567890123);
+
+        let decimal = value[1].parse::<JsonDecimal>()?;
+        assert!(matches!(decimal.to_u64(), Err(CastError::InvalidType)));
+        assert!(matches!("-1".parse::<JsonDecimal>()?.to_u128(), Err(CastError::OutOfRange)));
+        assert_eq!("-170141183460469231731687303715884105728".parse::<JsonDecimal>()?.to_i128()?, i128::MIN);
+        Ok(())
+    }
+
+    #[test]
+    fn wide_integers_stay_exact() -> Result<(), Box<dyn Error>> {
+        let value = JsonValue::from(u128::MAX);
+        assert_eq!(value.to_string(), u128::MAX.to_string());
+        assert_eq!(value.parse::<u128>()?, u128::MAX);
+        assert_eq!(value.parse::<JsonDecimal>()?.to_u128()?, u128::MAX);
+        assert!(matches!(value.parse::<i128>(), Err(CastError::OutOfRange)));
+        assert_eq!(JsonValue::from(i128::MIN).parse::<i128>()?, i128::MIN);
+        Ok(())
+    }
+
+    #[test]
+    fn arithmetic_rejects_results_that_are_too_wide() -> Result<(), Box<dyn Error>> {
+        let huge: JsonDecimal = "1e2000000000".parse()?;
+        let tiny: JsonDecimal = "1e-2000000000".parse()?;
+        let one: JsonDecimal = "1".parse()?;
+
+        assert!(matches!(huge.checked_add(&one), Err(CastError::OutOfRange)));
+        assert!(matches!(one.checked_sub(&tiny), Err(CastError::OutOfRange)));
+        assert!(matches!(huge.checked_sub(&tiny), Err(CastError::OutOfRange)));
+        assert_eq!(huge.checked_add(&"0".parse()?)?, huge);
+        assert_eq!(huge.checked_add(&huge)?.to_string(), "2e2000000000");
+        assert_eq!(huge.checked_mul(&tiny)?, one);
+
+        let wide: JsonDecimal = "9".repeat(JsonDecimal::MAX_DIGITS / 2 + 1).parse()?;
+        assert!(matches!(wide.checked_mul(&wide), Err(CastError::OutOfRange)));
+        let edge = format!("1e{}", JsonDecimal::MAX_DIGITS - 1).parse::<JsonDecimal>()?;
+        let expected = format!("1.{}1e{}", "0".repeat(JsonDecimal::MAX_DIGITS - 2), JsonDecimal::MAX_DIGITS - 1);
+        assert_eq!(edge.checked_add(&one)?.to_string(), expected);
+        Ok(())
+    }
+}
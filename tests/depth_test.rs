@@ -0,0 +1,84 @@
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+
+    use rusty_json::base::{JsonArray, JsonObject, JsonValue};
+    use rusty_json::extra::{JsonParser, ParseErrorKind, ParserOptions};
+
+    const DEPTH: usize = 200_000;
+
+    fn nested_arrays(depth: usize) -> String {
+        format!("{}{}", "[".repeat(depth), "]".repeat(depth))
+    }
+
+    fn nested_objects(depth: usize) -> String {
+        format!("{}1{}", r#"{"a":"#.repeat(depth), "}".repeat(depth))
+    }
+
+    #[test]
+    fn hostile_nesting_is_rejected() -> Result<(), Box<dyn Error>> {
+        let content = "[".repeat(DEPTH);
+
+        let error = JsonParser::parse(&content).unwrap_err();
+        let parse_error = error.parse_error().expect("expected a parse error");
+        assert_eq!(parse_error.kind(), ParseErrorKind::DepthLimitExceeded);
+        assert_eq!(parse_error.column(), ParserOptions::DEFAULT_MAX_DEPTH + 1);
+
+        let error = JsonParser::parse_reader(content.as_bytes()).unwrap_err();
+        assert_eq!(error.parse_error().map(|e| e.kind()), Some(ParseErrorKind::DepthLimitExceeded));
+
+        let limit = ParserOptions::DEFAULT_MAX_DEPTH;
+        assert!(JsonParser::parse(&nested_arrays(limit)).is_ok());
+        assert!(JsonParser::parse(&nested_arrays(limit + 1)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn deep_arrays_are_handled_without_recursion() -> Result<(), Box<dyn Error>> {
+        let content = nested_arrays(DEPTH);
+        let parser = JsonParser::builder().with_max_depth(usize::MAX).build();
+
+        let value = parser.read(&content)?;
+        let copy = value.clone();
+        assert_eq!(copy, value);
+        assert_eq!(value.to_string(), content);
+        assert_ne!(value, parser.read(&nested_arrays(DEPTH - 1))?);
+        Ok(())
+    }
+
+    #[test]
+    fn deep_objects_are_handled_without_recursion() -> Result<(), Box<dyn Error>> {
+        let content = nested_objects(DEPTH);
+        let parser = JsonParser::builder().with_max_depth(usize::MAX).build();
+
+        let value = parser.read(&content)?;
+        let mut copy = JsonValue::Null;
+        copy.clone_from(&value);
+        assert_eq!(copy, value);
+        assert_eq!(value.to_string().len(), content.len() + DEPTH);
+        Ok(())
+    }
+
+    #[test]
+    fn deep_trees_built_by_hand() -> Result<(), Box<dyn Error>> {
+        let mut value = JsonValue::Null;
+        for i in 0..DEPTH {
+            if i % 2 == 1 {
+                let mut array = JsonArray::new();
+                array.push(value);
+                value = JsonValue::Array(array);
+            } else {
+                let mut object = JsonObject::new();
+                object.set("k", value);
+                value = JsonValue::Object(object);
+            }
+        }
+
+        let array = JsonArray::from(value.clone());
+        assert_eq!(JsonValue::Array(array.clone()), value);
+        assert!(format!("{:?}", array).ends_with("]}]}]"));
+        drop(array);
+        drop(value);
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+
+    use rusty_json::base::{DuplicateKeyPolicy, JsonObject, JsonValue};
+    use rusty_json::extra::{JsonParser, ParseErrorKind};
+
+    const CONTENT: &str = "{\n  \"id\": 1,\n  \"role\": \"user\",\n  \"role\": \"admin\",\n  \"tags\": [],\n  \"role\": \"root\"\n}";
+
+    fn parser(policy: DuplicateKeyPolicy) -> JsonParser {
+        JsonParser::builder().with_duplicate_keys(policy).build()
+    }
+
+    #[test]
+    fn reject_names_key_and_both_positions() -> Result<(), Box<dyn Error>> {
+        let parser = parser(DuplicateKeyPolicy::Reject);
+
+        let error = parser.read(CONTENT).unwrap_err();
+        let parse_error = error.parse_error().expect("expected a parse error");
+        assert_eq!(parse_error.kind(), ParseErrorKind::DuplicateKey);
+        assert_eq!(parse_error.duplicate_key(), Some("role"));
+        assert_eq!((parse_error.line(), parse_error.column()), (4, 3));
+
+        let first = parse_error.first_position().expect("expected the first position");
+        assert_eq!((first.line(), first.column(), first.offset()), (3, 3, 15));
+        assert_eq!(
+            parse_error.to_string(),
+            "duplicate key \"role\" at line 4, column 3, first defined at line 3, column 3\n4 |   \"role\": \"admin\",\n  |   ^"
+        );
+
+        let error = parser.read_reader(CONTENT.as_bytes()).unwrap_err();
+        assert_eq!(error.parse_error(), Some(parse_error));
+        Ok(())
+    }
+
+    #[test]
+    fn reject_is_scoped_to_each_object() -> Result<(), Box<dyn Error>> {
+        let parser = parser(DuplicateKeyPolicy::Reject);
+        assert!(parser.read(r#"{"a": {"a": {"a": 1}, "b": 2}, "b": [{"a": 1}, {"a": 2}]}"#).is_ok());
+
+        let error = parser.read(r#"[{"a": 1}, {"b": 1, "c": {"b": 2}, "b": 3}]"#).unwrap_err();
+        let parse_error = error.parse_error().expect("expected a parse error");
+        assert_eq!(parse_error.duplicate_key(), Some("b"));
+        assert_eq!(parse_error.first_position().map(|p| p.column()), Some(13));
+        assert_eq!(parse_error.column(), 36);
+        Ok(())
+    }
+
+    #[test]
+    fn keep_first_and_keep_last() -> Result<(), Box<dyn Error>> {
+        let first = parser(DuplicateKeyPolicy::KeepFirst).read(CONTENT)?;
+        assert_eq!(first["role"], JsonValue::String("user".to_string()));
+
+        let last = parser(DuplicateKeyPolicy::KeepLast).read(CONTENT)?;
+        assert_eq!(last["role"], JsonValue::String("root".to_string()));
+
+        let keys: Vec<_> = JsonObject::from(last).keys().cloned().collect();
+        assert_eq!(keys, vec!["id", "role", "tags"]);
+        Ok(())
+    }
+
+    #[test]
+    fn collect_into_array() -> Result<(), Box<dyn Error>> {
+        let collected = parser(DuplicateKeyPolicy::Collect).read(CONTENT)?;
+        assert_eq!(collected["role"], JsonParser::parse(r#"["user", "admin", "root"]"#)?);
+        assert_eq!(collected["tags"], JsonParser::parse("[]")?);
+
+        let collected = parser(DuplicateKeyPolicy::Collect).read(r#"{"a": [1], "a": [2], "a": 3}"#)?;
+        assert_eq!(collected["a"], JsonParser::parse("[[1], [2], 3]")?);
+        Ok(())
+    }
+
+    #[test]
+    fn build_objects_with_a_policy() -> Result<(), Box<dyn Error>> {
+        let pairs = vec![("a", 1), ("b", 2), ("a", 3), ("a", 4)];
+
+        let last = JsonObject::from_pairs(pairs.clone(), DuplicateKeyPolicy::KeepLast)?;
+        assert_eq!(last["a"], JsonValue::from(4));
+        let first = JsonObject::from_pairs(pairs.clone(), DuplicateKeyPolicy::KeepFirst)?;
+        assert_eq!(first["a"], JsonValue::from(1));
+        let collected = JsonObject::from_pairs(pairs.clone(), DuplicateKeyPolicy::Collect)?;
+        assert_eq!(collected["a"], JsonParser::parse("[1, 3, 4]")?);
+        assert_eq!(collected.len(), 2);
+
+        let error = JsonObject::from_pairs(pairs, DuplicateKeyPolicy::Reject).unwrap_err();
+        assert_eq!((error.key(), error.first(), error.second()), ("a", 0, 2));
+        assert_eq!(error.to_string(), "Duplicate key \"a\" at pairs 0 and 2");
+        Ok(())
+    }
+}
@@ -0,0 +1,60 @@
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+
+    use rusty_json::base::{EscapeOptions, JsonArray, JsonObject, JsonValue};
+    use rusty_json::extra::{JsonFormatter, JsonParser};
+
+    const TRICKY: &str = "say \"hi\"\\ \n\r\t\x08\x0C\x01\x1F\x7F é 😀 </a> & /";
+
+    #[test]
+    fn display_escapes_strings_and_keys() -> Result<(), Box<dyn Error>> {
+        let mut object = JsonObject::new();
+        object.set("a\"b", TRICKY);
+        let value = JsonValue::Object(object.clone());
+
+        let text = value.to_string();
+        assert_eq!(
+            text,
+            "{\"a\\\"b\": \"say \\\"hi\\\"\\\\ \\n\\r\\t\\b\\f\\u0001\\u001f\x7F é 😀 </a> & /\"}"
+        );
+        assert_eq!(object.to_string(), text);
+        assert_eq!(JsonParser::parse(&text)?, value);
+
+        let array = JsonArray::from(vec![TRICKY]);
+        assert_eq!(JsonParser::parse(&array.to_string())?, JsonValue::Array(array));
+        Ok(())
+    }
+
+    #[test]
+    fn formatter_round_trips_escaped_strings() -> Result<(), Box<dyn Error>> {
+        let mut object = JsonObject::new();
+        object.set("line\nbreak", JsonArray::from(vec![TRICKY]));
+        let value = JsonValue::Object(object);
+
+        let text = JsonFormatter::default().format(&value);
+        assert!(text.contains(r#""line\nbreak": ["#));
+        assert_eq!(JsonParser::parse(&text)?, value);
+        Ok(())
+    }
+
+    #[test]
+    fn formatter_escape_options() -> Result<(), Box<dyn Error>> {
+        let value = JsonValue::String("é😀 </a> & /".to_string());
+        let format = |escape: EscapeOptions| JsonFormatter::builder().with_escape_options(escape).build().format(&value);
+
+        assert_eq!(format(EscapeOptions::new()), r#""é😀 </a> & /""#);
+        assert_eq!(format(EscapeOptions::new().with_ascii_only(true)), r#""\u00e9\ud83d\ude00 </a> & /""#);
+        assert_eq!(format(EscapeOptions::new().with_escaped_slash(true)), r#""é😀 <\/a> & \/""#);
+        assert_eq!(
+            format(EscapeOptions::new().with_html_safe(true)),
+            r#""é😀 \u003c/a\u003e \u0026 /""#
+        );
+
+        let all = EscapeOptions::new().with_ascii_only(true).with_escaped_slash(true).with_html_safe(true);
+        let text = format(all);
+        assert!(text.is_ascii());
+        assert_eq!(JsonParser::strict().read(&text)?, value);
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+
+    use rusty_json::base::{JsonNumber, JsonValue, NumberFormat};
+    use rusty_json::extra::{JsonFormatter, JsonParser};
+
+    /// Returns the float of a parsed number.
+    fn reparse(text: &str) -> Result<f64, Box<dyn Error>> {
+        match JsonParser::parse(text)? {
+            JsonValue::Number(number) => Ok(number.as_f64()),
+            other => Err(format!("expected a number, found {}", other).into()),
+        }
+    }
+
+    #[test]
+    fn shortest_representation() -> Result<(), Box<dyn Error>> {
+        let cases = [
+            (0.1, "0.1"),
+            (1.5, "1.5"),
+            (3.0, "3"),
+            (-0.0, "-0"),
+            (1e300, "1e300"),
+            (1.5e-300, "1.5e-300"),
+            (1e20, "100000000000000000000"),
+            (1e21, "1e21"),
+            (123456.789, "123456.789"),
+            (0.000001, "0.000001"),
+            (1e-7, "1e-7"),
+            (5e-324, "5e-324"),
+            (f64::MAX, "1.7976931348623157e308"),
+            (f64::NAN, "NaN"),
+            (f64::INFINITY, "inf"),
+            (f64::NEG_INFINITY, "-inf"),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(JsonValue::from(value).to_string(), expected);
+            assert_eq!(JsonFormatter::default().format(&JsonValue::from(value)), expected);
+        }
+        assert!(reparse("-inf")?.is_infinite());
+        Ok(())
+    }
+
+    #[test]
+    fn floats_round_trip_bit_for_bit() -> Result<(), Box<dyn Error>> {
+        let mut state: u64 = 0x2545F4914F6CDD1D;
+        let mut values = vec![0.1, 0.2, 0.3, 1.0 / 3.0, f64::MIN_POSITIVE, f64::EPSILON, 9007199254740993.0, -0.0];
+        for _ in 0..20_000 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            values.push(f64::from_bits(state));
+        }
+
+        let narrow = NumberFormat::new().with_exponent_thresholds(0, 1);
+        let wide = NumberFormat::new().with_exponent_thresholds(i32::MIN, i32::MAX);
+        for value in values.into_iter().filter(|value| value.is_finite()) {
+            let json = JsonValue::Number(JsonNumber::from_f64(value));
+            assert_eq!(reparse(&json.to_string())?.to_bits(), value.to_bits(), "{}", json);
+
+            for format in [narrow, wide] {
+                let text = JsonFormatter::builder().with_number_format(format).build().format(&json);
+                assert_eq!(reparse(&text)?.to_bits(), value.to_bits(), "{}", text);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn configurable_thresholds() -> Result<(), Box<dyn Error>> {
+        let value = JsonValue::from(vec![1234.5, 0.001, 1e16]);
+        let format = |numbers: NumberFormat| JsonFormatter::builder().with_number_format(numbers).build().format(&value);
+
+        assert_eq!(format(NumberFormat::new()), "[\n1234.5,\n0.001,\n10000000000000000\n]");
+        assert_eq!(
+            format(NumberFormat::new().with_exponent_thresholds(-2, 3)),
+            "[\n1.2345e3,\n1e-3,\n1e16\n]"
+        );
+        assert_eq!(JsonValue::from(u64::MAX).to_string(), "18446744073709551615");
+        Ok(())
+    }
+}
@@ -0,0 +1,221 @@
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+    use std::fs;
+    use std::io::{self, Write};
+
+    use rusty_json::extra::{JsonFormatter, JsonParser, JsonWriter, LineEnding};
+
+    /// A writer that accepts `capacity` bytes and fails afterwards.
+    struct Full {
+        capacity: usize,
+    }
+
+    impl Write for Full {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.capacity == 0 {
+                return Err(io::Error::new(io::ErrorKind::WriteZero, "disk full"));
+            }
+            let written = buf.len().min(self.capacity);
+            self.capacity -= written;
+            Ok(written)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn pretty_layout() -> Result<(), Box<dyn Error>> {
+        let value = JsonParser::parse(r#"{"name": "Ammar", "list": [1, {"a": [], "b": {}}], "ok": true}"#)?;
+        let expected = "{\n  \"name\": \"Ammar\",\n  \"list\": [\n    1,\n    {\n      \"a\": [],\n      \"b\": {}\n    }\n  ],\n  \"ok\": true\n}";
+        assert_eq!(JsonFormatter::default().format(&value), expected);
+
+        let tabs = JsonFormatter::builder().with_indent(1).with_indent_char('\t').build();
+        assert_eq!(tabs.format(&JsonParser::parse("[[1]]")?), "[\n\t[\n\t\t1\n\t]\n]");
+        assert_eq!(tabs.format(&JsonParser::parse("\"x\"")?), "\"x\"");
+        Ok(())
+    }
+
+    #[test]
+    fn streaming_matches_format() -> Result<(), Box<dyn Error>> {
+        let value = JsonParser::parse(&fs::read_to_string("tests/complex_flats.json")?)?;
+        let formatter = JsonFormatter::default();
+        let expected = formatter.format(&value);
+
+        let mut bytes = Vec::new();
+        formatter.format_to_writer(&value, &mut bytes)?;
+        assert_eq!(String::from_utf8(bytes)?, expected);
+
+        let mut text = String::new();
+        formatter.format_to_fmt(&value, &mut text)?;
+        assert_eq!(text, expected);
+        assert_eq!(JsonParser::parse(&text)?, value);
+        Ok(())
+    }
+
+    #[test]
+    fn writer_errors_are_returned() -> Result<(), Box<dyn Error>> {
+        let value = JsonParser::parse(&fs::read_to_string("tests/complex_flats.json")?)?;
+        let error = JsonFormatter::default().format_to_writer(&value, Full { capacity: 10_000 }).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::WriteZero);
+        Ok(())
+    }
+
+    #[test]
+    fn deep_documents_without_recursion() -> Result<(), Box<dyn Error>> {
+        let depth = 100_000;
+        let content = format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        let value = JsonParser::builder().with_max_depth(usize::MAX).build().read(&content)?;
+
+        let compact = JsonFormatter::builder().build();
+        let mut bytes = Vec::new();
+        compact.format_to_writer(&value, &mut bytes)?;
+        assert_eq!(bytes.len(), 2 * depth + 2 * (depth - 1));
+        Ok(())
+    }
+
+    #[test]
+    fn smart_layout_keeps_short_containers_inline() -> Result<(), Box<dyn Error>> {
+        let value = JsonParser::parse(r#"{"type": "Feature", "geometry": {"type": "LineString", "coordinates": [[102.5, 0.5], [103.5, 1.25], [104.5, 0.5]]}, "properties": {"name": "Dinagat", "tags": []}}"#)?;
+        let smart = |width| JsonFormatter::builder().with_indent(2).with_indent_char(' ').with_max_width(width).build();
+
+        let expected = "{\n  \"type\": \"Feature\",\n  \"geometry\": {\n    \"type\": \"LineString\",\n    \"coordinates\": [\n      [102.5, 0.5],\n      [103.5, 1.25],\n      [104.5, 0.5]\n    ]\n  },\n  \"properties\": {\n    \"name\": \"Dinagat\",\n    \"tags\": []\n  }\n}";
+        assert_eq!(smart(40).format(&value), expected);
+
+        let expected = "{\n  \"type\": \"Feature\",\n  \"geometry\": {\n    \"type\": \"LineString\",\n    \"coordinates\": [[102.5, 0.5], [103.5, 1.25], [104.5, 0.5]]\n  },\n  \"properties\": {\"name\": \"Dinagat\", \"tags\": []}\n}";
+        assert_eq!(smart(80).format(&value), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn smart_layout_counts_indent_key_and_comma() -> Result<(), Box<dyn Error>> {
+        let value = JsonParser::parse(r#"{"a": [1, 2, 3], "b": 1}"#)?;
+        let smart = |width| JsonFormatter::builder().with_indent(2).with_indent_char(' ').with_max_width(width).build();
+
+        // `  "a": [1, 2, 3],` is 17 columns wide.
+        assert_eq!(smart(17).format(&value), "{\n  \"a\": [1, 2, 3],\n  \"b\": 1\n}");
+        assert_eq!(smart(16).format(&value), "{\n  \"a\": [\n    1,\n    2,\n    3\n  ],\n  \"b\": 1\n}");
+        assert_eq!(smart(24).format(&value), r#"{"a": [1, 2, 3], "b": 1}"#);
+        assert_eq!(smart(0).format(&value), JsonFormatter::default().format(&value));
+        Ok(())
+    }
+
+    #[test]
+    fn smart_layout_stays_within_width() -> Result<(), Box<dyn Error>> {
+        let value = JsonParser::parse(&fs::read_to_string("tests/complex_flats.json")?)?;
+        let formatter = JsonFormatter::builder().with_indent(2).with_indent_char(' ').with_max_width(80).build();
+        let text = formatter.format(&value);
+        assert_eq!(JsonParser::parse(&text)?, value);
+
+        // Only a line holding a single long scalar may run past the width.
+        for line in text.lines().filter(|line| line.chars().count() > 80) {
+            let line = line.trim_end_matches(',');
+            assert!(!line.ends_with(']') && !line.ends_with('}'), "{}", line);
+        }
+        assert!(text.lines().count() < JsonFormatter::default().format(&value).lines().count());
+        Ok(())
+    }
+
+    #[test]
+    fn builder_defaults_indent_with_spaces() -> Result<(), Box<dyn Error>> {
+        let value = JsonParser::parse("[[1]]")?;
+        let text = JsonFormatter::builder().with_indent(2).build().format(&value);
+        assert_eq!(text, "[\n  [\n    1\n  ]\n]");
+        assert!(!text.contains('\0'));
+        Ok(())
+    }
+
+    #[test]
+    fn compact_mode_and_spacing() -> Result<(), Box<dyn Error>> {
+        let value = JsonParser::parse(r#"{"a": [1, 2], "b": {"c": null}, "d": []}"#)?;
+        let compact = JsonFormatter::builder().with_indent(2).with_compact(true);
+        assert_eq!(compact.build().format(&value), r#"{"a": [1, 2], "b": {"c": null}, "d": []}"#);
+
+        let minified = JsonFormatter::builder().with_compact(true).with_space_after_colon(false).with_space_after_comma(false);
+        assert_eq!(minified.build().format(&value), r#"{"a":[1,2],"b":{"c":null},"d":[]}"#);
+
+        // The comma spacing only applies to containers written on one line.
+        let pretty = JsonFormatter::builder().with_indent(1).with_space_after_colon(false).with_space_after_comma(false);
+        assert_eq!(pretty.build().format(&JsonParser::parse(r#"{"a": [1]}"#)?), "{\n \"a\":[\n  1\n ]\n}");
+        let smart = JsonFormatter::builder().with_max_width(80).with_space_after_comma(false);
+        assert_eq!(smart.build().format(&JsonParser::parse("[1, 2]")?), "[1,2]");
+        Ok(())
+    }
+
+    #[test]
+    fn sorted_keys() -> Result<(), Box<dyn Error>> {
+        let value = JsonParser::parse(r#"{"b": 1, "a": {"d": [{"z": 0, "y": 0}], "c": 3}, "B": 2}"#)?;
+        let sorted = JsonFormatter::builder().with_compact(true).with_sorted_keys(true).build();
+        assert_eq!(sorted.format(&value), r#"{"B": 2, "a": {"c": 3, "d": [{"y": 0, "z": 0}]}, "b": 1}"#);
+
+        let mut bytes = Vec::new();
+        sorted.format_to_writer(&value, &mut bytes)?;
+        assert_eq!(JsonParser::parse_bytes(&bytes)?, value);
+        Ok(())
+    }
+
+    #[test]
+    fn line_endings_and_trailing_newline() -> Result<(), Box<dyn Error>> {
+        let value = JsonParser::parse(r#"{"a": [1]}"#)?;
+        let windows = JsonFormatter::builder()
+            .with_indent(2)
+            .with_line_ending(LineEnding::CrLf)
+            .with_trailing_newline(true)
+            .build();
+        let expected = "{\r\n  \"a\": [\r\n    1\r\n  ]\r\n}\r\n";
+        assert_eq!(windows.format(&value), expected);
+
+        let mut bytes = Vec::new();
+        windows.format_to_writer(&value, &mut bytes)?;
+        assert_eq!(bytes, expected.as_bytes());
+
+        let mut writer = JsonWriter::with_formatter(Vec::new(), windows)?;
+        writer.value_ref(&value)?;
+        assert_eq!(writer.finish()?, expected.as_bytes());
+
+        let compact = JsonFormatter::builder().with_compact(true).with_trailing_newline(true).build();
+        assert_eq!(compact.format(&value), "{\"a\": [1]}\n");
+        Ok(())
+    }
+
+    #[test]
+    fn indent_unit_per_level() -> Result<(), Box<dyn Error>> {
+        let value = JsonParser::parse(r#"{"a": [1]}"#)?;
+        let tabs = JsonFormatter::builder().with_indent(4).with_indent_unit("\t").build();
+        assert_eq!(tabs.format(&value), "{\n\t\"a\": [\n\t\t1\n\t]\n}");
+
+        let marked = JsonFormatter::builder().with_indent_unit("| ").build();
+        assert_eq!(marked.format(&value), "{\n| \"a\": [\n| | 1\n| ]\n}");
+
+        // The width of the unit counts towards the maximum width.
+        let value = JsonParser::parse(r#"{"a": [1], "b": [2]}"#)?;
+        let smart = |unit| JsonFormatter::builder().with_indent_unit(unit).with_max_width(13).build();
+        assert_eq!(smart("    ").format(&value), "{\n    \"a\": [1],\n    \"b\": [2]\n}");
+        assert_eq!(smart("     ").format(&value), "{\n     \"a\": [\n          1\n     ],\n     \"b\": [2]\n}");
+        Ok(())
+    }
+
+    #[test]
+    fn writer_follows_the_style() -> Result<(), Box<dyn Error>> {
+        let formatter = JsonFormatter::builder()
+            .with_compact(true)
+            .with_sorted_keys(true)
+            .with_space_after_colon(false)
+            .with_space_after_comma(false)
+            .build();
+        let mut writer = JsonWriter::with_formatter(Vec::new(), formatter)?;
+        writer.begin_array()?;
+        writer.value_ref(&JsonParser::parse(r#"{"b": 1, "a": 2}"#)?)?;
+        writer.begin_object()?;
+        writer.key("y")?;
+        writer.value(1)?;
+        writer.key("x")?;
+        writer.value(2)?;
+        writer.end_object()?;
+        writer.end_array()?;
+        assert_eq!(writer.finish()?, br#"[{"a":2,"b":1},{"y":1,"x":2}]"#);
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+    use std::fs;
+
+    use rusty_json::base::JsonValue;
+    use rusty_json::extra::{JsonFormatter, JsonParser, JsonWriter, JsonWriterError};
+
+    /// Pushes `value` into `writer` one event at a time.
+    fn push(writer: &mut JsonWriter<Vec<u8>>, value: &JsonValue) -> Result<(), JsonWriterError> {
+        match value {
+            JsonValue::Object(object) => {
+                writer.begin_object()?;
+                for (key, value) in object.iter() {
+                    writer.key(key)?;
+                    push(writer, value)?;
+                }
+                writer.end_object()
+            }
+            JsonValue::Array(array) => {
+                writer.begin_array()?;
+                for value in array.iter() {
+                    push(writer, value)?;
+                }
+                writer.end_array()
+            }
+            _ => writer.value_ref(value),
+        }
+    }
+
+    #[test]
+    fn output_matches_formatter() -> Result<(), Box<dyn Error>> {
+        let value = JsonParser::parse(&fs::read_to_string("tests/complex_flats.json")?)?;
+        let nested = JsonParser::parse(r#"{"a": [], "b": {}, "c": [1, {"d": "x\n"}]}"#)?;
+        let tabs = JsonFormatter::builder().with_indent(1).with_indent_char('\t').build();
+        for formatter in [JsonFormatter::default(), tabs] {
+            for value in [&value, &nested] {
+                let mut writer = JsonWriter::with_formatter(Vec::new(), formatter.clone())?;
+                push(&mut writer, value)?;
+                assert_eq!(String::from_utf8(writer.finish()?)?, formatter.format(value));
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn whole_values_are_indented_in_place() -> Result<(), Box<dyn Error>> {
+        let mut writer = JsonWriter::new(Vec::new());
+        writer.begin_object()?;
+        writer.key("tree")?;
+        writer.value_ref(&JsonParser::parse("[1, [2]]")?)?;
+        writer.key("name")?;
+        writer.value("Ammar")?;
+        writer.end_object()?;
+        let text = String::from_utf8(writer.finish()?)?;
+        assert_eq!(text, "{\n  \"tree\": [\n    1,\n    [\n      2\n    ]\n  ],\n  \"name\": \"Ammar\"\n}");
+        Ok(())
+    }
+
+    #[test]
+    fn malformed_sequences_are_rejected() -> Result<(), Box<dyn Error>> {
+        let mut writer = JsonWriter::new(Vec::new());
+        assert!(matches!(writer.key("a"), Err(JsonWriterError::InvalidSequence(_))));
+        assert!(matches!(writer.end_array(), Err(JsonWriterError::InvalidSequence(_))));
+        writer.begin_object()?;
+        assert!(matches!(writer.value(1), Err(JsonWriterError::InvalidSequence(_))));
+        assert!(matches!(writer.begin_array(), Err(JsonWriterError::InvalidSequence(_))));
+        assert!(matches!(writer.end_array(), Err(JsonWriterError::InvalidSequence(_))));
+        writer.key("a")?;
+        assert!(matches!(writer.key("b"), Err(JsonWriterError::InvalidSequence(_))));
+        assert!(matches!(writer.end_object(), Err(JsonWriterError::InvalidSequence(_))));
+        writer.begin_array()?;
+        assert!(matches!(writer.key("c"), Err(JsonWriterError::InvalidSequence(_))));
+        assert!(matches!(writer.end_object(), Err(JsonWriterError::InvalidSequence(_))));
+        writer.end_array()?;
+        writer.end_object()?;
+        assert!(matches!(writer.value(2), Err(JsonWriterError::InvalidSequence(_))));
+
+        // Rejected calls leave nothing behind in the output.
+        assert_eq!(writer.finish()?, b"{\n  \"a\": []\n}");
+        Ok(())
+    }
+
+    #[test]
+    fn finish_requires_a_complete_document() -> Result<(), Box<dyn Error>> {
+        let writer = JsonWriter::new(Vec::new());
+        assert!(matches!(writer.finish(), Err(JsonWriterError::Incomplete(_))));
+
+        let mut writer = JsonWriter::new(Vec::new());
+        writer.begin_array()?;
+        writer.value(JsonValue::Null)?;
+        assert!(matches!(writer.finish(), Err(JsonWriterError::Incomplete(_))));
+
+        let mut writer = JsonWriter::new(Vec::new());
+        writer.value(true)?;
+        assert_eq!(writer.finish()?, b"true");
+        Ok(())
+    }
+}
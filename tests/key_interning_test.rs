@@ -0,0 +1,75 @@
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+    use std::fs;
+
+    use rusty_json::base::{JsonObject, JsonValue};
+    use rusty_json::extra::{JsonParser};
+
+    fn first_key(value: &JsonValue) -> &String {
+        match value {
+            JsonValue::Object(object) => object.keys().next().expect("a non-empty object"),
+            _ => panic!("expected an object"),
+        }
+    }
+
+    #[test]
+    fn interned_keys_share_one_allocation() -> Result<(), Box<dyn Error>> {
+        let content = r#"[{"id": 1, "name": "a"}, {"id": 2, "name": "b"}, {"nested": {"id": 3}}]"#;
+        let interning = JsonParser::builder().with_key_interning(true).build();
+        assert!(interning.options().intern_keys());
+
+        let shared = interning.read(content)?;
+        assert!(std::ptr::eq(first_key(&shared[0]), first_key(&shared[1])));
+        assert!(std::ptr::eq(first_key(&shared[0]), first_key(&shared[2]["nested"])));
+
+        let separate = JsonParser::parse(content)?;
+        assert!(!std::ptr::eq(first_key(&separate[0]), first_key(&separate[1])));
+        assert_eq!(shared, separate);
+
+        let reader = interning.read_reader(content.as_bytes())?;
+        assert!(std::ptr::eq(first_key(&reader[0]), first_key(&reader[1])));
+
+        let copy = shared.clone();
+        assert!(std::ptr::eq(first_key(&shared[0]), first_key(&copy[0])));
+        Ok(())
+    }
+
+    #[test]
+    fn lookups_and_iteration_are_unchanged() -> Result<(), Box<dyn Error>> {
+        let content = fs::read_to_string("tests/complex_flats.json")?;
+        let parser = JsonParser::builder().with_key_interning(true).build();
+        let value = parser.read(&content)?;
+        assert_eq!(value, JsonParser::parse(&content)?);
+        assert_eq!(value.to_string(), JsonParser::parse(&content)?.to_string());
+
+        let flat = JsonObject::from(value[0].clone());
+        assert!(flat.get("description").is_some());
+        assert_eq!(flat["listing"]["rooms"], value[0]["listing"]["rooms"]);
+        for (key, field) in flat.iter() {
+            assert_eq!(flat.get(key.as_str()), Some(field));
+        }
+        let keys: Vec<String> = flat.keys().cloned().collect();
+        let owned: Vec<(String, JsonValue)> = flat.clone().into_iter().collect();
+        assert_eq!(keys, owned.into_iter().map(|(key, _)| key).collect::<Vec<_>>());
+        assert_eq!(flat.filter(|key, _| key.starts_with('d')).len(), flat.keys().filter(|key| key.starts_with('d')).count());
+        Ok(())
+    }
+
+    #[test]
+    fn keys_convert_like_strings() -> Result<(), Box<dyn Error>> {
+        let mut object = JsonObject::new();
+        object.set("a", 1);
+        object.set(String::from("b"), 2);
+        object.set('c', 3);
+        let key = String::from("a");
+        object.set(&key, 4);
+        assert_eq!(key, "a");
+
+        assert_eq!(object.len(), 3);
+        assert_eq!(object["a"], JsonValue::from(4));
+        let keys: Vec<&String> = object.keys().collect();
+        assert_eq!(keys, ["a", "b", "c"]);
+        Ok(())
+    }
+}
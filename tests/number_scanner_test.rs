@@ -0,0 +1,88 @@
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+
+    use rusty_json::base::JsonValue;
+    use rusty_json::extra::JsonParser;
+
+    /// Parses `text` and checks that it decodes to the same `f64` as the standard library.
+    fn assert_matches_std(text: &str) -> Result<(), Box<dyn Error>> {
+        let expected: f64 = text.parse()?;
+        let JsonValue::Number(number) = JsonParser::parse(text)? else { panic!("expected a number") };
+        assert_eq!(number.as_f64().to_bits(), expected.to_bits(), "{}", text);
+        Ok(())
+    }
+
+    #[test]
+    fn hard_cases_match_the_standard_library() -> Result<(), Box<dyn Error>> {
+        let cases = [
+            "0.1",
+            "-0.0",
+            "1e22",
+            "1e23",
+            "9007199254740993.0",
+            "9007199254740993e10",
+            "2.2250738585072011e-308",
+            "2.2250738585072014e-308",
+            "4.9406564584124654e-324",
+            "2.4703282292062327e-324",
+            "2.4703282292062328e-324",
+            "1.7976931348623157e308",
+            "1.7976931348623158e308",
+            "1.7976931348623159e308",
+            "1e-400",
+            "1e400",
+            "0.000000000000000000000000000000000000000001e40",
+            "123456789012345678901234567890e-10",
+            "1.00000000000000011102230246251565404236316680908203125",
+            "1.00000000000000011102230246251565404236316680908203124",
+            "1.00000000000000011102230246251565404236316680908203126",
+            "7.3177701707893310e+15",
+            "8.5e-1",
+            "3.0e00",
+        ];
+        for text in cases {
+            assert_matches_std(text)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn random_floats_match_the_standard_library() -> Result<(), Box<dyn Error>> {
+        let mut state: u64 = 0x9E3779B97F4A7C15;
+        for _ in 0..20_000 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let value = f64::from_bits(state);
+            if !value.is_finite() {
+                continue;
+            }
+            let digits = (state % 30) as usize;
+            assert_matches_std(&format!("{:e}", value))?;
+            assert_matches_std(&format!("{:.*e}", digits, value))?;
+            assert_matches_std(&format!("{:.*}", digits, value.abs() % 1e9))?;
+            assert_matches_std(&format!("{}e-{}", state >> 11, state % 330))?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn integers_skip_float_conversion() -> Result<(), Box<dyn Error>> {
+        let value = JsonParser::parse("[9007199254740993, -9007199254740993, 18446744073709551616, 1.0, 1e2]")?;
+        let numbers: Vec<_> = (0..5)
+            .map(|index| match &value[index] {
+                JsonValue::Number(number) => number.clone(),
+                other => panic!("expected a number, found {}", other),
+            })
+            .collect();
+
+        assert_eq!(numbers[0].as_u64(), Some(9007199254740993));
+        assert_eq!(numbers[1].as_i64(), Some(-9007199254740993));
+        assert!(!numbers[2].is_u64());
+        assert_eq!(numbers[2].as_f64(), 18446744073709551616.0);
+        assert!(!numbers[3].is_u64() && !numbers[4].is_u64());
+        assert_eq!(numbers[4].as_f64(), 100.0);
+        Ok(())
+    }
+}
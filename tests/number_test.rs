@@ -0,0 +1,77 @@
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+
+    use rusty_json::base::casting::CastError;
+    use rusty_json::base::{JsonNumber, JsonValue};
+    use rusty_json::extra::{JsonFormatter, JsonParser};
+
+    #[test]
+    fn parse_integers_exactly() -> Result<(), Box<dyn Error>> {
+        let content = r#"[9007199254740993, 18446744073709551615, -9223372036854775808, 0, -0, 1.5, 2e3]"#;
+        let value = JsonParser::parse(content)?;
+
+        assert_eq!(value[0].parse::<u64>()?, 9007199254740993);
+        assert_eq!(value[1].parse::<u64>()?, u64::MAX);
+        assert_eq!(value[2].parse::<i64>()?, i64::MIN);
+
+        let JsonValue::Number(zero) = &value[4] else { panic!("expected a number") };
+        assert!(!zero.is_i64() && !zero.is_u64());
+        assert!(zero.as_f64().is_sign_negative());
+
+        let JsonValue::Number(exponent) = &value[6] else { panic!("expected a number") };
+        assert!(!exponent.is_u64());
+        assert_eq!(exponent.as_f64(), 2000.0);
+
+        let text = value.to_string();
+        assert!(text.starts_with("[9007199254740993, 18446744073709551615, -9223372036854775808, 0, -0, 1.5, "), "{}", text);
+        assert_eq!(JsonParser::parse_reader(content.as_bytes())?, value);
+        Ok(())
+    }
+
+    #[test]
+    fn number_representation() -> Result<(), Box<dyn Error>> {
+        let big = JsonNumber::from(u64::MAX);
+        assert!(big.is_u64() && !big.is_i64() && !big.is_f64());
+        assert_eq!(big.as_u64(), Some(u64::MAX));
+        assert_eq!(big.as_i64(), None);
+
+        let negative = JsonNumber::from(-5i8);
+        assert_eq!((negative.as_i64(), negative.as_u64()), (Some(-5), None));
+
+        assert_eq!(JsonNumber::from(i128::from(i64::MIN)).as_i64(), Some(i64::MIN));
+        assert_eq!(JsonNumber::from(u128::from(u64::MAX)).as_u64(), Some(u64::MAX));
+        assert!(!JsonNumber::from(u128::MAX).is_u64());
+
+        assert_eq!(JsonNumber::from(1), JsonNumber::from(1.0));
+        assert_ne!(JsonNumber::from(9007199254740993u64), JsonNumber::from(9007199254740992u64));
+        assert_ne!(JsonNumber::from(f64::NAN), JsonNumber::from(f64::NAN));
+        Ok(())
+    }
+
+    #[test]
+    fn writers_keep_integers_exact() -> Result<(), Box<dyn Error>> {
+        let value = JsonValue::from(vec![JsonValue::from(u64::MAX), JsonValue::from(i64::MIN), JsonValue::from(0.25)]);
+        assert_eq!(value.to_string(), "[18446744073709551615, -9223372036854775808, 0.25]");
+
+        let formatted = JsonFormatter::default().format(&value);
+        assert!(formatted.contains("18446744073709551615,"));
+        assert_eq!(JsonParser::parse(&formatted)?, value);
+        assert_eq!(JsonValue::from(u64::MAX).parse::<String>()?, "18446744073709551615");
+        Ok(())
+    }
+
+    #[test]
+    fn integer_casts_check_ranges() -> Result<(), Box<dyn Error>> {
+        let value = JsonParser::parse("[300, -1, 1.5, 18446744073709551615, 1e300]")?;
+
+        assert!(matches!(value[0].parse::<u8>(), Err(CastError::OutOfRange)));
+        assert_eq!(value[0].parse::<i16>()?, 300);
+        assert!(matches!(value[1].parse::<u32>(), Err(CastError::OutOfRange)));
+        assert!(matches!(value[2].parse::<i32>(), Err(CastError::InvalidType)));
+        assert!(matches!(value[3].parse::<i64>(), Err(CastError::OutOfRange)));
+        assert_eq!(value[3].parse::<u128>()?, u128::from(u64::MAX));
+        assert!(matches!(value[4].parse::<i128>(), Err(CastError::OutOfRange)));
+        Ok(())
+    }
+}
@@ -0,0 +1,117 @@
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+
+    use rusty_json::base::{DuplicateKeyPolicy, JsonValue};
+    use rusty_json::extra::{Extensions, JsonParser, ParseErrorKind, ParserOptions};
+
+    #[test]
+    fn default_parser_matches_parse() -> Result<(), Box<dyn Error>> {
+        let content = std::fs::read_to_string("tests/complex_flats.json")?;
+        let parser = JsonParser::builder().build();
+
+        assert_eq!(parser.options().max_depth(), Some(ParserOptions::DEFAULT_MAX_DEPTH));
+        assert_eq!(parser.options().duplicate_keys(), DuplicateKeyPolicy::KeepLast);
+        assert_eq!(parser.options().extensions(), Extensions::default());
+        assert_eq!(parser.read(&content)?, JsonParser::parse(&content)?);
+        assert_eq!(parser.read(r#"[+1, .5, 2., "a	b"]"#)?, JsonParser::parse(r#"[+1, .5, 2., "a	b"]"#)?);
+        Ok(())
+    }
+
+    #[test]
+    fn limit_nesting_depth() -> Result<(), Box<dyn Error>> {
+        let parser = JsonParser::builder().with_max_depth(3).build();
+        assert!(parser.read(r#"[{"a": [1]}]"#).is_ok());
+
+        let error = parser.read(r#"[{"a": [[1]]}]"#).unwrap_err();
+        let parse_error = error.parse_error().expect("expected a parse error");
+        assert_eq!(parse_error.kind(), ParseErrorKind::DepthLimitExceeded);
+        assert_eq!(parse_error.column(), 9);
+
+        let error = parser.read_reader(r#"[[[[]]]]"#.as_bytes()).unwrap_err();
+        assert_eq!(error.parse_error().map(|e| e.kind()), Some(ParseErrorKind::DepthLimitExceeded));
+        Ok(())
+    }
+
+    #[test]
+    fn limit_string_length() -> Result<(), Box<dyn Error>> {
+        let parser = JsonParser::builder().with_max_string_length(4).build();
+        assert!(parser.read(r#"{"abcd": "é\n\t"}"#).is_ok());
+
+        for input in [r#"["abcde"]"#, r#"{"abcde": 1}"#, r#"["abé\n"]"#] {
+            let error = parser.read(input).unwrap_err();
+            assert_eq!(error.parse_error().map(|e| e.kind()), Some(ParseErrorKind::StringTooLong), "{}", input);
+            let error = parser.read_reader(input.as_bytes()).unwrap_err();
+            assert_eq!(error.parse_error().map(|e| e.kind()), Some(ParseErrorKind::StringTooLong), "{}", input);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn limit_document_size() -> Result<(), Box<dyn Error>> {
+        let parser = JsonParser::builder().with_max_document_size(10).build();
+        assert!(parser.read(r#"[1, 2, 3] "#).is_ok());
+        assert!(parser.read_reader(r#"[1, 2, 3] "#.as_bytes()).is_ok());
+
+        for input in [r#"[1, 2, 3]  "#, r#"["abcdefghij"]"#] {
+            let error = parser.read(input).unwrap_err();
+            let parse_error = error.parse_error().expect("expected a parse error");
+            assert_eq!(parse_error.kind(), ParseErrorKind::DocumentTooLarge);
+            assert_eq!(parse_error.offset(), 10);
+
+            let error = parser.read_reader(input.as_bytes()).unwrap_err();
+            assert_eq!(error.parse_error(), Some(parse_error));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn duplicate_key_policies() -> Result<(), Box<dyn Error>> {
+        let content = r#"{"a": 1, "b": 2, "a": 3}"#;
+
+        let last = JsonParser::builder().with_duplicate_keys(DuplicateKeyPolicy::KeepLast).build().read(content)?;
+        assert_eq!(last["a"], JsonValue::Number(3.into()));
+        assert_eq!(last.to_string(), JsonParser::parse(content)?.to_string());
+
+        let first = JsonParser::builder().with_duplicate_keys(DuplicateKeyPolicy::KeepFirst).build().read(content)?;
+        assert_eq!(first["a"], JsonValue::Number(1.into()));
+
+        let parser = JsonParser::builder().with_duplicate_keys(DuplicateKeyPolicy::Reject).build();
+        assert!(parser.read(r#"{"a": {"a": 1}, "b": [{"a": 2}]}"#).is_ok());
+        let error = parser.read(content).unwrap_err();
+        let parse_error = error.parse_error().expect("expected a parse error");
+        assert_eq!(parse_error.kind(), ParseErrorKind::DuplicateKey);
+        assert_eq!(parse_error.column(), 18);
+        Ok(())
+    }
+
+    #[test]
+    fn reject_disabled_extensions() -> Result<(), Box<dyn Error>> {
+        let parser = JsonParser::builder().with_extensions(Extensions::NONE).build();
+        assert_eq!(parser.read(r#"[-0.5e+3, 0, 10, "a\tb"]"#)?, JsonParser::parse(r#"[-0.5e+3, 0, 10, "a\tb"]"#)?);
+
+        for input in ["+1", ".5", "1.", "-.5", "01", "-01", "nan", "Infinity", "\"a\tb\"", "[1,]", "1 // note"] {
+            let error = parser.read(input).unwrap_err();
+            assert_eq!(error.parse_error().map(|e| e.kind()), Some(ParseErrorKind::Syntax), "{}", input);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn accept_comments_and_trailing_commas() -> Result<(), Box<dyn Error>> {
+        let parser = JsonParser::builder()
+            .with_extensions(Extensions::COMMENTS | Extensions::TRAILING_COMMAS)
+            .build();
+        let content = "// settings\n{\n  \"a\": [1, 2,], /* inline */\n  \"b\": {\"c\": true,}, // done\n}\n";
+
+        let value = parser.read(content)?;
+        assert_eq!(value, JsonParser::parse(r#"{"a": [1, 2], "b": {"c": true}}"#)?);
+        assert_eq!(parser.read_reader(content.as_bytes())?, value);
+
+        assert!(parser.read("[1,,]").is_err());
+        assert!(parser.read("[1] /* open").is_err());
+        assert!(parser.read("[1] / 2").is_err());
+        assert!(JsonParser::parse("[1] // note").is_err());
+        Ok(())
+    }
+}
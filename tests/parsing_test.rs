@@ -145,7 +145,7 @@ mod test {
         let content = r#"
         {
             "integer": 42,
-            "floating": 3.14
+            "floating": 2.5
         }
         "#;
 
@@ -154,7 +154,7 @@ mod test {
         match parsed {
             JsonValue::Object(ref obj) => {
                 assert_eq!(obj["integer"], JsonValue::Number(42.into()));
-                assert_eq!(obj["floating"], JsonValue::Number(3.14.into()));
+                assert_eq!(obj["floating"], JsonValue::from(2.5));
             }
             _ => panic!("Parsed JSON is not an object"),
         }
@@ -213,4 +213,67 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn parse_error_reports_position() -> Result<(), Box<dyn Error>> {
+        let content = "{\n    \"name\": \"Ammar Dev\",\n    \"age\": tru\n}";
+
+        let error = JsonParser::parse(content).unwrap_err();
+        let parse_error = error.parse_error().expect("expected a parse error");
+
+        assert_eq!(parse_error.line(), 3);
+        assert_eq!(parse_error.column(), 12);
+        assert_eq!(parse_error.offset(), content.find("tru").unwrap());
+        assert_eq!(parse_error.expected(), "a value");
+        assert_eq!(parse_error.found(), "'t'");
+        assert_eq!(parse_error.snippet(), "3 |     \"age\": tru\n  |            ^");
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_error_on_unterminated_array() -> Result<(), Box<dyn Error>> {
+        let error = JsonParser::parse("[1, 2").unwrap_err();
+        let parse_error = error.parse_error().expect("expected a parse error");
+
+        assert_eq!(parse_error.offset(), 5);
+        assert_eq!(parse_error.expected(), "',' or ']'");
+        assert_eq!(parse_error.found(), "end of input");
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_error_on_trailing_characters() -> Result<(), Box<dyn Error>> {
+        let error = JsonParser::parse("{\"a\": 1} x").unwrap_err();
+        let parse_error = error.parse_error().expect("expected a parse error");
+
+        assert_eq!(parse_error.column(), 10);
+        assert_eq!(parse_error.expected(), "end of input");
+        assert_eq!(parse_error.found(), "'x'");
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_error_snippet_is_trimmed_on_long_lines() -> Result<(), Box<dyn Error>> {
+        let content = format!("[{}, ?]", vec!["1"; 500].join(", "));
+
+        let error = JsonParser::parse(&content).unwrap_err();
+        let parse_error = error.parse_error().expect("expected a parse error");
+        let lines: Vec<&str> = parse_error.snippet().lines().collect();
+
+        assert_eq!(parse_error.offset(), content.find('?').unwrap());
+        assert!(lines[0].starts_with("1 | ...") && lines[0].ends_with("?]"));
+        assert_eq!(lines[0].find('?'), lines[1].find('^'));
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_empty_string() -> Result<(), Box<dyn Error>> {
+        let parsed = JsonParser::parse(r#"{"": ""}"#)?;
+        assert_eq!(parsed[""], JsonValue::String(String::new()));
+        Ok(())
+    }
 }
@@ -0,0 +1,190 @@
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+    use std::fs::File;
+    use std::io::BufReader;
+
+    use rusty_json::base::JsonValue;
+    use rusty_json::extra::{JsonEvent, JsonParser, JsonReader, ParseErrorKind};
+
+    #[test]
+    fn read_events_in_order() -> Result<(), Box<dyn Error>> {
+        let content = r#"{"name": "Ammar", "tags": [1, true, null], "empty": {}}"#;
+        let mut reader = JsonReader::from_reader(content.as_bytes());
+
+        let mut events = Vec::new();
+        while let Some(event) = reader.next_event()? {
+            events.push(event);
+        }
+
+        assert_eq!(events, vec![
+            JsonEvent::StartObject,
+            JsonEvent::Key("name".into()),
+            JsonEvent::String("Ammar".into()),
+            JsonEvent::Key("tags".into()),
+            JsonEvent::StartArray,
+            JsonEvent::Number(1.into()),
+            JsonEvent::Boolean(true),
+            JsonEvent::Null,
+            JsonEvent::EndArray,
+            JsonEvent::Key("empty".into()),
+            JsonEvent::StartObject,
+            JsonEvent::EndObject,
+            JsonEvent::EndObject,
+        ]);
+        assert_eq!(reader.next_event()?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn skip_and_materialize_subtrees() -> Result<(), Box<dyn Error>> {
+        let content = r#"[
+            {"id": 1, "blob": {"deep": [[1, 2], {"x": "y\n"}]}, "meta": {"owner": "a"}},
+            {"id": 2, "blob": "short", "meta": {"owner": "b"}}
+        ]"#;
+        let mut reader = JsonReader::from_reader(content.as_bytes());
+
+        let mut ids = Vec::new();
+        let mut metas = Vec::new();
+        while let Some(event) = reader.next_event()? {
+            if let JsonEvent::Key(key) = event {
+                match key.as_ref() {
+                    "id" => ids.push(reader.read_value()?.parse::<i32>()?),
+                    "blob" => reader.skip_value()?,
+                    "meta" => metas.push(reader.read_value()?),
+                    _ => {}
+                }
+            }
+        }
+
+        assert_eq!(ids, vec![1, 2]);
+        assert_eq!(metas[1], JsonParser::parse(r#"{"owner": "b"}"#)?);
+        Ok(())
+    }
+
+    #[test]
+    fn peek_before_reading_elements() -> Result<(), Box<dyn Error>> {
+        let mut reader = JsonReader::from_reader("[[1], [2, 3], []]".as_bytes());
+        assert_eq!(reader.next_event()?, Some(JsonEvent::StartArray));
+
+        let mut lengths = Vec::new();
+        while reader.peek_event()? != Some(&JsonEvent::EndArray) {
+            assert_eq!(reader.depth(), 1);
+            lengths.push(reader.read_value()?.parse::<Vec<i32>>()?.len());
+        }
+
+        assert_eq!(lengths, vec![1, 2, 0]);
+        assert_eq!(reader.next_event()?, Some(JsonEvent::EndArray));
+        assert_eq!(reader.depth(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn scan_large_document() -> Result<(), Box<dyn Error>> {
+        let mut reader = JsonReader::from_reader(File::open("tests/flats.json")?);
+
+        let mut total_rooms = 0;
+        let mut records = 0;
+        while let Some(event) = reader.next_event()? {
+            match event {
+                JsonEvent::Key(key) if key == "rooms" => total_rooms += reader.read_value()?.parse::<u64>()?,
+                JsonEvent::Key(_) => reader.skip_value()?,
+                JsonEvent::StartObject => records += 1,
+                _ => {}
+            }
+        }
+
+        let parsed = JsonParser::parse(&std::fs::read_to_string("tests/flats.json")?)?;
+        let expected: u64 = parsed.clone().into_iter().map(|flat| flat["rooms"].parse::<u64>().unwrap()).sum();
+        assert_eq!(records, parsed.into_iter().count());
+        assert_eq!(total_rooms, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn reader_reports_errors() -> Result<(), Box<dyn Error>> {
+        let mut reader = JsonReader::from_reader(r#"{"a": 1 "b": 2}"#.as_bytes());
+        assert_eq!(reader.next_event()?, Some(JsonEvent::StartObject));
+        assert_eq!(reader.next_event()?, Some(JsonEvent::Key("a".into())));
+        assert_eq!(reader.next_event()?, Some(JsonEvent::Number(1.into())));
+
+        let error = reader.next_event().unwrap_err();
+        let parse_error = error.parse_error().expect("expected a parse error");
+        assert_eq!(parse_error.column(), 9);
+        assert_eq!(parse_error.expected(), "',' or '}'");
+
+        let mut reader = JsonReader::from_reader("[1, 2]".as_bytes());
+        reader.next_event()?;
+        reader.skip_value()?;
+        reader.skip_value()?;
+        assert!(reader.read_value().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn read_scalar_documents() -> Result<(), Box<dyn Error>> {
+        let mut reader = JsonReader::from_reader(" \"text\" ".as_bytes());
+        assert_eq!(reader.read_value()?, JsonValue::String("text".to_string()));
+        assert_eq!(reader.next_event()?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn misplaced_value_reads_report_their_position() -> Result<(), Box<dyn Error>> {
+        let mut reader = JsonReader::from_reader("[1, 2]".as_bytes());
+        reader.next_event()?;
+        reader.skip_value()?;
+        reader.skip_value()?;
+        let error = reader.read_value().unwrap_err();
+        let parse_error = error.parse_error().expect("expected a parse error");
+        assert_eq!((parse_error.line(), parse_error.column()), (1, 6));
+        assert_eq!((parse_error.expected(), parse_error.found()), ("a value", "']'"));
+
+        let mut reader = JsonReader::from_reader("{\n  \"key\": 1\n}".as_bytes());
+        reader.next_event()?;
+        let error = reader.skip_value().unwrap_err();
+        let parse_error = error.parse_error().expect("expected a parse error");
+        assert_eq!((parse_error.line(), parse_error.column()), (2, 3));
+
+        let mut reader = JsonReader::from_reader("1".as_bytes());
+        reader.skip_value()?;
+        assert!(reader.skip_value().unwrap_err().parse_error().is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_utf8_is_reported_at_the_offending_byte() -> Result<(), Box<dyn Error>> {
+        let content = b"[\"a\", \"x\xFFy\"]";
+        let error = JsonParser::parse_reader(&content[..]).unwrap_err();
+        let parse_error = error.parse_error().expect("expected a parse error");
+        assert_eq!(parse_error.kind(), ParseErrorKind::InvalidEncoding);
+        assert_eq!((parse_error.offset(), parse_error.column()), (8, 9));
+
+        let cases: [&[u8]; 5] = [
+            b"[\"x\xFFy\"]",
+            b"[\"\xC3\xA9\xE2\x82\"]",
+            b"{\"\xE2\x82\xAC\xF0\x9F\x98\": 1}",
+            b"[\"\xC3\\n\"]",
+            b"[\"\xE2\x82\xACok\xED\xA0\x80\"]",
+        ];
+        for content in cases {
+            let expected = JsonParser::parse_bytes(content).unwrap_err();
+            let expected = expected.parse_error().map(|e| (e.kind(), e.offset(), e.column()));
+            for capacity in 1..=content.len() {
+                let error = JsonParser::parse_buf_reader(BufReader::with_capacity(capacity, content)).unwrap_err();
+                assert_eq!(error.parse_error().map(|e| (e.kind(), e.offset(), e.column())), expected);
+
+                let mut reader = JsonReader::from_buf_reader(BufReader::with_capacity(capacity, content));
+                let error = reader.skip_value().unwrap_err();
+                assert_eq!(error.parse_error().map(|e| e.offset()), expected.map(|(_, offset, _)| offset));
+            }
+        }
+
+        let valid = "[\"é€😀\", {\"ключ\": \"値\"}]";
+        for capacity in 1..8 {
+            let value = JsonParser::parse_buf_reader(BufReader::with_capacity(capacity, valid.as_bytes()))?;
+            assert_eq!(value, JsonParser::parse(valid)?);
+        }
+        Ok(())
+    }
+}
@@ -2,7 +2,6 @@
 mod test {
     use std::collections::{BTreeMap, HashMap};
     use indexmap::IndexMap;
-    use rusty_json_serialization::JsonEntity;
     use rusty_json::base::{JsonArray, JsonObject, JsonValue};
     use rusty_json::extra::JsonEntity;
 
@@ -0,0 +1,92 @@
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+    use std::fs::File;
+    use std::io::{BufReader, Read};
+
+    use rusty_json::base::JsonValue;
+    use rusty_json::extra::{ConversationError, JsonParser};
+
+    /// A reader that yields its data and then fails.
+    struct FailingReader<'a> {
+        data: &'a [u8],
+    }
+
+    impl Read for FailingReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.data.is_empty() {
+                return Err(std::io::Error::new(std::io::ErrorKind::ConnectionReset, "connection reset"));
+            }
+            let count = self.data.len().min(buf.len());
+            buf[..count].copy_from_slice(&self.data[..count]);
+            self.data = &self.data[count..];
+            Ok(count)
+        }
+    }
+
+    #[test]
+    fn stream_large_json_matches_parse() -> Result<(), Box<dyn Error>> {
+        let content = std::fs::read_to_string("tests/complex_flats.json")?;
+        let streamed = JsonParser::parse_reader(File::open("tests/complex_flats.json")?)?;
+        assert_eq!(streamed, JsonParser::parse(&content)?);
+        Ok(())
+    }
+
+    #[test]
+    fn stream_with_tiny_buffer() -> Result<(), Box<dyn Error>> {
+        let content = r#"
+        {
+            "escaped": "Hello\nWorld \"quoted\" é",
+            "unicode": "こんにちは",
+            "numbers": [1, -2.5, 3e10, .5, +1],
+            "nested": {"empty": [], "object": {}, "null": null, "flags": [true, false]}
+        }
+        "#;
+
+        let reader = BufReader::with_capacity(1, content.as_bytes());
+        let streamed = JsonParser::parse_buf_reader(reader)?;
+
+        assert_eq!(streamed, JsonParser::parse(content)?);
+        assert_eq!(streamed["escaped"], JsonValue::String("Hello\nWorld \"quoted\" é".to_string()));
+        assert_eq!(streamed["unicode"], JsonValue::String("こんにちは".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn stream_reports_syntax_errors_with_position() -> Result<(), Box<dyn Error>> {
+        let content = "[\n  1,\n  2,,\n]";
+
+        let error = JsonParser::parse_reader(content.as_bytes()).unwrap_err();
+        let parse_error = error.parse_error().expect("expected a parse error");
+
+        assert_eq!(parse_error.line(), 3);
+        assert_eq!(parse_error.column(), 5);
+        assert_eq!(parse_error.offset(), 11);
+        assert_eq!(parse_error.expected(), "a value");
+        assert_eq!(parse_error.found(), "','");
+        assert_eq!(parse_error.snippet(), "3 |   2,,\n  |     ^");
+        assert_eq!(JsonParser::parse(content).unwrap_err().parse_error(), Some(parse_error));
+        Ok(())
+    }
+
+    #[test]
+    fn stream_reports_io_errors_separately() -> Result<(), Box<dyn Error>> {
+        let reader = FailingReader { data: br#"{"name": "Ammar"#.as_slice() };
+
+        match JsonParser::parse_reader(reader) {
+            Err(ConversationError::Io(error)) => assert_eq!(error.kind(), std::io::ErrorKind::ConnectionReset),
+            other => panic!("Expected an I/O error, got {:?}", other),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn stream_rejects_trailing_characters() -> Result<(), Box<dyn Error>> {
+        let error = JsonParser::parse_reader(r#"{"a": 1} {"b": 2}"#.as_bytes()).unwrap_err();
+        let parse_error = error.parse_error().expect("expected a parse error");
+
+        assert_eq!(parse_error.offset(), 9);
+        assert_eq!(parse_error.expected(), "end of input");
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+
+    use rusty_json::base::JsonValue;
+    use rusty_json::extra::{JsonParser, ParseErrorKind, SurrogatePolicy};
+
+    #[test]
+    fn decode_surrogate_pairs() -> Result<(), Box<dyn Error>> {
+        let content = r#"{"😀": ["𝄞 clef", "é€"]}"#;
+        let value = JsonParser::parse(content)?;
+
+        assert_eq!(value["😀"][0], JsonValue::String("𝄞 clef".to_string()));
+        assert_eq!(value["😀"][1], JsonValue::String("é€".to_string()));
+        assert_eq!(JsonParser::parse_reader(content.as_bytes())?, value);
+        Ok(())
+    }
+
+    #[test]
+    fn reject_lone_surrogates() -> Result<(), Box<dyn Error>> {
+        let parser = JsonParser::builder().build();
+        assert_eq!(parser.options().lone_surrogates(), SurrogatePolicy::Reject);
+
+        let cases = [
+            (r#"["\ud83d"]"#, 9),
+            (r#"["\ud83dx"]"#, 9),
+            (r#"["\ud83d\n"]"#, 10),
+            (r#"["\ud83dA"]"#, 9),
+            (r#"["\ude00"]"#, 3),
+        ];
+        for (input, column) in cases {
+            let error = parser.read(input).unwrap_err();
+            let parse_error = error.parse_error().expect("expected a parse error");
+            assert_eq!(parse_error.kind(), ParseErrorKind::Syntax, "{}", input);
+            assert_eq!(parse_error.column(), column, "{}", input);
+
+            let error = parser.read_reader(input.as_bytes()).unwrap_err();
+            assert_eq!(error.parse_error(), Some(parse_error));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn replace_lone_surrogates() -> Result<(), Box<dyn Error>> {
+        let parser = JsonParser::builder().with_lone_surrogates(SurrogatePolicy::Replace).build();
+        let cases = [
+            (r#""\ud83d""#, "\u{FFFD}"),
+            (r#""\ud83dx""#, "\u{FFFD}x"),
+            (r#""\ud83d\n""#, "\u{FFFD}\n"),
+            (r#""\ude00\ud83d""#, "\u{FFFD}\u{FFFD}"),
+            (r#""\ud83dA""#, "\u{FFFD}A"),
+            (r#""\ud83d😀""#, "\u{FFFD}😀"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parser.read(input)?, JsonValue::String(expected.to_string()), "{}", input);
+            assert_eq!(parser.read_reader(input.as_bytes())?, JsonValue::String(expected.to_string()), "{}", input);
+        }
+
+        assert!(parser.read(r#""\ud83d\x""#).is_err());
+        assert!(parser.read(r#""\ud83d\u00""#).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,95 @@
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+    use std::error::Error;
+    use std::fs;
+
+    use rusty_json::base::casting::CastError;
+    use rusty_json::base::{DuplicateKeyPolicy, JsonValue};
+    use rusty_json::extra::{ConversationError, JsonParser};
+
+    #[test]
+    fn navigate_without_decoding_everything() -> Result<(), Box<dyn Error>> {
+        let content = r#"{"a": [10, "x\ty", [true], {"b": 18446744073709551615}], "c": null, "d": -0.5}"#;
+        let doc = JsonParser::parse_tape(content)?;
+
+        assert_eq!(doc.get("a")?.at(0)?.parse::<i32>()?, 10);
+        assert_eq!(doc.get("a")?.at(1)?.parse::<String>()?, "x\ty");
+        assert!(doc.get("a")?.at(2)?.at(0)?.parse::<bool>()?);
+        assert_eq!(doc.get("a")?.at(3)?.get("b")?.parse::<u64>()?, u64::MAX);
+        assert_eq!(doc.get("d")?.parse::<f64>()?, -0.5);
+        assert!(doc.get("c")?.is_null());
+        assert_eq!(doc.get("a")?.len(), 4);
+        assert_eq!(doc.root().len(), 3);
+
+        assert!(matches!(doc.get("missing"), Err(CastError::FieldNotFound(key)) if key == "missing"));
+        assert!(matches!(doc.get("a")?.at(4), Err(CastError::OutOfRange)));
+        assert!(matches!(doc.get("c")?.at(0), Err(CastError::InvalidType)));
+        assert!(matches!(doc.at(0), Err(CastError::InvalidType)));
+        Ok(())
+    }
+
+    #[test]
+    fn extraction_matches_json_value() -> Result<(), Box<dyn Error>> {
+        for file in ["tests/flats.json", "tests/complex_flats.json"] {
+            let content = fs::read_to_string(file)?;
+            let doc = JsonParser::parse_tape(&content)?;
+            let value = JsonParser::parse(&content)?;
+
+            assert_eq!(doc.parse::<JsonValue>()?, value);
+            assert_eq!(doc.root().to_json_value(), value);
+            assert_eq!(doc.at(0)?.to_value_ref().to_string(), value[0].to_string());
+            for (index, flat) in doc.root().elements().enumerate().take(100) {
+                assert_eq!(flat.parse::<JsonValue>()?, value[index]);
+                for (key, field) in flat.entries() {
+                    assert_eq!(field.to_json_value(), value[index][key]);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn tape_applies_parser_options() -> Result<(), Box<dyn Error>> {
+        assert!(JsonParser::parse_tape("[1, 2").is_err());
+        assert!(JsonParser::parse_tape("[1] 2").is_err());
+
+        let content = r#"{"k": 1, "k": 2}"#;
+        let first = JsonParser::builder().with_duplicate_keys(DuplicateKeyPolicy::KeepFirst).build();
+        assert_eq!(first.read_tape(content)?.get("k")?.parse::<i32>()?, 1);
+        assert_eq!(JsonParser::parse_tape(content)?.get("k")?.parse::<i32>()?, 2);
+
+        let reject = JsonParser::builder().with_duplicate_keys(DuplicateKeyPolicy::Reject).build();
+        assert!(matches!(reject.read_tape(content), Err(ConversationError::ParsingError(_))));
+        assert!(JsonParser::strict().read_tape("[NaN]").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn collected_keys_match_json_value() -> Result<(), Box<dyn Error>> {
+        let content = r#"{"k": 1, "other": true, "k": [2], "k": {"n": 3}, "once": "x"}"#;
+        let parser = JsonParser::builder().with_duplicate_keys(DuplicateKeyPolicy::Collect).build();
+        let doc = parser.read_tape(content)?;
+        let value = parser.read(content)?;
+
+        let collected = doc.get("k")?;
+        assert!(collected.is_array());
+        assert_eq!(collected.len(), 3);
+        assert_eq!(collected.to_json_value(), value["k"]);
+        assert_eq!(collected.at(2)?.get("n")?.parse::<i32>()?, 3);
+        assert!(matches!(collected.at(3), Err(CastError::OutOfRange)));
+        assert!(matches!(collected.get("n"), Err(CastError::InvalidType)));
+        assert_eq!(collected.elements().count(), 3);
+        assert_eq!(doc.get("once")?.parse::<String>()?, "x");
+        assert_eq!(doc.parse::<JsonValue>()?, value);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_accepts_json_value_conversions() -> Result<(), Box<dyn Error>> {
+        let doc = JsonParser::parse_tape(r#"{"rooms": {"a": 1, "b": 2}}"#)?;
+        let rooms: HashMap<String, i32> = doc.get("rooms")?.parse()?;
+        assert_eq!(rooms, HashMap::from([("a".to_string(), 1), ("b".to_string(), 2)]));
+        Ok(())
+    }
+}
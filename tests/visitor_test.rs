@@ -0,0 +1,175 @@
+#[cfg(test)]
+mod test {
+    use std::borrow::Cow;
+    use std::error::Error;
+    use std::fs::File;
+
+    use rusty_json::base::JsonNumber;
+    use rusty_json::extra::{ConversationError, JsonParser, JsonReader, JsonVisitor};
+
+    /// Counts the tokens of a document without keeping any of them.
+    #[derive(Default)]
+    struct Counter {
+        objects: usize,
+        arrays: usize,
+        keys: usize,
+        scalars: usize,
+        depth: usize,
+        max_depth: usize,
+    }
+
+    impl Counter {
+        fn enter(&mut self) {
+            self.depth += 1;
+            self.max_depth = self.max_depth.max(self.depth);
+        }
+    }
+
+    impl JsonVisitor<'_> for Counter {
+        fn on_object_start(&mut self) -> Result<(), ConversationError> {
+            self.objects += 1;
+            self.enter();
+            Ok(())
+        }
+
+        fn on_object_end(&mut self) -> Result<(), ConversationError> {
+            self.depth -= 1;
+            Ok(())
+        }
+
+        fn on_array_start(&mut self) -> Result<(), ConversationError> {
+            self.arrays += 1;
+            self.enter();
+            Ok(())
+        }
+
+        fn on_array_end(&mut self) -> Result<(), ConversationError> {
+            self.depth -= 1;
+            Ok(())
+        }
+
+        fn on_key(&mut self, _key: Cow<'_, str>) -> Result<(), ConversationError> {
+            self.keys += 1;
+            Ok(())
+        }
+
+        fn on_string(&mut self, _value: Cow<'_, str>) -> Result<(), ConversationError> {
+            self.scalars += 1;
+            Ok(())
+        }
+
+        fn on_number(&mut self, _value: JsonNumber) -> Result<(), ConversationError> {
+            self.scalars += 1;
+            Ok(())
+        }
+
+        fn on_boolean(&mut self, _value: bool) -> Result<(), ConversationError> {
+            self.scalars += 1;
+            Ok(())
+        }
+
+        fn on_null(&mut self) -> Result<(), ConversationError> {
+            self.scalars += 1;
+            Ok(())
+        }
+    }
+
+    /// Collects the values of a single key and rejects negative numbers.
+    struct Collector<'a> {
+        key: &'a str,
+        matched: bool,
+        values: Vec<String>,
+    }
+
+    impl JsonVisitor<'_> for Collector<'_> {
+        fn on_key(&mut self, key: Cow<'_, str>) -> Result<(), ConversationError> {
+            self.matched = key == self.key;
+            Ok(())
+        }
+
+        fn on_string(&mut self, value: Cow<'_, str>) -> Result<(), ConversationError> {
+            if std::mem::take(&mut self.matched) {
+                self.values.push(value.into_owned());
+            }
+            Ok(())
+        }
+
+        fn on_number(&mut self, value: JsonNumber) -> Result<(), ConversationError> {
+            self.matched = false;
+            if value.as_f64() < 0.0 {
+                return Err(ConversationError::GenericError(format!("negative number: {}", value)));
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn count_tokens() -> Result<(), Box<dyn Error>> {
+        let mut counter = Counter::default();
+        JsonParser::visit(r#"{"a": [1, "x", null, {"b": true}], "c": [[]]}"#, &mut counter)?;
+
+        assert_eq!(counter.objects, 2);
+        assert_eq!(counter.arrays, 3);
+        assert_eq!(counter.keys, 3);
+        assert_eq!(counter.scalars, 4);
+        assert_eq!(counter.max_depth, 3);
+        assert_eq!(counter.depth, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn visit_reader_matches_visit() -> Result<(), Box<dyn Error>> {
+        let content = std::fs::read_to_string("tests/complex_flats.json")?;
+
+        let mut from_str = Counter::default();
+        JsonParser::visit(&content, &mut from_str)?;
+        let mut from_reader = Counter::default();
+        JsonParser::visit_reader(File::open("tests/complex_flats.json")?, &mut from_reader)?;
+
+        assert_eq!(from_str.objects, from_reader.objects);
+        assert_eq!(from_str.keys, from_reader.keys);
+        assert_eq!(from_str.scalars, from_reader.scalars);
+        Ok(())
+    }
+
+    #[test]
+    fn collect_and_abort() -> Result<(), Box<dyn Error>> {
+        let mut collector = Collector { key: "name", matched: false, values: Vec::new() };
+        JsonParser::visit(r#"[{"name": "a", "n": 1}, {"other": "b", "name": "c"}]"#, &mut collector)?;
+        assert_eq!(collector.values, vec!["a", "c"]);
+
+        let mut collector = Collector { key: "name", matched: false, values: Vec::new() };
+        let error = JsonParser::visit(r#"[{"name": "a"}, {"n": -1}, {"name": "c"}]"#, &mut collector).unwrap_err();
+        assert!(matches!(error, ConversationError::GenericError(ref message) if message == "negative number: -1"));
+        assert_eq!(collector.values, vec!["a"]);
+        Ok(())
+    }
+
+    #[test]
+    fn visit_reports_syntax_errors() -> Result<(), Box<dyn Error>> {
+        let mut counter = Counter::default();
+        let error = JsonParser::visit("[1, 2] 3", &mut counter).unwrap_err();
+        assert_eq!(error.parse_error().map(|e| e.expected()), Some("end of input"));
+        assert_eq!(counter.scalars, 2);
+
+        let error = JsonParser::visit("[1, tru]", &mut Counter::default()).unwrap_err();
+        let parse_error = error.parse_error().expect("expected a parse error");
+        assert_eq!(parse_error.column(), 5);
+        assert_eq!(parse_error.expected(), "a value");
+        Ok(())
+    }
+
+    #[test]
+    fn visit_single_value_of_reader() -> Result<(), Box<dyn Error>> {
+        let mut reader = JsonReader::from_text(r#"{"skip": [1, 2], "keep": {"a": [true]}}"#);
+        reader.next_event()?;
+        reader.next_event()?;
+        reader.skip_value()?;
+        reader.next_event()?;
+
+        let mut counter = Counter::default();
+        reader.visit_value(&mut counter)?;
+        assert_eq!((counter.objects, counter.arrays, counter.scalars), (1, 1, 1));
+        Ok(())
+    }
+}